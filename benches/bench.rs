@@ -8,6 +8,12 @@ use allocator_api2::{
     vec::Vec,
 };
 
+#[cfg(feature = "bench-with-counting-allocator")]
+use std::{
+    alloc::System,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
 use criterion::*;
 use ring_alloc::*;
 
@@ -141,13 +147,31 @@ fn reset_mem_stat() {
 
 const WARM_UP_SIZE: usize = 65535;
 const VEC_SIZES: [usize; 4] = [10, 146, 2134, 17453];
+const SHORT_LIVED_SIZES: [usize; 3] = [1, 16, 1024];
+const MANY_BOXES_SIZES: [usize; 3] = [1000, 10000, 100000];
+
+const INTERLEAVED_WINDOW: usize = 100;
+const INTERLEAVED_LONG_LIVED_EVERY: usize = 10000;
+const OVERALIGNED_COUNT: usize = 10000;
+const TYPED_COUNT: usize = 10000;
+const HANDLES_COUNT: usize = 10000;
+const BOXED_ITER_COUNT: usize = 10000;
+const MIGRATING_ROUNDS: usize = 100;
+const CHURN_COUNT: usize = 10000;
+const MIGRATING_BOXES: usize = 100;
+const ARENA_BOXES: usize = 10000;
+
+#[cfg(feature = "const-generic-config")]
+const THRESHOLDS_BOXES: usize = 10000;
+#[cfg(feature = "const-generic-config")]
+const THRESHOLDS_MIXED_SIZES: [usize; 8] = [8, 24, 48, 100, 200, 400, 1500, 20000];
 
 fn bench_alloc<A>(
     name: &str,
     c: &mut Criterion,
     mut alloc: A,
     reset: impl Fn(&mut A),
-    shrink_larger_align: bool,
+    _shrink_larger_align: bool,
 ) where
     A: Allocator,
 {
@@ -155,7 +179,7 @@ fn bench_alloc<A>(
 
     reset_mem_stat();
 
-    group.bench_function(format!("alloc"), |b| {
+    group.bench_function("alloc", |b| {
         b.iter(|| {
             let ptr = black_box(alloc.allocate(Layout::new::<u32>()).unwrap());
             unsafe {
@@ -332,6 +356,419 @@ where
     group.finish();
 }
 
+fn bench_many_boxes<A>(name: &str, c: &mut Criterion, mut alloc: A, reset: impl Fn(&mut A))
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("many-boxes/{name}"));
+
+    reset_mem_stat();
+
+    for size in MANY_BOXES_SIZES {
+        let mut boxes = std::vec::Vec::with_capacity(size);
+
+        group.bench_function(format!("Box<u32> x {size}"), |b| {
+            b.iter(|| {
+                // All boxes are alive at the same time.
+                for i in 0..size {
+                    boxes.push(Box::into_raw(Box::new_in(i as u32, &alloc)));
+                }
+                for ptr in boxes.drain(..) {
+                    unsafe { drop(Box::from_raw_in(ptr, &alloc)) };
+                }
+            });
+            reset(&mut alloc);
+        });
+
+        print_mem_stat();
+        reset_mem_stat();
+    }
+
+    group.finish();
+}
+
+/// Type with alignment larger than pointer alignment.
+#[repr(align(32))]
+struct Align32(#[allow(dead_code)] [u64; 4]);
+
+fn bench_overaligned<A>(name: &str, c: &mut Criterion, alloc: A)
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("overaligned/{name}"));
+
+    reset_mem_stat();
+
+    let mut boxes = std::vec::Vec::with_capacity(OVERALIGNED_COUNT);
+
+    group.bench_function(format!("Box<Align32> x {OVERALIGNED_COUNT}"), |b| {
+        b.iter(|| {
+            for i in 0..OVERALIGNED_COUNT {
+                boxes.push(Box::into_raw(Box::new_in(Align32([i as u64; 4]), &alloc)));
+            }
+            for ptr in boxes.drain(..) {
+                unsafe { drop(Box::from_raw_in(ptr, &alloc)) };
+            }
+        });
+    });
+
+    print_mem_stat();
+    reset_mem_stat();
+
+    group.finish();
+}
+
+fn bench_thread_exit<A>(name: &str, c: &mut Criterion, alloc: A)
+where
+    A: Allocator + Copy + Send + 'static,
+{
+    let mut group = c.benchmark_group(format!("thread-exit/{name}"));
+
+    group.bench_function("spawn + alloc tiny, small, large + join", |b| {
+        b.iter(|| {
+            std::thread::spawn(move || {
+                black_box(Box::new_in(0u64, alloc));
+                black_box(Box::new_in([0u64; 8], alloc));
+                black_box(Box::new_in([0u64; 512], alloc));
+            })
+            .join()
+            .unwrap();
+        })
+    });
+
+    group.finish();
+}
+
+/// Allocates and immediately deallocates small blocks.
+/// Compare `OneRingAlloc` with `SingleThreadOneRing`
+/// to see the cost of synchronization.
+fn bench_churn<A>(name: &str, c: &mut Criterion, alloc: A)
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("churn/{name}"));
+
+    group.bench_function(format!("alloc + dealloc u64 x {CHURN_COUNT}"), |b| {
+        b.iter(|| {
+            for _ in 0..CHURN_COUNT {
+                let ptr = alloc.allocate(Layout::new::<u64>()).unwrap();
+                unsafe { alloc.deallocate(black_box(ptr).cast(), Layout::new::<u64>()) };
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// Allocates boxes, drops them all and resets the allocator.
+/// Compare `RingAlloc` with bump-only one to see the cost of deallocation.
+fn bench_arena<A>(name: &str, c: &mut Criterion, mut alloc: A, reset: impl Fn(&mut A))
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("arena/{name}"));
+
+    let mut boxes = std::vec::Vec::with_capacity(ARENA_BOXES);
+
+    group.bench_function(format!("Box<u32> x {ARENA_BOXES} + reset"), |b| {
+        b.iter(|| {
+            for i in 0..ARENA_BOXES {
+                boxes.push(Box::into_raw(Box::new_in(i as u32, &alloc)));
+            }
+            for ptr in boxes.drain(..) {
+                unsafe { drop(Box::from_raw_in(ptr, &alloc)) };
+            }
+            reset(&mut alloc);
+        })
+    });
+
+    group.finish();
+}
+
+/// Allocates many tiny boxes that are alive at the same time.
+/// Returns memory report at peak usage.
+#[cfg(feature = "const-generic-config")]
+fn thresholds_tiny_boxes<C: SizeClasses>(alloc: &RingAlloc<Global, C>) -> MemoryReport {
+    let boxes = (0..THRESHOLDS_BOXES)
+        .map(|i| Box::new_in(i as u32, alloc))
+        .collect::<std::vec::Vec<_>>();
+    let report = alloc.memory_report();
+    drop(black_box(boxes));
+    report
+}
+
+/// Allocates blocks of sizes from bytes to tens of kilobytes
+/// that are alive at the same time.
+/// Returns memory report at peak usage.
+#[cfg(feature = "const-generic-config")]
+fn thresholds_mixed_sizes<C: SizeClasses>(alloc: &RingAlloc<Global, C>) -> MemoryReport {
+    let blocks = (0..THRESHOLDS_BOXES)
+        .map(|i| {
+            let size = THRESHOLDS_MIXED_SIZES[i % THRESHOLDS_MIXED_SIZES.len()];
+            let layout = Layout::from_size_align(size, 8).unwrap();
+            (alloc.allocate(layout).unwrap(), layout)
+        })
+        .collect::<std::vec::Vec<_>>();
+    let report = alloc.memory_report();
+    for (ptr, layout) in black_box(blocks) {
+        unsafe { alloc.deallocate(ptr.cast(), layout) };
+    }
+    report
+}
+
+/// Grows vectors of different sizes side by side.
+/// Returns memory report at peak usage.
+#[cfg(feature = "const-generic-config")]
+fn thresholds_vec_growth<C: SizeClasses>(alloc: &RingAlloc<Global, C>) -> MemoryReport {
+    let mut vecs = VEC_SIZES.map(|_| Vec::new_in(alloc));
+    for i in 0..VEC_SIZES[VEC_SIZES.len() - 1] {
+        for (vec, size) in vecs.iter_mut().zip(VEC_SIZES) {
+            if i < size {
+                vec.push(i as u32);
+            }
+        }
+    }
+    let report = alloc.memory_report();
+    drop(black_box(vecs));
+    report
+}
+
+/// Runs workloads with size class thresholds of `C`
+/// and adds row with throughput and memory overhead of each workload to the table.
+#[cfg(feature = "const-generic-config")]
+fn bench_thresholds<C: SizeClasses>(
+    config: &str,
+    c: &mut Criterion,
+    table: &mut std::vec::Vec<String>,
+) {
+    type Workload<C> = fn(&RingAlloc<Global, C>) -> MemoryReport;
+
+    let mut group = c.benchmark_group(format!("thresholds/{config}"));
+
+    let workloads: [(&str, Workload<C>); 3] = [
+        ("tiny boxes", thresholds_tiny_boxes::<C>),
+        ("mixed sizes", thresholds_mixed_sizes::<C>),
+        ("vec growth", thresholds_vec_growth::<C>),
+    ];
+
+    for (workload, run) in workloads {
+        let alloc = RingAlloc::<Global, C>::with_classes_in(Global);
+        let mut elapsed = std::time::Duration::ZERO;
+        let mut iters = 0;
+
+        group.bench_function(workload, |b| {
+            b.iter_custom(|n| {
+                let start = std::time::Instant::now();
+                for _ in 0..n {
+                    black_box(run(&alloc));
+                }
+                let spent = start.elapsed();
+                elapsed += spent;
+                iters += n;
+                spent
+            })
+        });
+
+        // Chunks are warm after the benchmark, as in steady state.
+        let report = run(&alloc);
+        let micros = elapsed.as_secs_f64() * 1e6 / iters.max(1) as f64;
+        table.push(format!(
+            "| {config} | {workload} | {micros:.1} | {} | {} | {} | {:.1}% |",
+            report.reserved,
+            report.chunks(),
+            report.overhead,
+            report.utilization() * 100.0,
+        ));
+    }
+
+    group.finish();
+}
+
+/// Runs more threads than CPUs, yielding between rounds of allocations,
+/// so that threads migrate between CPUs.
+fn bench_migrating<A>(name: &str, c: &mut Criterion, alloc: A)
+where
+    A: Allocator + Copy + Send + Sync,
+{
+    let mut group = c.benchmark_group(format!("migrating/{name}"));
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()) * 2;
+
+    group.bench_function(
+        format!("{threads} threads x {MIGRATING_ROUNDS} rounds x {MIGRATING_BOXES} boxes"),
+        |b| {
+            b.iter(|| {
+                std::thread::scope(|scope| {
+                    for _ in 0..threads {
+                        scope.spawn(|| {
+                            let mut boxes = std::vec::Vec::with_capacity(MIGRATING_BOXES);
+                            for _ in 0..MIGRATING_ROUNDS {
+                                for i in 0..MIGRATING_BOXES {
+                                    boxes.push(Box::new_in(i as u64, alloc));
+                                }
+                                boxes.clear();
+                                std::thread::yield_now();
+                            }
+                        });
+                    }
+                });
+            })
+        },
+    );
+
+    group.finish();
+}
+
+fn bench_interleaved<A>(name: &str, c: &mut Criterion, alloc: A)
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("interleaved/{name}"));
+
+    reset_mem_stat();
+
+    let mut window = std::collections::VecDeque::with_capacity(INTERLEAVED_WINDOW + 1);
+    let mut long_lived = std::vec::Vec::new();
+
+    group.bench_function("Box<u64> x 100000", |b| {
+        b.iter(|| {
+            // Short-lived boxes are freed in FIFO order,
+            // rare long-lived ones stay alive until the end.
+            for i in 0..100000usize {
+                let ptr = Box::into_raw(Box::new_in(i as u64, &alloc));
+                if i % INTERLEAVED_LONG_LIVED_EVERY == 0 {
+                    long_lived.push(ptr);
+                } else {
+                    window.push_back(ptr);
+                }
+                if window.len() > INTERLEAVED_WINDOW {
+                    let ptr = window.pop_front().unwrap();
+                    unsafe { drop(Box::from_raw_in(ptr, &alloc)) };
+                }
+            }
+            for ptr in window.drain(..).chain(long_lived.drain(..)) {
+                unsafe { drop(Box::from_raw_in(ptr, &alloc)) };
+            }
+        });
+    });
+
+    print_mem_stat();
+    reset_mem_stat();
+
+    group.finish();
+}
+
+fn bench_short_lived<A>(name: &str, c: &mut Criterion, new: impl Fn() -> A)
+where
+    A: Allocator,
+{
+    let mut group = c.benchmark_group(format!("short-lived/{name}"));
+
+    for size in SHORT_LIVED_SIZES {
+        group.bench_function(format!("new + alloc 4 bytes x {size}"), |b| {
+            b.iter(|| {
+                let alloc = new();
+                for _ in 0..size {
+                    black_box(alloc.allocate(Layout::new::<u32>()).unwrap());
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_typed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typed/ring_alloc::RingAlloc");
+
+    let alloc = RingAlloc::new();
+    let mut blocks = std::vec::Vec::with_capacity(TYPED_COUNT);
+
+    group.bench_function(
+        format!("allocate(Layout::new::<u32>()) x {TYPED_COUNT}"),
+        |b| {
+            b.iter(|| {
+                for _ in 0..TYPED_COUNT {
+                    blocks.push(alloc.allocate(Layout::new::<u32>()).unwrap().cast::<u8>());
+                }
+                for ptr in blocks.drain(..) {
+                    unsafe { alloc.deallocate(ptr, Layout::new::<u32>()) };
+                }
+            })
+        },
+    );
+
+    group.bench_function(format!("allocate_for::<u32>() x {TYPED_COUNT}"), |b| {
+        b.iter(|| {
+            for _ in 0..TYPED_COUNT {
+                blocks.push(alloc.allocate_for::<u32>().unwrap().cast::<u8>());
+            }
+            for ptr in blocks.drain(..) {
+                unsafe { alloc.deallocate_for(ptr.cast::<u32>()) };
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_boxed_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("boxed-iter/ring_alloc::RingAlloc");
+
+    let alloc = RingAlloc::new();
+
+    group.bench_function(format!("Box::new_in x {BOXED_ITER_COUNT}"), |b| {
+        b.iter(|| {
+            let mut boxes = Vec::with_capacity_in(BOXED_ITER_COUNT, alloc.clone());
+            for i in 0..BOXED_ITER_COUNT as u64 {
+                boxes.push(Box::new_in(i, alloc.clone()));
+            }
+            black_box(boxes);
+        })
+    });
+
+    group.bench_function(format!("boxed_iter x {BOXED_ITER_COUNT}"), |b| {
+        b.iter(|| black_box(alloc.boxed_iter(0..BOXED_ITER_COUNT as u64)))
+    });
+
+    group.finish();
+}
+
+fn bench_handles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handles/ring_alloc::RingAlloc");
+
+    let alloc = RingAlloc::new();
+
+    group.bench_function(
+        format!("Vec<u32> with cloned allocator x {HANDLES_COUNT}"),
+        |b| {
+            let mut vecs = std::vec::Vec::with_capacity(HANDLES_COUNT);
+            b.iter(|| {
+                for i in 0..HANDLES_COUNT {
+                    let mut vec = Vec::new_in(alloc.clone());
+                    vec.push(i as u32);
+                    vecs.push(vec);
+                }
+                vecs.clear();
+            })
+        },
+    );
+
+    group.bench_function(format!("Vec<u32> with handle x {HANDLES_COUNT}"), |b| {
+        let mut vecs = std::vec::Vec::with_capacity(HANDLES_COUNT);
+        b.iter(|| {
+            for i in 0..HANDLES_COUNT {
+                let mut vec = Vec::new_in(alloc.handle());
+                vec.push(i as u32);
+                vecs.push(vec);
+            }
+            vecs.clear();
+        })
+    });
+
+    group.finish();
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     let mut ring_alloc = RingAlloc::new();
     let mut bump = bumpalo::Bump::new();
@@ -381,6 +818,67 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         false,
     );
 
+    bench_many_boxes("Global", c, Global, |_| {});
+    bench_many_boxes("ring_alloc::RingAlloc", c, ring_alloc.clone(), |_| {});
+
+    #[cfg(feature = "std")]
+    bench_many_boxes("ring_alloc::OneRingAlloc", c, OneRingAlloc, |_| {});
+
+    bench_many_boxes("bumpalo::Bump", c, Bump { bump: &mut bump }, |b| b.reset());
+
+    bench_overaligned("Global", c, Global);
+    bench_overaligned("ring_alloc::RingAlloc", c, ring_alloc.clone());
+
+    #[cfg(feature = "std")]
+    bench_overaligned("ring_alloc::OneRingAlloc", c, OneRingAlloc);
+
+    bench_thread_exit("Global", c, Global);
+
+    // All boxes are dropped before reset.
+    bench_arena("ring_alloc::RingAlloc", c, RingAlloc::new(), |ra| unsafe {
+        ra.reset()
+    });
+    bench_arena(
+        "ring_alloc::RingAlloc (bump-only)",
+        c,
+        RingAlloc::new_bump_only_in(Global),
+        |ra| unsafe { ra.reset() },
+    );
+    bench_arena("bumpalo::Bump", c, Bump { bump: &mut bump }, |b| b.reset());
+
+    bench_churn("Global", c, Global);
+    bench_churn("ring_alloc::RingAlloc", c, ring_alloc.clone());
+
+    #[cfg(feature = "std")]
+    bench_churn("ring_alloc::OneRingAlloc", c, OneRingAlloc);
+
+    // Safety: benchmarks run on the main thread only.
+    #[cfg(feature = "std")]
+    bench_churn("ring_alloc::SingleThreadOneRing", c, unsafe {
+        OneRingAlloc::single_thread()
+    });
+
+    #[cfg(feature = "std")]
+    bench_thread_exit("ring_alloc::OneRingAlloc", c, OneRingAlloc);
+
+    bench_migrating("Global", c, Global);
+
+    #[cfg(feature = "std")]
+    bench_migrating("ring_alloc::OneRingAlloc", c, OneRingAlloc);
+
+    #[cfg(all(feature = "per-cpu", target_os = "linux"))]
+    bench_migrating("ring_alloc::PerCpuOneRing", c, OneRingAlloc::per_cpu());
+
+    bench_interleaved("Global", c, Global);
+    bench_interleaved("ring_alloc::RingAlloc", c, ring_alloc.clone());
+
+    bench_typed(c);
+    bench_boxed_iter(c);
+    bench_handles(c);
+
+    bench_short_lived("ring_alloc::RingAlloc", c, RingAlloc::new);
+    bench_short_lived("ring_alloc::InlineRingAlloc", c, InlineRingAlloc::new);
+
     bench_vec("Global", c, Global, |_| {});
     bench_vec("ring_alloc::RingAlloc", c, ring_alloc.clone(), |_| {});
 
@@ -394,6 +892,29 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         BlinkAlloc { blink: &mut blink },
         |b| b.reset(),
     );
+
+    #[cfg(feature = "const-generic-config")]
+    {
+        let mut table = std::vec::Vec::new();
+
+        bench_thresholds::<Thresholds<8, 256, 65536>>("8/256/65536", c, &mut table);
+        bench_thresholds::<Thresholds<16, 256, 65536>>("16/256/65536 (default)", c, &mut table);
+        bench_thresholds::<Thresholds<32, 256, 65536>>("32/256/65536", c, &mut table);
+        bench_thresholds::<Thresholds<16, 128, 65536>>("16/128/65536", c, &mut table);
+        bench_thresholds::<Thresholds<16, 512, 65536>>("16/512/65536", c, &mut table);
+        bench_thresholds::<Thresholds<16, 1024, 65536>>("16/1024/65536", c, &mut table);
+        bench_thresholds::<Thresholds<16, 256, 16384>>("16/256/16384", c, &mut table);
+        bench_thresholds::<Thresholds<16, 256, 262144>>("16/256/262144", c, &mut table);
+
+        println!();
+        println!(
+            "| thresholds | workload | µs/iter | reserved | chunks | overhead | utilization |"
+        );
+        println!("|---|---|---|---|---|---|---|");
+        for row in table {
+            println!("{row}");
+        }
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
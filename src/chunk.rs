@@ -1,6 +1,7 @@
 use core::{
     alloc::Layout,
     cell::Cell,
+    fmt,
     mem::{align_of, size_of},
     ptr::NonNull,
     sync::atomic::Ordering,
@@ -8,14 +9,244 @@ use core::{
 
 use allocator_api2::alloc::{AllocError, Allocator};
 
-use crate::{addr, cold, with_addr_mut, ImUsize};
+use crate::{addr, cold, with_addr_mut, ImPtr, ImUsize};
 
 #[repr(C)]
-#[derive(Debug)]
-pub(crate) struct Chunk<T, const N: usize> {
-    pub cursor: Cell<*mut u8>,
+pub(crate) struct Chunk<T: ImUsize, const N: usize> {
+    /// Address where next block is allocated.
+    /// Atomic for chunks that can be shared between threads.
+    ///
+    /// Only the thread that owns the chunk moves the cursor.
+    /// Ownership is handed over through global ring's mutex,
+    /// so cursor accesses are `Relaxed`.
+    pub cursor: T::Ptr,
+
+    /// Address below which all bytes are accounted as deallocated
+    /// or consumed by alignment padding.
+    /// `cursor - freed` is the number of live bytes.
+    ///
+    /// Any thread may add to it, but only the owner stores to it.
+    ///
+    /// * Deallocation adds block size with `Release` read-modify-write,
+    ///   publishing all accesses to the block.
+    ///   Block memory and the chunk are not touched afterwards.
+    /// * Owner adds alignment padding with `Relaxed` read-modify-write.
+    ///   Read-modify-writes never lose concurrent updates
+    ///   and don't break release sequences of deallocations.
+    /// * Owner loads it with `Acquire` before reusing memory,
+    ///   so accesses to deallocated blocks happen before their reuse.
+    /// * Owner stores to it only on reset, after it observed `freed == cursor`.
+    ///   No block is live then, so no deallocation can race with the store.
     pub freed: T,
     pub next: Cell<Option<NonNull<Chunk<T, N>>>>,
+    pub prev: Cell<Option<NonNull<Chunk<T, N>>>>,
+
+    /// Size class of the chunk, always `N`.
+    /// Unlike `capacity` it never changes, so blocks that grew beyond
+    /// size class of their chunk are routed back to it on deallocation.
+    /// Aligned chunks find that from the address and keep the field
+    /// only to verify size class in debug builds.
+    #[cfg(any(debug_assertions, not(feature = "aligned-chunks")))]
+    pub size: usize,
+
+    /// Address where chunk memory begins, right after the header
+    /// rounded up to the minimum alignment chunk is created with.
+    pub base: usize,
+
+    /// Number of bytes of memory the chunk occupies, header included.
+    /// Equals `N` unless chunk is allocated with larger capacity
+    /// or its unused tail is trimmed.
+    pub capacity: Cell<usize>,
+
+    /// Memory above this address and the cursor is known to be zeroed.
+    /// Cursor may move backwards on reset, leaving stale bytes behind,
+    /// so the mark is raised to the cursor whenever that happens.
+    pub dirty: Cell<usize>,
+
+    /// Number of blocks allocated from the chunk and not deallocated yet.
+    /// Maintained only by local rings, whose chunks are never shared.
+    pub blocks: Cell<usize>,
+
+    /// Number of bytes skipped to align blocks since last reset.
+    /// Only the thread that owns the chunk updates it.
+    pub padding: Cell<usize>,
+
+    /// Cursor position recorded by last [`Chunk::commit`].
+    /// Blocks below it survive [`Chunk::reset_all`].
+    /// Equals `base` when chunk has no committed blocks.
+    pub floor: Cell<usize>,
+
+    /// Number of bytes of committed blocks that are not deallocated yet.
+    pub floor_live: Cell<usize>,
+
+    /// Number of committed blocks that are not deallocated yet.
+    pub floor_blocks: Cell<usize>,
+
+    /// Allocator the chunk was allocated from instead of the underlying one,
+    /// after the underlying allocator failed to provide new chunk.
+    /// Chunk is returned to it when freed.
+    pub overflow: Option<&'static dyn Allocator>,
+
+    /// Id of the thread that donated the chunk to global ring last time,
+    /// `0` if the chunk was never donated.
+    /// Updated by the owner before donation, read under global ring's lock.
+    #[cfg(feature = "donor-tracking")]
+    pub donor: Cell<usize>,
+
+    /// Number of times all blocks of the chunk were forcibly deallocated
+    /// with [`Chunk::reset_all`].
+    /// Blocks record it in their metadata to catch their use after reset.
+    #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+    pub generation: Cell<usize>,
+
+    /// Metadata of the most recently allocated live block.
+    /// Live blocks are linked through their metadata,
+    /// so their allocation sites can be listed.
+    /// Maintained only by local rings, whose chunks are never shared.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    pub live_list: Cell<Option<NonNull<u8>>>,
+
+    /// Cursor does not advance past this address.
+    /// Equals end of the chunk until cursor wraps to the beginning.
+    #[cfg(feature = "front-reuse")]
+    pub limit: Cell<usize>,
+
+    /// Number of bytes allocated from each segment since last reset.
+    /// Blocks are attributed to the segment where they start.
+    #[cfg(feature = "front-reuse")]
+    pub segment_used: [Cell<usize>; SEGMENTS],
+
+    /// Number of bytes deallocated from each segment since last reset.
+    #[cfg(feature = "front-reuse")]
+    pub segment_freed: [T; SEGMENTS],
+}
+
+// Shows memory range of the chunk and how its bytes are spent
+// instead of raw cursor and counter values.
+// Never panics, so it is safe to use in panic messages of broken chunks.
+impl<T, const N: usize> fmt::Debug for Chunk<T, N>
+where
+    T: ImUsize,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cursor = addr(self.cursor().load(Ordering::Relaxed));
+        let freed = self.freed().load(Ordering::Acquire);
+
+        f.debug_struct("Chunk")
+            .field("base_addr", &format_args!("{:#x}", self.base_addr()))
+            .field("end_addr", &format_args!("{:#x}", self.end_addr()))
+            .field("used", &cursor.saturating_sub(self.base_addr()))
+            .field("live", &cursor.saturating_sub(freed))
+            .field("reclaimable", &freed.saturating_sub(self.base_addr()))
+            .field("free", &self.end_addr().saturating_sub(cursor))
+            .field("unused", &(cursor == freed))
+            .field("next", &self.next.get())
+            .field("prev", &self.prev.get())
+            .finish()
+    }
+}
+
+/// Metadata written before each block.
+#[cfg(not(feature = "aligned-chunks"))]
+#[repr(C)]
+struct BlockMeta<C> {
+    /// Chunk the block is allocated from.
+    chunk: NonNull<C>,
+
+    /// Generation of the chunk when block was allocated.
+    #[cfg(feature = "debug-validate")]
+    generation: usize,
+
+    /// Where the block was allocated.
+    #[cfg(all(feature = "debug-introspect", debug_assertions))]
+    location: &'static core::panic::Location<'static>,
+
+    /// Neighbours in the list of live blocks of the chunk.
+    #[cfg(all(feature = "debug-introspect", debug_assertions))]
+    prev: Cell<Option<NonNull<u8>>>,
+
+    #[cfg(all(feature = "debug-introspect", debug_assertions))]
+    next: Cell<Option<NonNull<u8>>>,
+}
+
+/// Number of segments chunk is split into to track which parts of it
+/// have no live blocks.
+#[cfg(feature = "front-reuse")]
+const SEGMENTS: usize = 8;
+
+/// Distance between bytes written by [`Chunk::prefault`].
+/// Smallest page size of supported platforms.
+const PREFAULT_STRIDE: usize = 4096;
+
+/// Number of guard bytes before and after each block.
+#[cfg(feature = "debug-redzone")]
+pub(crate) const REDZONE_SIZE: usize = 16;
+
+/// Value of guard bytes.
+#[cfg(feature = "debug-redzone")]
+const REDZONE_PATTERN: u8 = 0xAB;
+
+/// Returns layout of user's memory surrounded by guard bytes
+/// and offset of the user's memory in it.
+#[cfg(feature = "debug-redzone")]
+#[inline(always)]
+fn redzone_layout(layout: Layout) -> Option<(Layout, usize)> {
+    let redzone = Layout::from_size_align(REDZONE_SIZE, 1).ok()?;
+    let (with_front, offset) = redzone.extend(layout).ok()?;
+    let (with_both, _) = with_front.extend(redzone).ok()?;
+    Some((with_both, offset))
+}
+
+/// Fills guard bytes around block of `size` bytes.
+///
+/// # Safety
+///
+/// `ptr` must be allocated with layout of `size` bytes
+/// surrounded by guard bytes.
+#[cfg(feature = "debug-redzone")]
+#[inline(always)]
+unsafe fn fill_redzones(ptr: *mut u8, size: usize) {
+    unsafe {
+        ptr.sub(REDZONE_SIZE)
+            .write_bytes(REDZONE_PATTERN, REDZONE_SIZE);
+        ptr.add(size).write_bytes(REDZONE_PATTERN, REDZONE_SIZE);
+    }
+}
+
+/// Checks that guard bytes around block of `size` bytes are intact.
+/// Panics if they are overwritten.
+///
+/// # Safety
+///
+/// `ptr` must be allocated with layout of `size` bytes
+/// surrounded by guard bytes.
+#[cfg(feature = "debug-redzone")]
+#[inline(always)]
+unsafe fn check_redzones(ptr: *mut u8, size: usize) {
+    // Safety: guard bytes are within the block.
+    let (before, after) = unsafe {
+        (
+            core::slice::from_raw_parts(ptr.sub(REDZONE_SIZE), REDZONE_SIZE),
+            core::slice::from_raw_parts(ptr.add(size), REDZONE_SIZE),
+        )
+    };
+
+    if before.iter().any(|&b| b != REDZONE_PATTERN) {
+        panic!(
+            "Memory before block {:p} of {} bytes is corrupted: {:x?}",
+            ptr, size, before
+        );
+    }
+    if after.iter().any(|&b| b != REDZONE_PATTERN) {
+        panic!(
+            "Memory after block {:p} of {} bytes is corrupted: {:x?}",
+            ptr, size, after
+        );
+    }
 }
 
 impl<T, const N: usize> Chunk<T, N>
@@ -24,13 +255,23 @@ where
 {
     const SIZE: usize = N;
 
+    #[cfg(not(feature = "aligned-chunks"))]
     const ALIGNMENT: usize = align_of::<Self>();
 
+    /// Chunks are aligned to their size,
+    /// so chunk of a block is found by masking its address.
+    #[cfg(feature = "aligned-chunks")]
+    const ALIGNMENT: usize = N;
+
     const LAYOUT: Layout = match Layout::from_size_align(Self::SIZE, Self::ALIGNMENT) {
         Ok(layout) => layout,
         Err(_) => panic!("Invalid chunk size"),
     };
 
+    /// Chunk capacity is trimmed in multiples of this size.
+    /// Trimmed chunks end on a segment boundary when `front-reuse` is enabled.
+    const TRIM_GRANULE: usize = N / 8;
+
     const LAYOUT_IS_VALID: bool = {
         if Self::SIZE < size_of::<Self>() {
             panic!("Chunk size is too small");
@@ -38,24 +279,133 @@ where
         if Self::ALIGNMENT < align_of::<Self>() {
             panic!("Chunk alignment is too small");
         }
+        if cfg!(feature = "aligned-chunks") && !Self::SIZE.is_power_of_two() {
+            panic!("Chunk size must be power of two");
+        }
         true
     };
 
     pub fn new<'a, A>(alloc: A) -> Result<NonNull<Self>, AllocError>
+    where
+        A: Allocator + 'a,
+    {
+        Self::_new(alloc, N, 1, false)
+    }
+
+    /// Allocates chunk that occupies at least `capacity` bytes.
+    ///
+    /// Capacity is never less than `N` and is rounded up to multiple of `N / 8`.
+    /// Chunk belongs to the same size class as chunks of `N` bytes
+    /// and can hold blocks larger than those.
+    ///
+    /// With `aligned-chunks` feature capacity can't exceed `N`,
+    /// since blocks are found by masking their address.
+    ///
+    /// Memory begins at address aligned to `min_align`,
+    /// so blocks with alignment up to `min_align` need no padding after reset.
+    /// `min_align` must be power of two.
+    ///
+    /// With `zeroed` memory is zeroed by underlying allocator,
+    /// so that [`Chunk::allocate_zeroed`] doesn't need to zero fresh blocks.
+    pub fn with_capacity<'a, A>(
+        alloc: A,
+        capacity: usize,
+        min_align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<Self>, AllocError>
+    where
+        A: Allocator + 'a,
+    {
+        let capacity = capacity
+            .max(N)
+            .checked_add(Self::TRIM_GRANULE - 1)
+            .ok_or(AllocError)?
+            & !(Self::TRIM_GRANULE - 1);
+
+        if cfg!(feature = "aligned-chunks") && capacity > N {
+            return Err(AllocError);
+        }
+
+        Self::_new(alloc, capacity, min_align, zeroed)
+    }
+
+    #[inline(always)]
+    fn _new<'a, A>(
+        alloc: A,
+        capacity: usize,
+        min_align: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<Self>, AllocError>
     where
         A: Allocator + 'a,
     {
         debug_assert!(Self::LAYOUT_IS_VALID);
+        debug_assert!(capacity >= N);
+        debug_assert!(min_align.is_power_of_two());
 
-        let ptr = alloc.allocate(Self::LAYOUT)?.cast::<Self>();
+        // Header is padded to `min_align` at most.
+        if size_of::<Self>() + min_align > capacity {
+            return Err(AllocError);
+        }
+
+        let layout = if capacity == N {
+            Self::LAYOUT
+        } else {
+            Layout::from_size_align(capacity, Self::ALIGNMENT).map_err(|_| AllocError)?
+        };
+
+        let ptr = if zeroed {
+            alloc.allocate_zeroed(layout)?.cast::<Self>()
+        } else {
+            alloc.allocate(layout)?.cast::<Self>()
+        };
         let memory = unsafe { ptr.as_ptr().add(1).cast::<u8>() };
+        let base = (addr(memory) + min_align - 1) & !(min_align - 1);
+
+        // Safety: `base` is within the chunk memory as checked above.
+        let memory = unsafe { with_addr_mut(memory, base) };
+
+        // Contents of memory not zeroed by the allocator are unknown.
+        let dirty = if zeroed {
+            addr(memory)
+        } else {
+            addr(ptr.as_ptr()) + capacity
+        };
 
         // Safety: Writing into memory allocated for `Chunk`.
         unsafe {
             ptr.as_ptr().write(Chunk {
-                cursor: Cell::new(memory),
+                cursor: T::Ptr::new(memory),
                 freed: T::new(addr(memory)),
                 next: Cell::new(None),
+                prev: Cell::new(None),
+                #[cfg(any(debug_assertions, not(feature = "aligned-chunks")))]
+                size: N,
+                base,
+                capacity: Cell::new(capacity),
+                dirty: Cell::new(dirty),
+                blocks: Cell::new(0),
+                padding: Cell::new(0),
+                floor: Cell::new(base),
+                floor_live: Cell::new(0),
+                floor_blocks: Cell::new(0),
+                overflow: None,
+                #[cfg(feature = "donor-tracking")]
+                donor: Cell::new(0),
+                #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+                generation: Cell::new(0),
+                #[cfg(all(
+                    feature = "debug-introspect",
+                    debug_assertions,
+                    not(feature = "aligned-chunks")
+                ))]
+                live_list: Cell::new(None),
+                #[cfg(feature = "front-reuse")]
+                limit: Cell::new(addr(ptr.as_ptr()) + capacity),
+                #[cfg(feature = "front-reuse")]
+                segment_used: [(); SEGMENTS].map(|()| Cell::new(0)),
+                #[cfg(feature = "front-reuse")]
+                segment_freed: [(); SEGMENTS].map(|()| T::new(0)),
             });
         }
 
@@ -65,27 +415,191 @@ where
     /// # Safety
     ///
     /// `ptr` must be valid pointer to `Self` allocated by `alloc` using same allocator
-    /// or compatible one.
+    /// or compatible one, unless chunk was allocated from overflow allocator.
     pub unsafe fn free<A>(ptr: NonNull<Self>, alloc: A)
     where
         A: Allocator,
     {
-        // Safety: `ptr` is valid pointer to `Self` allocated by `alloc`.
+        // Safety: `ptr` is valid pointer to `Self`.
+        let (capacity, overflow) = unsafe { (ptr.as_ref().capacity.get(), ptr.as_ref().overflow) };
+
+        // Safety: `capacity` is a multiple of alignment.
+        let layout = unsafe { Layout::from_size_align_unchecked(capacity, Self::ALIGNMENT) };
+
+        // Safety: `ptr` is valid pointer to `Self` allocated by `alloc`
+        // or by its overflow allocator and shrunk to `capacity` bytes.
         unsafe {
-            alloc.deallocate(ptr.cast(), Self::LAYOUT);
+            match overflow {
+                Some(overflow) => overflow.deallocate(ptr.cast(), layout),
+                None => alloc.deallocate(ptr.cast(), layout),
+            }
         }
     }
 
+    /// Unwraps result of the first allocation from a fresh chunk.
+    ///
+    /// Size classes guarantee that any routed layout fits fresh chunk,
+    /// still failure is reported instead of panicking.
+    /// On failure the chunk is freed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid pointer to `Self` allocated by `alloc`
+    /// and not linked into any ring.
+    #[inline(always)]
+    pub unsafe fn fresh_block<A>(
+        ptr: NonNull<Self>,
+        block: Option<NonNull<u8>>,
+        alloc: A,
+    ) -> Result<NonNull<u8>, AllocError>
+    where
+        A: Allocator,
+    {
+        match block {
+            Some(block) => Ok(block),
+            None => {
+                // Safety: `ptr` is allocated by `alloc` and not linked.
+                unsafe { Self::free(ptr, alloc) };
+                Err(AllocError)
+            }
+        }
+    }
+
+    /// Reserves memory for `layout` at the beginning of fresh chunk
+    /// and moves beginning of the chunk memory past it.
+    ///
+    /// Reserved memory is not a block, resets never rewind the cursor over it
+    /// and it is released only together with the chunk.
+    /// Returns `None` if it doesn't leave room for blocks in the chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid pointer to `Self` that has no blocks allocated
+    /// and is not shared.
+    pub unsafe fn reserve_front(ptr: NonNull<Self>, layout: Layout) -> Option<NonNull<u8>> {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let chunk = unsafe { &mut *ptr.as_ptr() };
+        debug_assert_eq!(addr(chunk.cursor.load(Ordering::Relaxed)), chunk.base);
+
+        let word = align_of::<usize>();
+        let start = chunk.base.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let base = start.checked_add(layout.size())?.checked_add(word - 1)? & !(word - 1);
+        if base >= chunk.end_addr() {
+            return None;
+        }
+
+        let cursor = chunk.cursor.load(Ordering::Relaxed);
+
+        // Safety: `start` and `base` are within the chunk memory.
+        let (start, cursor) =
+            unsafe { (with_addr_mut(cursor, start), with_addr_mut(cursor, base)) };
+
+        chunk.base = base;
+        chunk.floor.set(base);
+        chunk.cursor.store(cursor, Ordering::Relaxed);
+        chunk.freed.store(base, Ordering::Relaxed);
+        chunk.raise_dirty();
+
+        // Safety: `start` is within the chunk memory.
+        Some(unsafe { NonNull::new_unchecked(start) })
+    }
+
+    /// Returns unused tail of the chunk after the cursor to the underlying allocator.
+    ///
+    /// Memory up to the cursor, rounded up to `N / 8` bytes, is kept.
+    /// Returns number of bytes returned, or zero if tail is too small
+    /// or `alloc` fails to shrink the chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid pointer to `Self` allocated by `alloc`.
+    /// `alloc` must shrink blocks in place.
+    pub unsafe fn trim<A>(ptr: NonNull<Self>, alloc: A) -> usize
+    where
+        A: Allocator,
+    {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let chunk = unsafe { ptr.as_ref() };
+
+        // Overflow allocator is not known to shrink in place.
+        if chunk.overflow.is_some() {
+            return 0;
+        }
+
+        let capacity = chunk.capacity.get();
+        let used = addr(chunk.cursor().load(Ordering::Relaxed)) - chunk.chunk_addr();
+        let new_capacity = (used + Self::TRIM_GRANULE - 1) & !(Self::TRIM_GRANULE - 1);
+
+        if new_capacity >= capacity {
+            return 0;
+        }
+
+        // Cursor may have wrapped leaving live blocks at the end of the chunk.
+        #[cfg(feature = "front-reuse")]
+        if !(chunk.segment_of(chunk.chunk_addr() + new_capacity)..SEGMENTS)
+            .all(|segment| chunk.segment_unused(segment))
+        {
+            return 0;
+        }
+
+        // Safety: Both sizes are multiples of alignment.
+        let (old_layout, new_layout) = unsafe {
+            (
+                Layout::from_size_align_unchecked(capacity, Self::ALIGNMENT),
+                Layout::from_size_align_unchecked(new_capacity, Self::ALIGNMENT),
+            )
+        };
+
+        // Safety: `ptr` is allocated by `alloc` with `old_layout`
+        // and new size is smaller.
+        match unsafe { alloc.shrink(ptr.cast(), old_layout, new_layout) } {
+            Ok(new_ptr) => {
+                debug_assert_eq!(
+                    new_ptr.cast::<u8>(),
+                    ptr.cast::<u8>(),
+                    "Chunk is moved by shrink"
+                );
+            }
+            Err(_) => return 0,
+        }
+
+        chunk.capacity.set(new_capacity);
+
+        #[cfg(feature = "front-reuse")]
+        chunk.limit.set(chunk.limit.get().min(chunk.end_addr()));
+
+        capacity - new_capacity
+    }
+
     fn chunk_addr(&self) -> usize {
         addr(self as *const Self)
     }
 
     fn base_addr(&self) -> usize {
-        self.chunk_addr() + size_of::<Self>()
+        self.base
     }
 
     fn end_addr(&self) -> usize {
-        self.chunk_addr() + N
+        self.chunk_addr() + self.capacity.get()
+    }
+
+    /// Checks if address is within chunk memory.
+    #[inline(always)]
+    pub fn contains(&self, addr: usize) -> bool {
+        self.base_addr() <= addr && addr < self.end_addr()
+    }
+
+    /// Returns address cursor may not advance past.
+    #[inline(always)]
+    fn limit_addr(&self) -> usize {
+        #[cfg(feature = "front-reuse")]
+        {
+            self.limit.get()
+        }
+        #[cfg(not(feature = "front-reuse"))]
+        {
+            self.end_addr()
+        }
     }
 
     // unsafe fn with_addr(&self, addr: usize) -> *mut u8 {
@@ -98,7 +612,7 @@ where
     }
 
     /// Returns cursor position in the chunk.
-    fn cursor(&self) -> &Cell<*mut u8> {
+    fn cursor(&self) -> &T::Ptr {
         &self.cursor
     }
 
@@ -112,6 +626,43 @@ impl<T, const N: usize> Chunk<T, N>
 where
     T: ImUsize,
 {
+    /// Returns number of bytes consumed by allocations since last reset.
+    #[inline(always)]
+    pub fn used(&self) -> usize {
+        addr(self.cursor().load(Ordering::Relaxed)) - self.base_addr()
+    }
+
+    /// Returns number of bytes occupied by blocks that are not deallocated yet.
+    ///
+    /// If chunk is potentially shared, the value may be outdated.
+    #[inline(always)]
+    pub fn live(&self) -> usize {
+        addr(self.cursor().load(Ordering::Relaxed)) - self.freed().load(Ordering::Acquire)
+    }
+
+    /// Writes zero to one byte per page of memory after the cursor,
+    /// so that pages are faulted in before blocks are allocated there.
+    ///
+    /// Headers and allocated blocks are not touched.
+    /// Memory above dirty mark stays zeroed.
+    ///
+    /// # Safety
+    ///
+    /// Chunk must not be shared with other threads.
+    #[inline]
+    pub unsafe fn prefault(&self) {
+        let cursor = self.cursor().load(Ordering::Relaxed);
+        let limit = self.limit_addr();
+
+        let mut page = addr(cursor);
+        while page < limit {
+            // Safety: memory between cursor and limit is not allocated.
+            // Writing zero keeps memory above dirty mark zeroed.
+            unsafe { with_addr_mut(cursor, page).write_volatile(0) };
+            page = (page + PREFAULT_STRIDE) & !(PREFAULT_STRIDE - 1);
+        }
+    }
+
     /// Checks if chunk is unused.
     /// This state can be changed by calling `allocate`.
     ///
@@ -119,7 +670,7 @@ where
     /// while another thread is allocating from this chunk.
     #[inline(always)]
     pub fn unused(&self) -> bool {
-        self.freed().load(Ordering::Acquire) == addr(self.cursor().get())
+        self.freed().load(Ordering::Acquire) == addr(self.cursor().load(Ordering::Relaxed))
     }
 
     /// Resets chunk to unused state.
@@ -129,13 +680,31 @@ where
     /// and returns `true`.
     #[inline(always)]
     pub fn reset(&self) -> bool {
-        let mut cursor = self.cursor().get();
+        let mut cursor = self.cursor().load(Ordering::Relaxed);
         if self.freed().load(Ordering::Acquire) == addr(cursor) {
+            self.raise_dirty();
+
             // Safety: base_addr is beginning of the chunk memory
             // and cursor is within the chunk memory.
             cursor = unsafe { with_addr_mut(cursor, self.base_addr()) };
+
+            // No live blocks, so no deallocation races with this store.
             self.freed().store(addr(cursor), Ordering::Relaxed);
-            self.cursor().set(cursor);
+            self.cursor().store(cursor, Ordering::Relaxed);
+            self.padding.set(0);
+
+            // Unused chunk has no committed blocks left.
+            debug_assert_eq!(self.floor_live.get(), 0);
+            self.floor.set(self.base_addr());
+
+            #[cfg(feature = "front-reuse")]
+            {
+                self.limit.set(self.end_addr());
+                for (used, freed) in self.segment_used.iter().zip(&self.segment_freed) {
+                    used.set(0);
+                    freed.store(0, Ordering::Relaxed);
+                }
+            }
             true
         } else {
             cold();
@@ -143,50 +712,523 @@ where
         }
     }
 
+    /// Marks all blocks allocated from the chunk as deallocated
+    /// and resets the chunk.
+    ///
+    /// If chunk has committed blocks, they stay allocated
+    /// and cursor is rewound to the floor instead.
+    ///
+    /// # Safety
+    ///
+    /// Blocks allocated from the chunk after last commit must not be used afterwards.
+    /// Chunk must not be shared with other threads.
     #[inline(always)]
-    fn _allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
-        let cursor = self.cursor().get();
+    pub unsafe fn reset_all(&self) {
+        if self.committed() {
+            self.raise_dirty();
+
+            let floor = self.floor.get();
+            let cursor = self.cursor().load(Ordering::Relaxed);
+
+            // Keep `cursor - freed` equal to number of live bytes.
+            self.freed()
+                .store(floor - self.floor_live.get(), Ordering::Relaxed);
+
+            // Safety: floor is within the chunk memory below the cursor.
+            self.cursor()
+                .store(unsafe { with_addr_mut(cursor, floor) }, Ordering::Relaxed);
+            self.blocks.set(self.floor_blocks.get());
+
+            // Only committed blocks stay listed.
+            #[cfg(all(
+                feature = "debug-introspect",
+                debug_assertions,
+                not(feature = "aligned-chunks")
+            ))]
+            {
+                let mut meta_ptr = self.live_list.get();
+                while let Some(ptr) = meta_ptr {
+                    // Safety: metadata of listed blocks is intact until they are unlisted.
+                    meta_ptr = unsafe { Self::meta_at(ptr) }.next.get();
+                    if addr(ptr.as_ptr()) >= floor {
+                        // Safety: block is listed in this chunk.
+                        unsafe { self.unlist(ptr) };
+                    }
+                }
+            }
+            return;
+        }
+
+        self.blocks.set(0);
+
+        #[cfg(all(
+            feature = "debug-introspect",
+            debug_assertions,
+            not(feature = "aligned-chunks")
+        ))]
+        self.live_list.set(None);
+
+        #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+        self.generation.set(self.generation.get().wrapping_add(1));
+
+        self.freed().store(
+            addr(self.cursor().load(Ordering::Relaxed)),
+            Ordering::Relaxed,
+        );
+        let reset = self.reset();
+        debug_assert!(reset);
+    }
+
+    /// Makes blocks allocated from the chunk so far survive [`Chunk::reset_all`].
+    ///
+    /// Chunk without live blocks is reset instead.
+    /// Chunk must not be shared with other threads.
+    #[inline(always)]
+    pub fn commit(&self) {
+        if self.reset() {
+            return;
+        }
 
+        let cursor = addr(self.cursor().load(Ordering::Relaxed));
+        self.floor.set(cursor);
+        self.floor_live
+            .set(cursor - self.freed().load(Ordering::Relaxed));
+        self.floor_blocks.set(self.blocks.get());
+    }
+
+    /// Checks if chunk holds committed blocks.
+    #[inline(always)]
+    pub fn committed(&self) -> bool {
+        self.floor_live.get() != 0
+    }
+
+    /// Accounts deallocation of the block if it is committed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from this chunk with `layout`.
+    #[inline(always)]
+    pub unsafe fn release_committed(&self, ptr: *mut u8, layout: Layout) {
+        let (meta_layout, offset) = Self::block_layout(layout).unwrap();
+
+        // Committed blocks end at the floor at most.
+        if addr(ptr) - offset < self.floor.get() {
+            self.floor_live
+                .set(self.floor_live.get() - meta_layout.size());
+            self.floor_blocks.set(self.floor_blocks.get() - 1);
+        }
+    }
+
+    /// Adds block to the list of live blocks of the chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from this chunk with `layout` and not listed yet.
+    /// Chunk must not be shared with other threads.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    #[inline(always)]
+    pub unsafe fn list_live(&self, ptr: *mut u8, layout: Layout) {
+        let (_, offset) = Self::block_layout(layout).unwrap();
+
+        // Safety: metadata is written before the block by `allocate`.
+        let meta_ptr = unsafe { NonNull::new_unchecked(ptr.sub(offset)) };
+        let meta = unsafe { Self::meta_at(meta_ptr) };
+
+        let head = self.live_list.replace(Some(meta_ptr));
+        meta.next.set(head);
+        if let Some(head) = head {
+            // Safety: metadata of listed blocks is intact until they are unlisted.
+            unsafe { Self::meta_at(head) }.prev.set(Some(meta_ptr));
+        }
+    }
+
+    /// Removes block from the list of live blocks of the chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from this chunk with `layout` and listed.
+    /// Chunk must not be shared with other threads.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    #[inline(always)]
+    pub unsafe fn unlist_live(&self, ptr: *mut u8, layout: Layout) {
+        let (_, offset) = Self::block_layout(layout).unwrap();
+
+        // Safety: metadata is written before the block by `allocate`.
+        unsafe { self.unlist(NonNull::new_unchecked(ptr.sub(offset))) }
+    }
+
+    /// Calls `f` with allocation site of each live block of the chunk,
+    /// most recently allocated first.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    pub fn live_locations(&self, mut f: impl FnMut(&'static core::panic::Location<'static>)) {
+        let mut meta_ptr = self.live_list.get();
+        while let Some(ptr) = meta_ptr {
+            // Safety: metadata of listed blocks is intact until they are unlisted.
+            let meta = unsafe { Self::meta_at(ptr) };
+            f(meta.location);
+            meta_ptr = meta.next.get();
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `meta_ptr` must point to metadata of listed block of this chunk.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    unsafe fn unlist(&self, meta_ptr: NonNull<u8>) {
+        // Safety: metadata of listed blocks is intact until they are unlisted.
+        let meta = unsafe { Self::meta_at(meta_ptr) };
+        let prev = meta.prev.take();
+        let next = meta.next.take();
+
+        match prev {
+            None => self.live_list.set(next),
+            // Safety: metadata of listed blocks is intact until they are unlisted.
+            Some(prev) => unsafe { Self::meta_at(prev) }.next.set(next),
+        }
+        if let Some(next) = next {
+            // Safety: metadata of listed blocks is intact until they are unlisted.
+            unsafe { Self::meta_at(next) }.prev.set(prev);
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `meta_ptr` must point to metadata written by `allocate`
+    /// that is not overwritten during `'a`.
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    #[inline(always)]
+    unsafe fn meta_at<'a>(meta_ptr: NonNull<u8>) -> &'a BlockMeta<Self> {
+        unsafe { &*meta_ptr.as_ptr().cast::<BlockMeta<Self>>() }
+    }
+
+    /// Returns aligned address for the block with given layout
+    /// and cursor address after it.
+    /// Returns `None` if block doesn't fit into the chunk.
+    #[inline(always)]
+    fn _place(&self, cursor: *mut u8, layout: Layout) -> Option<(usize, usize)> {
         let aligned = addr(cursor).checked_add(layout.align() - 1)? & !(layout.align() - 1);
         let new_cursor = aligned.checked_add(layout.size())?;
-        if new_cursor > self.end_addr() {
+        if new_cursor > self.limit_addr() {
             // cold();
             return None;
         }
+        Some((aligned, new_cursor))
+    }
+
+    #[inline(always)]
+    fn _allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let cursor = self.cursor().load(Ordering::Relaxed);
+
+        if layout.align() <= align_of::<usize>() && addr(cursor) & (layout.align() - 1) == 0 {
+            // Fast path for word-aligned allocations when cursor is already aligned.
+            // No alignment padding is required, so `freed` is not touched.
+            return self._allocate_aligned(cursor, layout.size());
+        }
+
+        let (aligned, new_cursor) = self._place(cursor, layout)?;
 
         // Safety: `aligned` is within the chunk.
         let ptr = unsafe { with_addr_mut(cursor, aligned) };
 
         // Safety: `new_cursor` is within the chunk.
         let new_cursor = unsafe { with_addr_mut(cursor, new_cursor) };
-        self.cursor().set(new_cursor);
+        self.cursor().store(new_cursor, Ordering::Relaxed);
 
         // Safety: `freed` is always not greater than `cursor`.
         // So this cannot overflow.
+        // May race with deallocation on another thread,
+        // see `Chunk::freed` why `Relaxed` is enough.
         let overhead = aligned - addr(cursor);
         self.freed().fetch_add(overhead, Ordering::Relaxed);
+        self.padding.set(self.padding.get() + overhead);
 
         // Safety: Range form `ptr` to `ptr + layout.size()` is within the chunk.
         Some(unsafe { NonNull::new_unchecked(ptr) })
     }
 
+    /// Size of the metadata prefix of each block.
+    #[cfg(not(feature = "aligned-chunks"))]
+    const PREFIX_SIZE: usize = size_of::<BlockMeta<Self>>();
+
+    /// Alignment of the metadata prefix of each block.
+    #[cfg(not(feature = "aligned-chunks"))]
+    const PREFIX_ALIGN: usize = align_of::<BlockMeta<Self>>();
+
+    /// Number of bytes of metadata each block carries besides user's memory,
+    /// not counting padding of block size to its alignment.
+    pub const META_SIZE: usize = {
+        #[cfg(not(feature = "aligned-chunks"))]
+        let prefix = Self::PREFIX_SIZE;
+        #[cfg(feature = "aligned-chunks")]
+        let prefix = 0;
+
+        #[cfg(feature = "debug-redzone")]
+        let prefix = prefix + 2 * REDZONE_SIZE;
+
+        prefix
+    };
+
+    /// Returns layout of the block with metadata prefix for `layout`
+    /// and offset of the user's memory in it.
+    ///
+    /// Block size is padded to its alignment, so the cursor stays word-aligned
+    /// after allocating blocks of sub-word sizes and the next allocation
+    /// takes the fast path without alignment padding.
+    ///
+    /// Layouts aligned no stricter than metadata need no padding after it,
+    /// so user's memory is at constant offset and [`Layout::extend`] is skipped.
+    #[cfg(not(feature = "aligned-chunks"))]
+    #[inline(always)]
+    pub fn prefixed_layout(layout: Layout) -> Option<(Layout, usize)> {
+        if layout.align() > Self::PREFIX_ALIGN {
+            return Self::extended_layout(layout);
+        }
+
+        // Block is aligned to metadata and its size rounded up to that alignment.
+        let (size, align) = (Self::PREFIX_SIZE, Self::PREFIX_ALIGN);
+        if layout.size() > isize::MAX as usize - size - (align - 1) {
+            return None;
+        }
+        let block_size = (size + layout.size() + (align - 1)) & !(align - 1);
+
+        // Safety: `align` is power of two and `block_size` rounded up to it
+        // doesn't exceed `isize::MAX`.
+        let block_layout = unsafe { Layout::from_size_align_unchecked(block_size, align) };
+        Some((block_layout, size))
+    }
+
+    /// Same as [`Chunk::prefixed_layout`] for any alignment.
+    #[cfg(not(feature = "aligned-chunks"))]
+    #[inline(always)]
+    pub fn extended_layout(layout: Layout) -> Option<(Layout, usize)> {
+        let (meta_layout, offset) = Layout::new::<BlockMeta<Self>>().extend(layout).ok()?;
+        Some((meta_layout.pad_to_align(), offset))
+    }
+
+    /// Returns layout of the block for `layout` and offset of the user's memory in it.
+    ///
+    /// Blocks have no metadata prefix.
+    /// Block size is padded to the word size, so the cursor stays word-aligned,
+    /// and is never zero, so the block never starts at the end of the chunk
+    /// where masking its address would yield the next chunk.
+    #[cfg(feature = "aligned-chunks")]
+    #[inline(always)]
+    fn prefixed_layout(layout: Layout) -> Option<(Layout, usize)> {
+        let word = size_of::<usize>();
+        let size = layout.size().max(1).checked_add(word - 1)? & !(word - 1);
+        let block_layout = Layout::from_size_align(size, layout.align()).ok()?;
+        Some((block_layout, 0))
+    }
+
+    /// Returns layout of the block for `layout` and offset of the user's memory in it.
+    ///
+    /// With `debug-redzone` feature user's memory is surrounded by guard bytes.
+    #[inline(always)]
+    fn block_layout(layout: Layout) -> Option<(Layout, usize)> {
+        #[cfg(feature = "debug-redzone")]
+        {
+            let (layout, redzone_offset) = redzone_layout(layout)?;
+            let (block_layout, offset) = Self::prefixed_layout(layout)?;
+            Some((block_layout, offset + redzone_offset))
+        }
+        #[cfg(not(feature = "debug-redzone"))]
+        Self::prefixed_layout(layout)
+    }
+
+    /// Returns pointer to the beginning of the block with metadata
+    /// and pointer to the chunk it was allocated from.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from chunk of this size
+    /// with layout that has user's memory at `offset` in the block.
+    #[inline(always)]
+    unsafe fn block_meta(ptr: *mut u8, offset: usize) -> (*mut u8, NonNull<Self>) {
+        #[cfg(not(feature = "aligned-chunks"))]
+        {
+            let meta_ptr = unsafe { ptr.sub(offset) };
+
+            // Safety: metadata is written before the block by `allocate`.
+            let meta = unsafe { &*meta_ptr.cast::<BlockMeta<Self>>() };
+            (meta_ptr, meta.chunk)
+        }
+        #[cfg(feature = "aligned-chunks")]
+        {
+            // Offset is non-zero only when guard bytes precede user's memory.
+            let block_ptr = unsafe { ptr.sub(offset) };
+
+            // Safety: chunk is aligned to `N` and block is within first `N` bytes of it.
+            let chunk_ptr = unsafe { with_addr_mut(block_ptr, addr(block_ptr) & !(N - 1)) };
+
+            // Safety: masked address of the block is non-null chunk address.
+            (block_ptr, unsafe {
+                NonNull::new_unchecked(chunk_ptr.cast())
+            })
+        }
+    }
+
+    /// Checks that block was allocated after the chunk was last reset.
+    ///
+    /// Returns `false` if block is not in this chunk.
+    /// Callers find the chunk by block address, pointer to the chunk
+    /// in metadata of stale block may be overwritten and is not read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if block was allocated before the chunk was reset.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated with `layout` by a ring-allocator.
+    #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+    pub unsafe fn check_generation(&self, ptr: *mut u8, layout: Layout) -> bool {
+        let offset = match Self::block_layout(layout) {
+            Some((_, offset)) => offset,
+            None => return false,
+        };
+
+        // Oversized blocks may lie right after the chunk,
+        // so both block and its metadata must be within the chunk.
+        if !self.contains(addr(ptr)) || !self.contains(addr(ptr).wrapping_sub(offset)) {
+            return false;
+        }
+
+        // Safety: metadata is within chunk memory, which is not freed on reset.
+        let meta = unsafe { &*ptr.sub(offset).cast::<BlockMeta<Self>>() };
+
+        assert_eq!(
+            meta.generation,
+            self.generation.get(),
+            "Block is used after its ring-allocator was reset"
+        );
+        true
+    }
+
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
     #[inline(always)]
     pub fn allocate(&self, chunk_ptr: NonNull<Self>, layout: Layout) -> Option<NonNull<u8>> {
-        let (meta_layout, offset) = Layout::new::<NonNull<Self>>().extend(layout).ok()?;
+        let (meta_layout, offset) = Self::block_layout(layout)?;
         let ptr = self._allocate(meta_layout)?;
 
-        // Safety: `ptr` is allocated to contain `usize` followed with memory for `layout`.
+        #[cfg(feature = "front-reuse")]
+        self.track_allocate(addr(ptr.as_ptr()), meta_layout.size());
+
+        // Safety: `ptr` is allocated to contain `BlockMeta` followed with memory for `layout`.
+        #[cfg(not(feature = "aligned-chunks"))]
         unsafe {
-            ptr.as_ptr().cast::<NonNull<Self>>().write(chunk_ptr);
+            ptr.as_ptr().cast::<BlockMeta<Self>>().write(BlockMeta {
+                chunk: chunk_ptr,
+                #[cfg(feature = "debug-validate")]
+                generation: self.generation.get(),
+                #[cfg(all(feature = "debug-introspect", debug_assertions))]
+                location: core::panic::Location::caller(),
+                #[cfg(all(feature = "debug-introspect", debug_assertions))]
+                prev: Cell::new(None),
+                #[cfg(all(feature = "debug-introspect", debug_assertions))]
+                next: Cell::new(None),
+            });
         }
 
+        #[cfg(feature = "aligned-chunks")]
+        debug_assert_eq!(addr(ptr.as_ptr()) & !(N - 1), addr(chunk_ptr.as_ptr()));
+
         // Safety: offset for `layout` in `meta_layout` used to calculate `ptr`.
         let ptr = unsafe { ptr.as_ptr().add(offset) };
 
+        // Chunk base is not aligned for align-dominated layouts,
+        // alignment is provided by padding the cursor.
+        debug_assert_eq!(addr(ptr) & (layout.align() - 1), 0);
+
+        // Safety: guard bytes are allocated around user's memory.
+        #[cfg(feature = "debug-redzone")]
+        unsafe {
+            fill_redzones(ptr, layout.size())
+        };
+
         // Safety: `ptr` is allocation for `layout`.
         Some(unsafe { NonNull::new_unchecked(ptr) })
     }
 
+    /// Allocates block with zeroed memory.
+    ///
+    /// Only part of the block below the dirty mark is zeroed.
+    /// Memory above it was not handed out since the chunk was zeroed.
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    #[inline(always)]
+    pub fn allocate_zeroed(&self, chunk_ptr: NonNull<Self>, layout: Layout) -> Option<NonNull<u8>> {
+        // Everything below the cursor may have been written.
+        let dirty = self
+            .dirty
+            .get()
+            .max(addr(self.cursor().load(Ordering::Relaxed)));
+
+        let ptr = self.allocate(chunk_ptr, layout)?;
+
+        let dirty_size = dirty.saturating_sub(addr(ptr.as_ptr())).min(layout.size());
+
+        // Safety: `ptr` is allocated for `layout.size()` bytes.
+        unsafe { ptr.as_ptr().write_bytes(0, dirty_size) };
+
+        Some(ptr)
+    }
+
+    /// Raises the dirty mark to the cursor before cursor moves backwards.
+    #[inline(always)]
+    fn raise_dirty(&self) {
+        let cursor = addr(self.cursor().load(Ordering::Relaxed));
+        if self.dirty.get() < cursor {
+            self.dirty.set(cursor);
+        }
+    }
+
+    /// Allocates block of `size` bytes at `cursor` that is already aligned.
+    #[inline(always)]
+    fn _allocate_aligned(&self, cursor: *mut u8, size: usize) -> Option<NonNull<u8>> {
+        let new_cursor = addr(cursor).checked_add(size)?;
+        if new_cursor > self.limit_addr() {
+            return None;
+        }
+
+        // Safety: `new_cursor` is within the chunk.
+        let new_cursor = unsafe { with_addr_mut(cursor, new_cursor) };
+        self.cursor().store(new_cursor, Ordering::Relaxed);
+
+        // Safety: Range form `cursor` to `cursor + size` is within the chunk.
+        Some(unsafe { NonNull::new_unchecked(cursor) })
+    }
+
+    /// Checks if block with given layout can be allocated from this chunk
+    /// at current cursor position.
+    #[inline(always)]
+    pub fn fits(&self, layout: Layout) -> bool {
+        match Self::block_layout(layout) {
+            Some((meta_layout, _)) => self
+                ._place(self.cursor().load(Ordering::Relaxed), meta_layout)
+                .is_some(),
+            None => false,
+        }
+    }
+
     #[inline(always)]
     unsafe fn _deallocate(&self, size: usize) {
         // Safety: `freed` is always less than `cursor - size`.
@@ -194,17 +1236,414 @@ where
         self.freed().fetch_add(size, Ordering::Release);
     }
 
+    /// Deallocates block with stack discipline.
+    ///
+    /// If block is the last one allocated from the chunk, cursor is rewound
+    /// to the beginning of the block.
+    /// Chunk is reset as soon as it becomes unused.
+    ///
+    /// # Safety
+    ///
+    /// Chunk must not be shared with other threads.
+    #[inline(always)]
+    pub unsafe fn deallocate_lifo(ptr: *mut u8, layout: Layout) {
+        let (meta_layout, offset) = Self::block_layout(layout).unwrap();
+
+        let (meta_ptr, chunk_ptr) = unsafe { Self::block_meta(ptr, offset) };
+
+        // Safety: chunk is alive since `ptr` is alive.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chunk.size, N,
+            "Block is deallocated with layout of different size class"
+        );
+
+        #[cfg(feature = "debug-redzone")]
+        unsafe {
+            check_redzones(ptr, layout.size())
+        };
+
+        #[cfg(feature = "front-reuse")]
+        chunk.track_deallocate(addr(meta_ptr), meta_layout.size());
+
+        if addr(meta_ptr) + meta_layout.size() == addr(chunk.cursor().load(Ordering::Relaxed))
+            && addr(meta_ptr) >= chunk.floor.get()
+        {
+            // Block is on top and not committed. Alignment padding before it stays accounted in `freed`.
+            chunk.raise_dirty();
+            chunk.cursor().store(meta_ptr.cast(), Ordering::Relaxed);
+        } else {
+            unsafe {
+                chunk._deallocate(meta_layout.size());
+            }
+        }
+
+        chunk.reset();
+    }
+
+    /// Attempts to grow block in place to `new_size` bytes.
+    ///
+    /// Succeeds only if block is the last one allocated from the chunk
+    /// and chunk has enough space after it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from chunk of this size with `layout`.
+    /// Chunk must not be shared with other threads.
+    #[inline(always)]
+    pub unsafe fn grow_in_place(ptr: *mut u8, layout: Layout, new_size: usize) -> bool {
+        let (meta_layout, offset) = Self::block_layout(layout).unwrap();
+
+        let (meta_ptr, chunk_ptr) = unsafe { Self::block_meta(ptr, offset) };
+
+        // Safety: chunk is alive since `ptr` is alive.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chunk.size, N,
+            "Block is grown with layout of different size class"
+        );
+
+        #[cfg(feature = "debug-redzone")]
+        unsafe {
+            check_redzones(ptr, layout.size())
+        };
+
+        if addr(meta_ptr) + meta_layout.size() != addr(chunk.cursor().load(Ordering::Relaxed)) {
+            return false;
+        }
+
+        // Committed block may not extend past the floor.
+        if addr(meta_ptr) < chunk.floor.get() {
+            return false;
+        }
+
+        // Block keeps its alignment, so new block layout has the same offset.
+        let new_layout = match Layout::from_size_align(new_size, layout.align()) {
+            Ok(new_layout) => new_layout,
+            Err(_) => return false,
+        };
+        let new_block_size = match Self::block_layout(new_layout) {
+            Some((new_meta_layout, _)) => new_meta_layout.size(),
+            None => return false,
+        };
+
+        match addr(meta_ptr).checked_add(new_block_size) {
+            Some(new_cursor) if new_cursor <= chunk.limit_addr() => {
+                #[cfg(feature = "front-reuse")]
+                chunk.track_allocate(addr(meta_ptr), new_block_size - meta_layout.size());
+
+                // Safety: `new_cursor` is within the chunk.
+                let new_cursor = unsafe { with_addr_mut(ptr, new_cursor) };
+                chunk.cursor().store(new_cursor, Ordering::Relaxed);
+
+                // Safety: guard bytes are allocated around grown user's memory.
+                #[cfg(feature = "debug-redzone")]
+                unsafe {
+                    fill_redzones(ptr, new_size)
+                };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns pointer to the chunk the block was allocated from.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from chunk of this size with `layout`.
+    #[inline(always)]
+    pub unsafe fn chunk_of(ptr: *mut u8, layout: Layout) -> NonNull<Self> {
+        let (_, offset) = Self::block_layout(layout).unwrap();
+
+        unsafe { Self::block_meta(ptr, offset).1 }
+    }
+
     #[inline(always)]
     pub unsafe fn deallocate(ptr: *mut u8, layout: Layout) {
-        let (meta_layout, offset) = Layout::new::<NonNull<Self>>().extend(layout).unwrap();
+        let (meta_layout, offset) = Self::block_layout(layout).unwrap();
 
-        let meta_ptr = unsafe { ptr.sub(offset) }.cast::<NonNull<Self>>();
-        let chunk_ptr = unsafe { *meta_ptr };
+        let chunk_ptr = unsafe { Self::block_meta(ptr, offset).1 };
 
         // Safety: chunk is alive since `ptr` is alive.
         let chunk = unsafe { chunk_ptr.as_ref() };
+
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chunk.size, N,
+            "Block is deallocated with layout of different size class"
+        );
+
+        #[cfg(feature = "debug-redzone")]
+        unsafe {
+            check_redzones(ptr, layout.size())
+        };
+        // Zero-sized block may end exactly at the end of the chunk,
+        // so check the address of its metadata instead.
+        debug_assert!(
+            chunk.base_addr() <= addr(ptr) - offset && addr(ptr) - offset < chunk.end_addr(),
+            "Block is not within the chunk"
+        );
+
+        #[cfg(feature = "front-reuse")]
+        chunk.track_deallocate(addr(ptr) - offset, meta_layout.size());
+
         unsafe {
             chunk._deallocate(meta_layout.size());
         }
     }
+
+    /// Deallocates block without accounting it in the chunk.
+    ///
+    /// Returns pointer to the chunk of the block and number of bytes
+    /// that must be accounted later with [`Chunk::release`].
+    /// Until then chunk is not considered unused.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be allocated from chunk of this size with `layout`.
+    #[cfg(feature = "deferred-frees")]
+    #[cfg_attr(not(feature = "front-reuse"), allow(unused_variables))]
+    #[inline(always)]
+    pub unsafe fn deallocate_deferred(ptr: *mut u8, layout: Layout) -> (NonNull<Self>, usize) {
+        let (meta_layout, offset) = Self::block_layout(layout).unwrap();
+
+        let (meta_ptr, chunk_ptr) = unsafe { Self::block_meta(ptr, offset) };
+
+        // Safety: chunk is alive since `ptr` is alive.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        #[cfg(debug_assertions)]
+        debug_assert_eq!(
+            chunk.size, N,
+            "Block is deallocated with layout of different size class"
+        );
+
+        #[cfg(feature = "debug-redzone")]
+        unsafe {
+            check_redzones(ptr, layout.size())
+        };
+
+        #[cfg(feature = "front-reuse")]
+        chunk.track_deallocate(addr(meta_ptr), meta_layout.size());
+
+        (chunk_ptr, meta_layout.size())
+    }
+
+    /// Accounts `size` bytes of blocks deallocated with [`Chunk::deallocate_deferred`].
+    ///
+    /// # Safety
+    ///
+    /// `size` must not exceed number of bytes deallocated from this chunk
+    /// and not accounted yet.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub unsafe fn release(&self, size: usize) {
+        unsafe { self._deallocate(size) }
+    }
+}
+
+#[cfg(feature = "front-reuse")]
+impl<T, const N: usize> Chunk<T, N>
+where
+    T: ImUsize,
+{
+    const SEGMENT_SIZE: usize = N / SEGMENTS;
+
+    /// Returns segment the address belongs to.
+    /// Last segment extends to the end of chunks with capacity larger than `N`.
+    #[inline(always)]
+    fn segment_of(&self, block_addr: usize) -> usize {
+        ((block_addr - self.chunk_addr()) / Self::SEGMENT_SIZE).min(SEGMENTS - 1)
+    }
+
+    #[inline(always)]
+    fn track_allocate(&self, block_addr: usize, size: usize) {
+        let used = &self.segment_used[self.segment_of(block_addr)];
+        used.set(used.get().wrapping_add(size));
+    }
+
+    #[inline(always)]
+    fn track_deallocate(&self, block_addr: usize, size: usize) {
+        // Sync with `Acquire` in `segment_unused`.
+        self.segment_freed[self.segment_of(block_addr)].fetch_add(size, Ordering::Release);
+    }
+
+    /// Checks if all blocks that start in the segment are deallocated.
+    #[inline(always)]
+    fn segment_unused(&self, segment: usize) -> bool {
+        self.segment_freed[segment].load(Ordering::Acquire) == self.segment_used[segment].get()
+    }
+
+    /// Makes memory of deallocated blocks available for allocation
+    /// while some blocks in the chunk are still alive.
+    ///
+    /// Extends the limit over following segments that have no live blocks.
+    /// If limit can't be extended, wraps cursor to the beginning of the chunk
+    /// when first segment has no live blocks.
+    /// Returns `false` if nothing can be reclaimed.
+    ///
+    /// Memory after the cursor up to the old limit is not used until the chunk is reset.
+    #[cold]
+    pub fn reclaim(&self) -> bool {
+        let end = self.end_addr();
+        let limit = self.limit.get();
+
+        let mut new_limit = limit;
+        while new_limit < end && self.segment_unused(self.segment_of(new_limit)) {
+            new_limit = (new_limit + Self::SEGMENT_SIZE).min(end);
+        }
+
+        if new_limit > limit {
+            self.limit.set(new_limit);
+            return true;
+        }
+
+        let cursor = self.cursor().load(Ordering::Relaxed);
+        // Wrapping over committed blocks would let cursor below the floor.
+        if addr(cursor) == self.base_addr()
+            || self.floor.get() != self.base_addr()
+            || !self.segment_unused(0)
+        {
+            return false;
+        }
+
+        self.raise_dirty();
+
+        // Keep `cursor - freed` equal to number of live bytes.
+        self.freed().fetch_add(
+            (addr(cursor) - self.base_addr()).wrapping_neg(),
+            Ordering::Relaxed,
+        );
+
+        // Safety: base_addr is beginning of the chunk memory.
+        self.cursor().store(
+            unsafe { with_addr_mut(cursor, self.base_addr()) },
+            Ordering::Relaxed,
+        );
+        self.limit.set(self.chunk_addr() + Self::SEGMENT_SIZE);
+        true
+    }
+}
+
+/// Checks that `next` and `prev` links of chunks from `head` to `tail` are consistent.
+/// Returns number of chunks.
+#[cfg(all(test, feature = "alloc"))]
+pub(crate) fn check_links<T: ImUsize, const N: usize>(
+    head: Option<NonNull<Chunk<T, N>>>,
+    tail: Option<NonNull<Chunk<T, N>>>,
+) -> usize {
+    let mut prev = None;
+    let mut chunk = head;
+    let mut count = 0;
+
+    while let Some(c) = chunk {
+        // Safety: chunks in the ring are always valid.
+        let c_ref = unsafe { c.as_ref() };
+        assert_eq!(c_ref.prev.get(), prev, "Broken `prev` link");
+        #[cfg(any(debug_assertions, not(feature = "aligned-chunks")))]
+        assert_eq!(c_ref.size, N, "Chunk of different size class in the ring");
+
+        prev = chunk;
+        chunk = c_ref.next.get();
+        count += 1;
+    }
+
+    assert_eq!(prev, tail, "Tail is not the last chunk");
+    count
+}
+
+/// Checks that `freed` and cursor stay ordered within chunk memory.
+#[cfg(all(test, feature = "alloc"))]
+pub(crate) fn check_counters<T: ImUsize, const N: usize>(chunk: &Chunk<T, N>) {
+    let cursor = addr(chunk.cursor().load(Ordering::Relaxed));
+    let freed = chunk.freed().load(Ordering::Acquire);
+
+    assert!(chunk.base_addr() <= freed, "`freed` is below chunk memory");
+    assert!(freed <= cursor, "`freed` is past the cursor");
+    assert!(cursor <= chunk.end_addr(), "Cursor is past chunk memory");
+}
+
+/// Run of unused chunks with adjacent memory
+/// that can be returned to the underlying allocator with single call.
+pub(crate) struct FreeRun<T: ImUsize, const N: usize> {
+    first: Option<NonNull<Chunk<T, N>>>,
+    count: usize,
+}
+
+impl<T, const N: usize> FreeRun<T, N>
+where
+    T: ImUsize,
+{
+    pub const fn new() -> Self {
+        FreeRun {
+            first: None,
+            count: 0,
+        }
+    }
+
+    /// Adds chunk to the run.
+    /// If chunk is not adjacent to the run, the run is freed first
+    /// and new run is started with this chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid pointer to unused chunk allocated by `alloc`.
+    /// `alloc` must accept deallocation of adjacent chunks as a single block.
+    pub unsafe fn push<A>(&mut self, ptr: NonNull<Chunk<T, N>>, alloc: &A)
+    where
+        A: Allocator,
+    {
+        // Trimmed chunks are not merged, they leave gaps between neighbours.
+        // Chunks from overflow allocator are returned to it one by one.
+        // Safety: `ptr` is valid pointer to chunk.
+        if unsafe { ptr.as_ref().capacity.get() != N || ptr.as_ref().overflow.is_some() } {
+            // Safety: guaranteed by caller.
+            unsafe { Chunk::free(ptr, alloc) };
+            return;
+        }
+
+        if let Some(first) = self.first {
+            if addr(ptr.as_ptr()) == addr(first.as_ptr()) + self.count * N {
+                self.count += 1;
+                return;
+            }
+            if addr(ptr.as_ptr()) + N == addr(first.as_ptr()) {
+                self.first = Some(ptr);
+                self.count += 1;
+                return;
+            }
+
+            // Safety: guaranteed by caller.
+            unsafe { self.flush(alloc) };
+        }
+
+        self.first = Some(ptr);
+        self.count = 1;
+    }
+
+    /// Frees all chunks in the run with single call.
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`FreeRun::push`].
+    pub unsafe fn flush<A>(&mut self, alloc: &A)
+    where
+        A: Allocator,
+    {
+        if let Some(first) = self.first.take() {
+            // Safety: chunks in the run span `count * N` bytes
+            // and this layout was valid for single chunk.
+            let layout = unsafe {
+                Layout::from_size_align_unchecked(self.count * N, Chunk::<T, N>::ALIGNMENT)
+            };
+
+            // Safety: guaranteed by caller.
+            unsafe { alloc.deallocate(first.cast(), layout) };
+        }
+    }
 }
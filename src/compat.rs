@@ -0,0 +1,296 @@
+//! Compatibility shims for migrating from other allocators.
+
+use core::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    ptr::{self, NonNull},
+};
+
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+use crate::{ArenaVec, RingAlloc};
+
+/// Arena with API of `bumpalo::Bump` backed by [`RingAlloc`].
+///
+/// Provides most commonly used methods of `bumpalo::Bump` with the same signatures,
+/// so code written for `bumpalo` can try ring-allocator with minimal changes.
+///
+/// Like with `bumpalo`, values allocated with `alloc*` methods are never dropped.
+/// Memory is reclaimed by [`BumpCompat::reset`].
+/// `&BumpCompat` is an allocator, and blocks deallocated through it
+/// are reclaimed by the ring-allocator as usual.
+///
+/// Blocks too large for the rings are allocated from the underlying allocator.
+/// The arena records them and deallocates them on reset and when dropped.
+///
+/// # Example
+///
+/// ```
+/// use ring_alloc::compat::BumpCompat as Bump;
+///
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let mut bump = Bump::new();
+///
+/// let point = bump.alloc(Point { x: 1, y: 2 });
+/// point.x += 10;
+/// assert_eq!(point.x + point.y, 13);
+///
+/// let numbers = bump.alloc_slice_copy(&[1, 2, 3]);
+/// numbers[0] = 10;
+/// assert_eq!(numbers, [10, 2, 3]);
+///
+/// let name = bump.alloc_str("ring");
+/// name.make_ascii_uppercase();
+/// assert_eq!(name, "RING");
+///
+/// // Forget all allocations at once.
+/// bump.reset();
+/// ```
+pub struct BumpCompat<A: Allocator = Global> {
+    alloc: RingAlloc<A>,
+    oversized: UnsafeCell<ArenaVec<OversizedBlock, A>>,
+}
+
+/// Block allocated from [`BumpCompat`] that is not tracked by the rings.
+struct OversizedBlock {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl BumpCompat {
+    /// Returns new arena that uses [`Global`] allocator.
+    #[inline(always)]
+    pub fn new() -> Self {
+        BumpCompat::new_in(Global)
+    }
+}
+
+impl Default for BumpCompat {
+    #[inline(always)]
+    fn default() -> Self {
+        BumpCompat::new()
+    }
+}
+
+impl<A> BumpCompat<A>
+where
+    A: Allocator,
+{
+    /// Returns new arena that uses given allocator.
+    #[inline(always)]
+    pub fn new_in(allocator: A) -> Self {
+        let alloc = RingAlloc::new_in(allocator);
+        BumpCompat {
+            oversized: UnsafeCell::new(ArenaVec::new_in(alloc.clone())),
+            alloc,
+        }
+    }
+
+    /// Allocates space for an object of type `T` and moves `val` into it.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, val: T) -> &mut T {
+        self.alloc_with(|| val)
+    }
+
+    /// Allocates space for an object of type `T` and initializes it with `f`.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_with<T, F>(&self, f: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+
+        // Safety: `ptr` is allocated for `T` and not aliased.
+        unsafe {
+            ptr.as_ptr().write(f());
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Allocates space for a copy of `src` slice and copies it.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T>(&self, src: &[T]) -> &mut [T]
+    where
+        T: Copy,
+    {
+        let ptr = self.alloc_slice_layout::<T>(src.len());
+
+        // Safety: `ptr` is allocated for `src.len()` elements of `T` and not aliased.
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            core::slice::from_raw_parts_mut(ptr.as_ptr(), src.len())
+        }
+    }
+
+    /// Allocates space for a clone of `src` slice and clones elements into it.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_clone<T>(&self, src: &[T]) -> &mut [T]
+    where
+        T: Clone,
+    {
+        let ptr = self.alloc_slice_layout::<T>(src.len());
+
+        for (i, val) in src.iter().enumerate() {
+            // Safety: `ptr` is allocated for `src.len()` elements of `T`.
+            unsafe { ptr.as_ptr().add(i).write(val.clone()) };
+        }
+
+        // Safety: all elements are initialized and memory is not aliased.
+        unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), src.len()) }
+    }
+
+    /// Allocates space for a copy of `src` string and copies it.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, src: &str) -> &mut str {
+        let bytes = self.alloc_slice_copy(src.as_bytes());
+
+        // Safety: bytes are copied from valid UTF-8 string.
+        unsafe { core::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Allocates block of memory for `layout`.
+    ///
+    /// Panics if memory can't be allocated.
+    #[inline(always)]
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        match self.allocate_block(layout, false) {
+            Ok(ptr) => ptr.cast(),
+            Err(AllocError) => alloc::alloc::handle_alloc_error(layout),
+        }
+    }
+
+    #[inline(always)]
+    fn alloc_slice_layout<T>(&self, len: usize) -> NonNull<T> {
+        match Layout::array::<T>(len) {
+            Ok(layout) => self.alloc_layout(layout).cast(),
+            Err(_) => panic!("Slice is too large"),
+        }
+    }
+
+    /// Allocates block and records it if it is not tracked by the rings.
+    #[inline(always)]
+    fn allocate_block(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        let allocate = |layout| match zeroed {
+            false => self.alloc.allocate(layout),
+            true => self.alloc.allocate_zeroed(layout),
+        };
+
+        if !RingAlloc::<A>::is_oversized(layout) {
+            return allocate(layout);
+        }
+
+        // Safety: list of oversized blocks is borrowed only within methods of the arena
+        // that don't call each other, and the arena is not `Sync`.
+        let oversized = unsafe { &mut *self.oversized.get() };
+
+        // Reserve first, so allocated block is never left unrecorded.
+        oversized.try_reserve(1)?;
+        let ptr = allocate(layout)?;
+        oversized.push(OversizedBlock {
+            ptr: ptr.cast(),
+            layout,
+        });
+        Ok(ptr)
+    }
+
+    /// Deallocates block and forgets it if it was recorded.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block currently allocated from this arena with `layout`.
+    #[inline(always)]
+    unsafe fn deallocate_block(&self, ptr: NonNull<u8>, layout: Layout) {
+        if RingAlloc::<A>::is_oversized(layout) {
+            // Safety: list of oversized blocks is borrowed only within methods of the arena
+            // that don't call each other, and the arena is not `Sync`.
+            let oversized = unsafe { &mut *self.oversized.get() };
+
+            if let Some(idx) = oversized.iter().position(|block| block.ptr == ptr) {
+                let last = oversized.len() - 1;
+                oversized.swap(idx, last);
+                oversized.pop();
+            }
+        }
+
+        // Safety: covered by function contract.
+        unsafe { self.alloc.deallocate(ptr, layout) }
+    }
+
+    fn free_oversized(&mut self) {
+        let blocks = core::mem::replace(
+            self.oversized.get_mut(),
+            ArenaVec::new_in(self.alloc.clone()),
+        );
+
+        for block in blocks.iter() {
+            // Safety: block is allocated from the ring-allocator with this layout
+            // and unique borrow guarantees that it is not referenced anymore.
+            unsafe { self.alloc.deallocate(block.ptr, block.layout) };
+        }
+    }
+
+    /// Deallocates all values allocated from this arena at once.
+    ///
+    /// Chunks are kept for reuse.
+    /// Destructors of allocated values are not run.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.free_oversized();
+
+        // Safety: `&mut self` guarantees that no references to allocated values
+        // and no collections that use `&self` as allocator are alive.
+        // Ring-allocator is never shared outside
+        // and the list of oversized blocks has no buffer now.
+        unsafe { self.alloc.reset() }
+    }
+}
+
+impl<A> Drop for BumpCompat<A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.free_oversized();
+    }
+}
+
+unsafe impl<A> Allocator for &BumpCompat<A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_block(layout, false)
+    }
+
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_block(layout, true)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: covered by `Allocator::deallocate` contract.
+        unsafe { self.deallocate_block(ptr, layout) }
+    }
+}
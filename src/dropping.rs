@@ -0,0 +1,233 @@
+use core::{alloc::Layout, cell::UnsafeCell, mem::needs_drop, ptr::NonNull};
+
+use allocator_api2::alloc::{AllocError, Allocator};
+
+use crate::{ArenaVec, RingAlloc};
+
+/// Destructor of a value allocated from [`DroppingRing`].
+struct DropEntry {
+    ptr: NonNull<u8>,
+    drop: unsafe fn(NonNull<u8>),
+}
+
+/// Block of a value allocated from [`DroppingRing`]
+/// that is not tracked by the rings and must be deallocated explicitly.
+struct OversizedBlock {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+/// # Safety
+///
+/// `ptr` must point to initialized value of type `T` that is not used afterwards.
+unsafe fn drop_value<T>(ptr: NonNull<u8>) {
+    // Safety: covered by function contract.
+    unsafe { core::ptr::drop_in_place(ptr.cast::<T>().as_ptr()) }
+}
+
+#[cfg(not(feature = "alloc"))]
+macro_rules! dropping_ring {
+    ($(#[$meta:meta])* pub struct $dropping_ring:ident;) => {
+        $(#[$meta])*
+        pub struct $dropping_ring<A: Allocator> {
+            alloc: RingAlloc<A>,
+            drops: UnsafeCell<ArenaVec<DropEntry, A>>,
+            oversized: UnsafeCell<ArenaVec<OversizedBlock, A>>,
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! dropping_ring {
+    ($(#[$meta:meta])* pub struct $dropping_ring:ident;) => {
+        $(#[$meta])*
+        pub struct $dropping_ring<A: Allocator = allocator_api2::alloc::Global> {
+            alloc: RingAlloc<A>,
+            drops: UnsafeCell<ArenaVec<DropEntry, A>>,
+            oversized: UnsafeCell<ArenaVec<OversizedBlock, A>>,
+        }
+    };
+}
+
+dropping_ring! {
+    /// Arena of values with destructors built on [`RingAlloc`].
+    ///
+    /// [`RingAlloc::reset`] deallocates all blocks at once without running destructors.
+    /// This arena records destructor of each value that needs one in a side list
+    /// and runs them on [`DroppingRing::reset`] and when the arena is dropped,
+    /// in reverse allocation order.
+    /// Values too large for the rings are recorded too
+    /// and their blocks are deallocated after destructors run.
+    ///
+    /// Values must be `'static`, so their destructors can't observe
+    /// other values of the arena that are already dropped.
+    ///
+    /// The arena owns its ring-allocator and never hands out its handles,
+    /// since reset invalidates all blocks allocated from it.
+    pub struct DroppingRing;
+}
+
+#[cfg(feature = "alloc")]
+impl DroppingRing {
+    /// Returns new [`DroppingRing`] that uses [`Global`] allocator.
+    ///
+    /// [`Global`]: allocator_api2::alloc::Global
+    #[inline(always)]
+    pub fn new() -> Self {
+        DroppingRing::new_in(allocator_api2::alloc::Global)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for DroppingRing {
+    #[inline(always)]
+    fn default() -> Self {
+        DroppingRing::new()
+    }
+}
+
+impl<A> DroppingRing<A>
+where
+    A: Allocator,
+{
+    /// Returns new [`DroppingRing`] with its own ring-allocator
+    /// that uses given allocator.
+    #[inline(always)]
+    pub fn new_in(allocator: A) -> Self {
+        Self::with_ring(RingAlloc::new_in(allocator))
+    }
+
+    /// Attempts to create new [`DroppingRing`] with its own ring-allocator
+    /// that uses given allocator.
+    #[inline(always)]
+    pub fn try_new_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(Self::with_ring(RingAlloc::try_new_in(allocator)?))
+    }
+
+    #[inline(always)]
+    fn with_ring(alloc: RingAlloc<A>) -> Self {
+        DroppingRing {
+            drops: UnsafeCell::new(ArenaVec::new_in(alloc.clone())),
+            oversized: UnsafeCell::new(ArenaVec::new_in(alloc.clone())),
+            alloc,
+        }
+    }
+
+    /// Moves value into the arena and returns mutable reference to it.
+    ///
+    /// On allocation error [`handle_alloc_error`] is called.
+    ///
+    /// [`handle_alloc_error`]: allocator_api2::alloc::handle_alloc_error
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: 'static>(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Ok(value) => value,
+            Err(AllocError) => alloc::alloc::handle_alloc_error(core::alloc::Layout::new::<T>()),
+        }
+    }
+
+    /// Attempts to move value into the arena and returns mutable reference to it.
+    ///
+    /// Value is dropped if allocation fails.
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T: 'static>(&self, value: T) -> Result<&mut T, AllocError> {
+        // Safety: side lists are borrowed only within methods of the arena
+        // that don't call each other, and the arena is not `Sync`.
+        let (drops, oversized) = unsafe { (&mut *self.drops.get(), &mut *self.oversized.get()) };
+
+        let is_oversized = RingAlloc::<A>::is_oversized(Layout::new::<T>());
+
+        // Reserve before moving the value in, so it is never left without destructor.
+        if needs_drop::<T>() {
+            drops.try_reserve(1)?;
+        }
+        if is_oversized {
+            oversized.try_reserve(1)?;
+        }
+
+        let ptr = self.alloc.allocate_for::<T>()?;
+
+        // Safety: `ptr` is allocated for value of type `T`.
+        unsafe { ptr.as_ptr().write(value) };
+
+        if needs_drop::<T>() {
+            drops.push(DropEntry {
+                ptr: ptr.cast(),
+                drop: drop_value::<T>,
+            });
+        }
+
+        if is_oversized {
+            oversized.push(OversizedBlock {
+                ptr: ptr.cast(),
+                layout: Layout::new::<T>(),
+            });
+        }
+
+        // Safety: value is initialized and is not referenced
+        // until the arena is reset or dropped, which requires unique borrow.
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+
+    /// Returns number of values in the arena whose destructors are not run yet.
+    #[inline(always)]
+    pub fn pending_drops(&self) -> usize {
+        // Safety: side list is borrowed only within methods of the arena
+        // that don't call each other, and the arena is not `Sync`.
+        unsafe { &*self.drops.get() }.len()
+    }
+
+    /// Drops all values in the arena and deallocates their memory at once.
+    ///
+    /// Destructors run in reverse allocation order.
+    /// If a destructor panics, remaining values are leaked
+    /// and memory is deallocated when the arena is dropped.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.run_drops();
+        self.free_oversized();
+
+        // Safety: all values are dropped and unique borrow guarantees
+        // that no references to them remain.
+        // Ring-allocator is not shared outside of the arena
+        // and the side lists have no buffers now.
+        unsafe { self.alloc.reset() }
+    }
+
+    fn run_drops(&mut self) {
+        let drops = core::mem::replace(self.drops.get_mut(), ArenaVec::new_in(self.alloc.clone()));
+
+        for entry in drops.iter().rev() {
+            // Safety: entry is recorded for initialized value of the arena
+            // that is not referenced anymore and is dropped only once.
+            unsafe { (entry.drop)(entry.ptr) };
+        }
+    }
+
+    fn free_oversized(&mut self) {
+        let blocks = core::mem::replace(
+            self.oversized.get_mut(),
+            ArenaVec::new_in(self.alloc.clone()),
+        );
+
+        for block in blocks.iter() {
+            // Safety: block is allocated from the ring-allocator with this layout,
+            // its value is dropped or leaked and is not referenced anymore.
+            unsafe { self.alloc.deallocate(block.ptr, block.layout) };
+        }
+    }
+}
+
+impl<A> Drop for DroppingRing<A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.run_drops();
+        self.free_oversized();
+    }
+}
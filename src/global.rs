@@ -1,12 +1,20 @@
 use core::{
-    alloc::Layout, cell::Cell, hint::unreachable_unchecked, ptr::NonNull, sync::atomic::AtomicUsize,
+    alloc::Layout, cell::Cell, hint::unreachable_unchecked, marker::PhantomData, ops::DerefMut,
+    ptr::NonNull, sync::atomic::Ordering,
 };
-use std::thread_local;
+
+#[cfg(any(feature = "deferred-frees", not(feature = "aligned-chunks")))]
+use core::sync::atomic::AtomicBool;
 
 use allocator_api2::alloc::{AllocError, Allocator, Global};
-use parking_lot::Mutex;
 
-use crate::layout_max;
+#[cfg(test)]
+use crate::chunk::check_links;
+use crate::{
+    layout_max,
+    sync::{AtomicPtr, AtomicUsize, Mutex},
+    LazyRingAlloc, MemoryReport, RingAlloc, RingStats, SizeClass,
+};
 
 type Chunk<const N: usize> = crate::chunk::Chunk<AtomicUsize, N>;
 
@@ -28,10 +36,151 @@ const LARGE_ALLOCATION_MAX_SIZE: usize = 65536;
 /// Size of the chunk for allocations larger than `SMALL_ALLOCATION_MAX_SIZE`.
 const LARGE_ALLOCATION_CHUNK_SIZE: usize = 2097152;
 
+/// Deferred deallocations are accounted in the chunk
+/// when this number of bytes is accumulated.
+#[cfg(feature = "deferred-frees")]
+const DEFERRED_FREES_THRESHOLD: usize = 4096;
+
+/// Number of attempts to lock global ring on thread exit
+/// before chunks are orphaned instead.
+const RETIRE_LOCK_ATTEMPTS: usize = 64;
+
+/// Oversized blocks up to this number of bytes are cached for reuse.
+/// Larger blocks are allocated with requested layout.
+const OVERSIZED_CACHE_MAX_SIZE: usize = 1 << 24;
+
+/// Oversized blocks with alignment up to this value are cached for reuse.
+/// Cached blocks are allocated with this alignment, so they fit any cacheable layout.
+const OVERSIZED_CACHE_ALIGN: usize = 4096;
+
+/// Maximum number of blocks cached for each rounded size.
+const OVERSIZED_CACHE_BUCKET_CAPACITY: usize = 4;
+
+/// Maximum total size of cached oversized blocks.
+const OVERSIZED_CACHE_MAX_BYTES: usize = 1 << 26;
+
+/// Number of buckets of oversized cache for each power of two.
+/// Cacheable blocks are rounded up to a quarter of the power of two below their size,
+/// wasting less than 25% of the block.
+const OVERSIZED_CACHE_STEPS: usize = 4;
+
+/// Number of buckets of oversized cache.
+const OVERSIZED_CACHE_BUCKETS: usize = (OVERSIZED_CACHE_MAX_SIZE.trailing_zeros()
+    - LARGE_ALLOCATION_MAX_SIZE.trailing_zeros()) as usize
+    * OVERSIZED_CACHE_STEPS;
+
 type TinyChunk = Chunk<{ TINY_ALLOCATION_CHUNK_SIZE }>;
 type SmallChunk = Chunk<{ SMALL_ALLOCATION_CHUNK_SIZE }>;
 type LargeChunk = Chunk<{ LARGE_ALLOCATION_CHUNK_SIZE }>;
 
+#[cfg(not(loom))]
+std::thread_local! {
+    /// Set while the thread is inside [`OneRingAlloc`] used as global allocator.
+    /// Must not have destructor, as registering one may allocate.
+    static IN_RINGS: Cell<bool> = const { Cell::new(false) };
+
+    /// Set while [`Backing`] forwards a request to `Global`.
+    /// Must not have destructor, as registering one may allocate.
+    static IN_BACKING: Cell<bool> = const { Cell::new(false) };
+}
+
+#[cfg(feature = "donor-tracking")]
+std::thread_local! {
+    /// Id that tags chunks donated by the thread, `0` until first donation.
+    /// Must not have destructor, so it is available while rings are retired.
+    static DONOR_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Next id to assign to a donating thread.
+#[cfg(feature = "donor-tracking")]
+static NEXT_DONOR_ID: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(1);
+
+/// Returns id of the calling thread that tags chunks it donates,
+/// assigning one on first use.
+/// Returns `0` if thread-local storage is already destroyed.
+#[cfg(feature = "donor-tracking")]
+fn donor_id() -> usize {
+    DONOR_ID
+        .try_with(|id| {
+            if id.get() == 0 {
+                id.set(NEXT_DONOR_ID.fetch_add(1, Ordering::Relaxed));
+            }
+            id.get()
+        })
+        .unwrap_or(0)
+}
+
+/// Marks the thread with a flag until dropped.
+#[cfg(not(loom))]
+struct Entered(&'static std::thread::LocalKey<Cell<bool>>);
+
+#[cfg(not(loom))]
+impl Entered {
+    /// Marks the thread as inside [`OneRingAlloc`].
+    /// Returns `None` if the thread is already inside.
+    #[inline(always)]
+    fn enter() -> Option<Self> {
+        Self::set(&IN_RINGS)
+    }
+
+    /// Marks the thread as inside [`Backing`].
+    /// Returns `None` if the thread is already inside.
+    #[inline(always)]
+    fn enter_backing() -> Option<Self> {
+        Self::set(&IN_BACKING)
+    }
+
+    #[inline(always)]
+    fn set(flag: &'static std::thread::LocalKey<Cell<bool>>) -> Option<Self> {
+        if flag.with(|flag| flag.replace(true)) {
+            None
+        } else {
+            Some(Entered(flag))
+        }
+    }
+}
+
+#[cfg(not(loom))]
+impl Drop for Entered {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.with(|flag| flag.set(false));
+    }
+}
+
+/// Allocator for chunks and oversized blocks.
+///
+/// Forwards to `Global` with the thread marked as inside [`Backing`],
+/// so that when [`OneRingAlloc`] is the global allocator these requests
+/// go to `System` instead of recursing into the rings.
+/// Blocks are always deallocated through [`Backing`] as well,
+/// so they are returned to `System`.
+#[derive(Clone, Copy)]
+struct Backing;
+
+unsafe impl Allocator for Backing {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(not(loom))]
+        let _entered = Entered::enter_backing();
+        Global.allocate(layout)
+    }
+
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(not(loom))]
+        let _entered = Entered::enter_backing();
+        Global.allocate_zeroed(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        #[cfg(not(loom))]
+        let _entered = Entered::enter_backing();
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
 struct LocalRing<T> {
     // Head of the ring.
     // This is the current chunk.
@@ -51,6 +200,49 @@ impl<T> LocalRing<T> {
     }
 }
 
+/// Bytes deallocated by the thread from a chunk and not accounted in it yet.
+struct DeferredFrees<T> {
+    chunk: Cell<Option<NonNull<T>>>,
+    bytes: Cell<usize>,
+}
+
+impl<T> DeferredFrees<T> {
+    const fn new() -> Self {
+        DeferredFrees {
+            chunk: Cell::new(None),
+            bytes: Cell::new(0),
+        }
+    }
+}
+
+impl<const N: usize> DeferredFrees<Chunk<N>> {
+    #[cfg(feature = "deferred-frees")]
+    #[inline(always)]
+    fn add(&self, chunk: NonNull<Chunk<N>>, size: usize) {
+        if self.chunk.get() != Some(chunk) {
+            self.flush();
+            self.chunk.set(Some(chunk));
+        }
+
+        let bytes = self.bytes.get() + size;
+        self.bytes.set(bytes);
+
+        if bytes >= DEFERRED_FREES_THRESHOLD {
+            self.flush();
+        }
+    }
+
+    /// Accounts deferred bytes in the chunk.
+    #[inline(always)]
+    fn flush(&self) {
+        if let Some(chunk) = self.chunk.take() {
+            // Safety: chunk with unaccounted bytes is never unused,
+            // so it is not freed.
+            unsafe { chunk.as_ref().release(self.bytes.take()) };
+        }
+    }
+}
+
 struct GlobalRing<T> {
     // Head of the ring.
     // This is the current chunk.
@@ -70,10 +262,317 @@ impl<T> GlobalRing<T> {
     }
 }
 
+/// Global ring guarded by a lock, with lock-free stack of orphaned chunks.
+///
+/// Exiting thread that can't acquire the lock pushes its chunks to the stack,
+/// and they are adopted into the ring when it is locked next time.
+struct SharedRing<T> {
+    ring: Mutex<GlobalRing<T>>,
+
+    // Chunks linked by `next` field.
+    orphans: AtomicPtr<T>,
+
+    /// Maximum number of chunks the ring retains.
+    /// See [`OneRingAlloc::set_global_chunk_cap`].
+    cap: crate::sync::AtomicUsize,
+}
+
+impl<T> SharedRing<T> {
+    #[cfg(not(loom))]
+    const fn new() -> Self {
+        SharedRing {
+            ring: Mutex::new(GlobalRing::new()),
+            orphans: AtomicPtr::new(core::ptr::null_mut()),
+            cap: crate::sync::AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    #[cfg(loom)]
+    fn new() -> Self {
+        SharedRing {
+            ring: Mutex::new(GlobalRing::new()),
+            orphans: AtomicPtr::new(core::ptr::null_mut()),
+            cap: crate::sync::AtomicUsize::new(usize::MAX),
+        }
+    }
+}
+
+impl<const N: usize> SharedRing<Chunk<N>> {
+    /// Locks the ring, adopting orphaned chunks.
+    #[inline(always)]
+    fn lock(&self) -> impl DerefMut<Target = GlobalRing<Chunk<N>>> + '_ {
+        let mut ring = self.ring.lock();
+        self.adopt(&mut ring);
+        ring
+    }
+
+    /// Attempts to lock the ring without blocking, spinning for a bounded time.
+    #[inline(always)]
+    fn try_lock(&self) -> Option<impl DerefMut<Target = GlobalRing<Chunk<N>>> + '_> {
+        for _ in 0..RETIRE_LOCK_ATTEMPTS {
+            if let Some(mut ring) = self.ring.try_lock() {
+                self.adopt(&mut ring);
+                return Some(ring);
+            }
+            core::hint::spin_loop();
+        }
+        None
+    }
+
+    #[inline(always)]
+    fn get_mut(&mut self) -> &mut GlobalRing<Chunk<N>> {
+        let chain = self.orphans.swap(core::ptr::null_mut(), Ordering::Acquire);
+        let ring = self.ring.get_mut();
+        Self::adopt_chain(chain, ring);
+        ring
+    }
+
+    /// Moves orphaned chunks to the end of the locked ring.
+    #[inline(always)]
+    fn adopt(&self, ring: &mut GlobalRing<Chunk<N>>) {
+        if self.orphans.load(Ordering::Relaxed).is_null() {
+            return;
+        }
+
+        let chain = self.orphans.swap(core::ptr::null_mut(), Ordering::Acquire);
+        Self::adopt_chain(chain, ring);
+        self.enforce_cap(ring);
+    }
+
+    /// Frees oldest unused chunks of the locked ring
+    /// while it holds more chunks than the cap.
+    ///
+    /// Chunks are appended to the ring, so the oldest are closest to the head.
+    /// Chunks with live blocks are kept even if the ring stays over the cap.
+    #[inline(always)]
+    fn enforce_cap(&self, ring: &mut GlobalRing<Chunk<N>>) {
+        let cap = self.cap.load(Ordering::Relaxed);
+        if cap != usize::MAX {
+            Self::evict(ring, cap);
+        }
+    }
+
+    #[cold]
+    fn evict(ring: &mut GlobalRing<Chunk<N>>, cap: usize) {
+        let mut chunks = 0usize;
+        let mut chunk = ring.head;
+        while let Some(c) = chunk {
+            chunks += 1;
+            // Safety: chunks in the ring are always valid.
+            chunk = unsafe { c.as_ref().next() };
+        }
+        let mut excess = chunks.saturating_sub(cap);
+
+        let mut chunk = &mut ring.head;
+        let mut tail = None;
+
+        while let Some(mut c) = *chunk {
+            if excess > 0 && unsafe { c.as_ref().unused() } {
+                excess -= 1;
+
+                // Safety: chunks in the ring are always valid.
+                let next = unsafe { c.as_mut().next() };
+                *chunk = next;
+                if let Some(mut next) = next {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { *next.as_mut().prev.get_mut() = tail };
+                }
+
+                // Safety: `c` is valid pointer to `Chunk` allocated by `Backing`
+                // and it is not linked into the ring anymore.
+                unsafe {
+                    Chunk::free(c, Backing);
+                }
+            } else {
+                tail = Some(c);
+
+                // Safety: chunks in the ring are always valid.
+                chunk = unsafe { c.as_mut().next.get_mut() };
+            }
+        }
+
+        ring.tail = tail;
+    }
+
+    #[cold]
+    fn adopt_chain(chain: *mut Chunk<N>, ring: &mut GlobalRing<Chunk<N>>) {
+        let head = match NonNull::new(chain) {
+            None => return,
+            Some(head) => head,
+        };
+
+        // Orphans are linked by `next` only, restore `prev` links.
+        let mut tail = head;
+        // Safety: orphaned chunks are valid and owned by the stack.
+        while let Some(next) = unsafe { tail.as_ref().next() } {
+            unsafe { next.as_ref().prev.set(Some(tail)) };
+            tail = next;
+        }
+        unsafe { head.as_ref().prev.set(None) };
+
+        let orphans = LocalRing::new();
+        orphans.head.set(Some(head));
+        orphans.tail.set(Some(tail));
+        LocalRings::append(&orphans, ring);
+    }
+
+    /// Pushes all chunks of the local ring to the stack of orphans.
+    fn orphan(&self, ring: &LocalRing<Chunk<N>>) {
+        let (head, tail) = match (ring.head.take(), ring.tail.take()) {
+            (Some(head), Some(tail)) => (head, tail),
+            _ => return,
+        };
+
+        let mut top = self.orphans.load(Ordering::Relaxed);
+        loop {
+            // Safety: chunks are owned by this thread until pushed.
+            unsafe { tail.as_ref().next.set(NonNull::new(top)) };
+
+            match self.orphans.compare_exchange_weak(
+                top,
+                head.as_ptr(),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => top = current,
+            }
+        }
+    }
+}
+
+/// Thread's rings pinned to a slot.
+struct PinnedRings {
+    slot: usize,
+    tiny_ring: GlobalRing<TinyChunk>,
+    small_ring: GlobalRing<SmallChunk>,
+    large_ring: GlobalRing<LargeChunk>,
+}
+
+/// Cache of deallocated oversized blocks, bucketed by rounded size.
+///
+/// Sizes between two consecutive powers of two are split into
+/// [`OVERSIZED_CACHE_STEPS`] buckets.
+/// Cached blocks form intrusive singly-linked lists,
+/// each block stores pointer to the next one in its first word.
+struct OversizedCache {
+    heads: [Option<NonNull<u8>>; OVERSIZED_CACHE_BUCKETS],
+    counts: [usize; OVERSIZED_CACHE_BUCKETS],
+    bytes: usize,
+}
+
+impl OversizedCache {
+    const fn new() -> Self {
+        OversizedCache {
+            heads: [None; OVERSIZED_CACHE_BUCKETS],
+            counts: [0; OVERSIZED_CACHE_BUCKETS],
+            bytes: 0,
+        }
+    }
+
+    /// Returns bucket and layout of the cached block for oversized `layout`.
+    /// Returns `None` if blocks with this layout are not cached.
+    #[inline(always)]
+    fn bucket(layout: Layout) -> Option<(usize, Layout)> {
+        if layout.align() > OVERSIZED_CACHE_ALIGN || layout.size() > OVERSIZED_CACHE_MAX_SIZE {
+            return None;
+        }
+
+        debug_assert!(layout.size() > LARGE_ALLOCATION_MAX_SIZE);
+
+        // Size is in `(2^pow, 2^(pow + 1)]` and is rounded up to the step within it.
+        let pow = usize::BITS - 1 - (layout.size() - 1).leading_zeros();
+        let step = 1 << (pow - OVERSIZED_CACHE_STEPS.trailing_zeros());
+        let size = (layout.size() + step - 1) & !(step - 1);
+
+        let bucket = (pow - LARGE_ALLOCATION_MAX_SIZE.trailing_zeros()) as usize
+            * OVERSIZED_CACHE_STEPS
+            + size / step
+            - OVERSIZED_CACHE_STEPS
+            - 1;
+
+        debug_assert_eq!(Self::bucket_size(bucket), size);
+
+        // Safety: size is not larger than `OVERSIZED_CACHE_MAX_SIZE`
+        // and multiple of `OVERSIZED_CACHE_ALIGN`.
+        let layout = unsafe { Layout::from_size_align_unchecked(size, OVERSIZED_CACHE_ALIGN) };
+        Some((bucket, layout))
+    }
+
+    /// Returns size of blocks in the bucket.
+    #[inline(always)]
+    const fn bucket_size(bucket: usize) -> usize {
+        let pow =
+            LARGE_ALLOCATION_MAX_SIZE.trailing_zeros() as usize + bucket / OVERSIZED_CACHE_STEPS;
+        let step = 1 << (pow - OVERSIZED_CACHE_STEPS.trailing_zeros() as usize);
+        (OVERSIZED_CACHE_STEPS + 1 + bucket % OVERSIZED_CACHE_STEPS) * step
+    }
+
+    fn pop(&mut self, bucket: usize, layout: Layout) -> Option<NonNull<u8>> {
+        let block = self.heads[bucket]?;
+
+        // Safety: cached blocks store pointer to the next block in first word.
+        self.heads[bucket] = unsafe { *block.as_ptr().cast::<Option<NonNull<u8>>>() };
+        self.counts[bucket] -= 1;
+        self.bytes -= layout.size();
+        Some(block)
+    }
+
+    /// Pushes block to the cache.
+    /// Returns `false` if cache is full.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be allocated from `Backing` with `layout` returned by [`OversizedCache::bucket`].
+    unsafe fn push(&mut self, bucket: usize, layout: Layout, block: NonNull<u8>) -> bool {
+        if self.counts[bucket] >= OVERSIZED_CACHE_BUCKET_CAPACITY
+            || self.bytes + layout.size() > OVERSIZED_CACHE_MAX_BYTES
+        {
+            return false;
+        }
+
+        // Safety: block is large enough and aligned for a pointer.
+        unsafe {
+            block
+                .as_ptr()
+                .cast::<Option<NonNull<u8>>>()
+                .write(self.heads[bucket])
+        };
+        self.heads[bucket] = Some(block);
+        self.counts[bucket] += 1;
+        self.bytes += layout.size();
+        true
+    }
+
+    /// Frees all cached blocks.
+    fn clean(&mut self) {
+        for bucket in 0..OVERSIZED_CACHE_BUCKETS {
+            // Safety: size is not larger than `OVERSIZED_CACHE_MAX_SIZE`
+            // and multiple of `OVERSIZED_CACHE_ALIGN`.
+            let layout = unsafe {
+                Layout::from_size_align_unchecked(Self::bucket_size(bucket), OVERSIZED_CACHE_ALIGN)
+            };
+            while let Some(block) = self.pop(bucket, layout) {
+                // Safety: cached blocks are allocated from `Backing` with this layout.
+                unsafe { Backing.deallocate(block, layout) };
+            }
+        }
+    }
+}
+
 struct GlobalRings {
-    tiny_ring: Mutex<GlobalRing<TinyChunk>>,
-    small_ring: Mutex<GlobalRing<SmallChunk>>,
-    large_ring: Mutex<GlobalRing<LargeChunk>>,
+    tiny_ring: SharedRing<TinyChunk>,
+    small_ring: SharedRing<SmallChunk>,
+    large_ring: SharedRing<LargeChunk>,
+    pinned: Mutex<Vec<PinnedRings>>,
+    oversized: Mutex<OversizedCache>,
+
+    /// Size classes enabled for allocations.
+    /// See [`OneRingAlloc::configure_classes`].
+    #[cfg(not(feature = "aligned-chunks"))]
+    tiny_enabled: AtomicBool,
+    #[cfg(not(feature = "aligned-chunks"))]
+    small_enabled: AtomicBool,
 }
 
 impl Drop for GlobalRings {
@@ -81,39 +580,174 @@ impl Drop for GlobalRings {
         Self::clean(self.tiny_ring.get_mut());
         Self::clean(self.small_ring.get_mut());
         Self::clean(self.large_ring.get_mut());
+
+        for pinned in self.pinned.get_mut().iter_mut() {
+            Self::clean(&mut pinned.tiny_ring);
+            Self::clean(&mut pinned.small_ring);
+            Self::clean(&mut pinned.large_ring);
+        }
+
+        self.oversized.get_mut().clean();
     }
 }
 
 impl GlobalRings {
+    #[inline(always)]
+    fn tiny_enabled(&self) -> bool {
+        #[cfg(not(feature = "aligned-chunks"))]
+        return self.tiny_enabled.load(Ordering::Relaxed);
+
+        #[cfg(feature = "aligned-chunks")]
+        true
+    }
+
+    #[inline(always)]
+    fn small_enabled(&self) -> bool {
+        #[cfg(not(feature = "aligned-chunks"))]
+        return self.small_enabled.load(Ordering::Relaxed);
+
+        #[cfg(feature = "aligned-chunks")]
+        true
+    }
+
     #[inline(always)]
     fn clean_all(&self) {
         Self::clean(&mut self.tiny_ring.lock());
         Self::clean(&mut self.small_ring.lock());
         Self::clean(&mut self.large_ring.lock());
+        self.oversized.lock().clean();
+    }
+
+    /// Sets maximum number of chunks retained by global ring of the size class
+    /// and frees excess unused chunks.
+    fn set_chunk_cap(&self, class: SizeClass, cap: usize) {
+        fn set<const N: usize>(ring: &SharedRing<Chunk<N>>, cap: usize) {
+            ring.cap.store(cap, Ordering::Relaxed);
+            ring.enforce_cap(&mut ring.lock());
+        }
+
+        match class {
+            SizeClass::Tiny => set(&self.tiny_ring, cap),
+            SizeClass::Small => set(&self.small_ring, cap),
+            SizeClass::Large => set(&self.large_ring, cap),
+        }
+    }
+
+    fn compact_all(&self) {
+        Self::compact(&mut self.tiny_ring.lock());
+        Self::compact(&mut self.small_ring.lock());
+        Self::compact(&mut self.large_ring.lock());
+        self.oversized.lock().clean();
+    }
+
+    /// Frees unused chunks of the ring and sorts the rest
+    /// by number of live bytes, so that mostly-free chunks are stolen first.
+    fn compact<const N: usize>(ring: &mut GlobalRing<Chunk<N>>) {
+        Self::clean(ring);
+
+        // Insertion sort, stable for chunks with equal number of live bytes.
+        // Global rings are short and this must not allocate.
+        let mut sorted: Option<NonNull<Chunk<N>>> = None;
+        let mut chunk = ring.head.take();
+
+        while let Some(mut c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_mut = unsafe { c.as_mut() };
+            chunk = c_mut.next.get_mut().take();
+            let live = c_mut.live();
+
+            let mut place = &mut sorted;
+            while let Some(mut p) = *place {
+                // Safety: chunks in the ring are always valid.
+                let p_mut = unsafe { p.as_mut() };
+                if p_mut.live() > live {
+                    break;
+                }
+                place = p_mut.next.get_mut();
+            }
+
+            *c_mut.next.get_mut() = place.take();
+            *place = Some(c);
+        }
+
+        let mut tail = None;
+        let mut chunk = sorted;
+        while let Some(mut c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_mut = unsafe { c.as_mut() };
+            *c_mut.prev.get_mut() = tail;
+            tail = Some(c);
+            chunk = *c_mut.next.get_mut();
+        }
+
+        ring.head = sorted;
+        ring.tail = tail;
+    }
+
+    /// Frees unused chunks donated by the thread with given id.
+    /// Returns number of freed chunks.
+    #[cfg(feature = "donor-tracking")]
+    fn drain_donations(&self, donor: usize) -> usize {
+        Self::free_unused(&mut self.tiny_ring.lock(), |c| c.donor.get() == donor)
+            + Self::free_unused(&mut self.small_ring.lock(), |c| c.donor.get() == donor)
+            + Self::free_unused(&mut self.large_ring.lock(), |c| c.donor.get() == donor)
     }
 
     #[inline(always)]
     fn clean<const N: usize>(ring: &mut GlobalRing<Chunk<N>>) {
+        Self::free_unused(ring, |_| true);
+    }
+
+    /// Frees unused chunks of the ring that match the predicate.
+    /// Returns number of freed chunks.
+    #[inline(always)]
+    fn free_unused<const N: usize>(
+        ring: &mut GlobalRing<Chunk<N>>,
+        f: impl Fn(&Chunk<N>) -> bool,
+    ) -> usize {
         let mut chunk = &mut ring.head;
+        let mut tail = None;
+        let mut count = 0;
 
         while let Some(mut c) = *chunk {
-            if unsafe { c.as_ref().unused() } {
+            if unsafe { c.as_ref().unused() && f(c.as_ref()) } {
+                count += 1;
+
                 // Safety: chunks in the ring are always valid.
-                *chunk = unsafe { c.as_mut().next() };
+                let next = unsafe { c.as_mut().next() };
+                *chunk = next;
+                if let Some(mut next) = next {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { *next.as_mut().prev.get_mut() = tail };
+                }
 
                 // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
                 unsafe {
-                    Chunk::free(c, Global);
+                    Chunk::free(c, Backing);
                 }
             } else {
+                tail = Some(c);
+
                 // Safety: chunks in the ring are always valid.
                 chunk = unsafe { c.as_mut().next.get_mut() };
             }
         }
 
-        if ring.head.is_none() {
-            ring.tail = None;
-        }
+        ring.tail = tail;
+        count
+    }
+}
+
+/// Adds chunks of the ring starting with `chunk` to the stats.
+fn ring_stats<const N: usize>(mut chunk: Option<NonNull<Chunk<N>>>, stats: &mut RingStats) {
+    while let Some(c) = chunk {
+        // Safety: chunks in the ring are always valid.
+        let c = unsafe { c.as_ref() };
+
+        stats.chunks += 1;
+        stats.reserved += N;
+        stats.live += c.live();
+        chunk = c.next();
     }
 }
 
@@ -124,91 +758,612 @@ struct LocalRings {
     tiny_ring: LocalRing<TinyChunk>,
     small_ring: LocalRing<SmallChunk>,
     large_ring: LocalRing<LargeChunk>,
+    #[cfg(feature = "deferred-frees")]
+    defer_frees: Cell<bool>,
+    tiny_deferred: DeferredFrees<TinyChunk>,
+    small_deferred: DeferredFrees<SmallChunk>,
+    large_deferred: DeferredFrees<LargeChunk>,
+
+    // Index of dedicated rings these rings belong to.
+    // `None` for rings of `OneRingAlloc`.
+    namespace: Option<usize>,
 }
 
 impl Drop for LocalRings {
     fn drop(&mut self) {
-        self.clean_all();
-        self.flush_all();
+        self.exit();
     }
 }
 
 impl LocalRings {
+    const fn new(namespace: Option<usize>) -> Self {
+        LocalRings {
+            tiny_ring: LocalRing::new(),
+            small_ring: LocalRing::new(),
+            large_ring: LocalRing::new(),
+            #[cfg(feature = "deferred-frees")]
+            defer_frees: Cell::new(false),
+            tiny_deferred: DeferredFrees::new(),
+            small_deferred: DeferredFrees::new(),
+            large_deferred: DeferredFrees::new(),
+            namespace,
+        }
+    }
+
+    /// Allocates block that is not oversized from these rings,
+    /// taking chunks from `global` rings when needed.
+    ///
+    /// If `detached` is `true`, global rings are never waited for.
+    #[inline(always)]
+    fn allocate(
+        &self,
+        global: &GlobalRings,
+        layout: Layout,
+        detached: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(layout_max(layout) <= LARGE_ALLOCATION_MAX_SIZE);
+
+        // Disabled size classes are served by the next enabled one.
+        if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE && global.tiny_enabled() {
+            _allocate(
+                &self.tiny_ring,
+                &self.tiny_deferred,
+                &global.tiny_ring,
+                layout,
+                detached,
+            )
+        } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE && global.small_enabled() {
+            _allocate(
+                &self.small_ring,
+                &self.small_deferred,
+                &global.small_ring,
+                layout,
+                detached,
+            )
+        } else {
+            _allocate(
+                &self.large_ring,
+                &self.large_deferred,
+                &global.large_ring,
+                layout,
+                detached,
+            )
+        }
+    }
+
+    /// Returns global rings that chunks of these rings are moved to.
+    #[inline(always)]
+    fn global(&self) -> &'static GlobalRings {
+        RingSet(self.namespace).global()
+    }
+
+    #[inline(always)]
+    fn flush_deferred(&self) {
+        self.tiny_deferred.flush();
+        self.small_deferred.flush();
+        self.large_deferred.flush();
+    }
+
     #[inline(always)]
     fn clean_all(&self) {
-        Self::clean(&self.tiny_ring);
-        Self::clean(&self.small_ring);
-        Self::clean(&self.large_ring);
+        // Chunks with deferred frees would not be recognized as unused.
+        self.flush_deferred();
+
+        Self::clean(&self.tiny_ring, false);
+        Self::clean(&self.small_ring, false);
+        Self::clean(&self.large_ring, false);
     }
 
+    /// Frees unused chunks of the ring.
+    ///
+    /// If `keep_spare` is `true`, first unused chunk is reset, unlinked
+    /// and returned instead of being freed.
     #[inline(always)]
-    fn clean<const N: usize>(ring: &LocalRing<Chunk<N>>) {
+    fn clean<const N: usize>(
+        ring: &LocalRing<Chunk<N>>,
+        keep_spare: bool,
+    ) -> Option<NonNull<Chunk<N>>> {
         let mut chunk = &ring.head;
+        let mut tail = None;
+        let mut spare = None;
 
         while let Some(c) = chunk.get() {
             if unsafe { c.as_ref().unused() } {
                 // Safety: chunks in the ring are always valid.
-                chunk.set(unsafe { c.as_ref().next() });
+                let next = unsafe { c.as_ref().next() };
+                chunk.set(next);
+                if let Some(next) = next {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { next.as_ref().prev.set(tail) };
+                }
 
-                // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
-                unsafe {
-                    Chunk::free(c, Global);
+                if keep_spare && spare.is_none() {
+                    // Safety: chunks in the ring are always valid.
+                    let c_ref = unsafe { c.as_ref() };
+                    let reset = c_ref.reset();
+                    debug_assert!(reset);
+                    c_ref.next.set(None);
+                    c_ref.prev.set(None);
+                    spare = Some(c);
+                } else {
+                    // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
+                    unsafe {
+                        Chunk::free(c, Backing);
+                    }
                 }
             } else {
+                tail = Some(c);
+
                 // Safety: chunks in the ring are always valid.
                 chunk = unsafe { &c.as_ref().next };
             }
         }
 
-        if ring.head.get().is_none() {
-            ring.tail.set(None);
-        }
+        ring.tail.set(tail);
+        spare
     }
 
-    #[inline(always)]
-    fn flush_all(&mut self) {
-        Self::flush(&mut self.tiny_ring, &GLOBAL_RINGS.tiny_ring);
-        Self::flush(&mut self.small_ring, &GLOBAL_RINGS.small_ring);
-        Self::flush(&mut self.large_ring, &GLOBAL_RINGS.large_ring);
+    /// Releases chunks of exiting thread.
+    fn exit(&self) {
+        // Chunks with deferred frees would not be recognized as unused.
+        self.flush_deferred();
+
+        let global = self.global();
+        Self::retire(&self.tiny_ring, &global.tiny_ring);
+        Self::retire(&self.small_ring, &global.small_ring);
+        Self::retire(&self.large_ring, &global.large_ring);
     }
 
+    /// Frees unused chunks of the ring and moves the rest to global ring.
+    ///
+    /// If global ring is empty, one unused chunk is moved there as well,
+    /// so that next thread steals it instead of allocating fresh chunk.
+    /// This makes spawning short-lived threads cheaper,
+    /// while global rings never accumulate more than one spare chunk per exit.
+    ///
+    /// Global ring is not locked when there's nothing to move.
     #[inline(always)]
-    fn flush<const N: usize>(ring: &mut LocalRing<Chunk<N>>, global: &Mutex<GlobalRing<Chunk<N>>>) {
-        match (ring.head.take(), ring.tail.take()) {
-            (None, None) => {}
-            (Some(head), Some(tail)) => {
-                let mut global = global.lock();
+    fn retire<const N: usize>(ring: &LocalRing<Chunk<N>>, global_ring: &SharedRing<Chunk<N>>) {
+        let spare = Self::clean(ring, true);
+        if spare.is_none() && ring.head.get().is_none() {
+            return;
+        }
 
-                match (global.head, global.tail) {
-                    (None, None) => {
-                        global.head = Some(head);
-                        global.tail = Some(tail);
-                    }
-                    (Some(_g_head), Some(mut g_tail)) => unsafe {
-                        *g_tail.as_mut().next.get_mut() = Some(head);
-                        global.tail = Some(tail);
-                    },
-                    _ => unsafe { unreachable_unchecked() },
-                }
+        #[cfg(feature = "donor-tracking")]
+        {
+            let donor = donor_id();
+            if let Some(spare) = spare {
+                // Safety: `spare` is valid chunk owned by this thread.
+                unsafe { spare.as_ref().donor.set(donor) };
+            }
+            let mut chunk = ring.head.get();
+            while let Some(c) = chunk {
+                // Safety: chunks in the ring are always valid.
+                let c = unsafe { c.as_ref() };
+                c.donor.set(donor);
+                chunk = c.next();
             }
-            _ => unsafe { unreachable_unchecked() },
         }
-    }
-}
 
-thread_local! {
-    static LOCAL_RINGS: LocalRings = const { LocalRings {
-        tiny_ring: LocalRing::new(),
-        small_ring: LocalRing::new(),
-        large_ring: LocalRing::new(),
-    } };
-}
+        let spare = {
+            let mut global = match global_ring.try_lock() {
+                Some(global) => global,
+                None => {
+                    // Don't block exiting thread on contended lock.
+                    // Chunks in use are adopted by the global ring later.
+                    if let Some(spare) = spare {
+                        // Safety: `spare` is unlinked chunk allocated by `Backing`.
+                        unsafe { Chunk::free(spare, Backing) };
+                    }
+                    global_ring.orphan(ring);
+                    return;
+                }
+            };
+
+            let spare = match spare {
+                Some(spare) if global.head.is_none() => {
+                    let spare_ring = LocalRing::new();
+                    spare_ring.head.set(Some(spare));
+                    spare_ring.tail.set(Some(spare));
+                    Self::append(&spare_ring, &mut global);
+                    None
+                }
+                spare => spare,
+            };
 
-static GLOBAL_RINGS: GlobalRings = GlobalRings {
-    tiny_ring: Mutex::new(GlobalRing::new()),
-    small_ring: Mutex::new(GlobalRing::new()),
-    large_ring: Mutex::new(GlobalRing::new()),
-};
+            Self::append(ring, &mut global);
+            global_ring.enforce_cap(&mut global);
+            spare
+        };
+
+        if let Some(spare) = spare {
+            // Safety: `spare` is unlinked chunk allocated by `Backing`.
+            unsafe { Chunk::free(spare, Backing) };
+        }
+    }
+
+    #[inline(always)]
+    fn donate_all(&self) -> usize {
+        self.flush_deferred();
+
+        let global = self.global();
+        Self::donate(&self.tiny_ring, &global.tiny_ring)
+            + Self::donate(&self.small_ring, &global.small_ring)
+            + Self::donate(&self.large_ring, &global.large_ring)
+    }
+
+    /// Moves unused chunks from local ring to the end of global ring.
+    /// Returns number of moved chunks.
+    fn donate<const N: usize>(ring: &LocalRing<Chunk<N>>, global: &SharedRing<Chunk<N>>) -> usize {
+        let donated = LocalRing::new();
+        let mut count = 0;
+
+        let mut chunk = &ring.head;
+        let mut tail = None;
+
+        while let Some(c) = chunk.get() {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            // Reset so that stealing thread starts from the beginning of the chunk.
+            if c_ref.reset() {
+                #[cfg(feature = "donor-tracking")]
+                c_ref.donor.set(donor_id());
+
+                let next = c_ref.next();
+                chunk.set(next);
+                if let Some(next) = next {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { next.as_ref().prev.set(tail) };
+                }
+                c_ref.next.set(None);
+                c_ref.prev.set(donated.tail.get());
+
+                match donated.tail.get() {
+                    None => donated.head.set(Some(c)),
+                    // Safety: chunks in the ring are always valid.
+                    Some(t) => unsafe { t.as_ref().next.set(Some(c)) },
+                }
+                donated.tail.set(Some(c));
+                count += 1;
+            } else {
+                tail = Some(c);
+                chunk = &c_ref.next;
+            }
+        }
+
+        ring.tail.set(tail);
+
+        if count > 0 {
+            let mut ring = global.lock();
+            Self::append(&donated, &mut ring);
+            global.enforce_cap(&mut ring);
+        }
+        count
+    }
+
+    /// Moves all chunks from local ring to the end of global ring.
+    #[inline(always)]
+    fn append<const N: usize>(ring: &LocalRing<Chunk<N>>, global: &mut GlobalRing<Chunk<N>>) {
+        match (ring.head.take(), ring.tail.take()) {
+            (None, None) => {}
+            (Some(head), Some(tail)) => match (global.head, global.tail) {
+                (None, None) => {
+                    global.head = Some(head);
+                    global.tail = Some(tail);
+                }
+                (Some(_g_head), Some(mut g_tail)) => unsafe {
+                    *g_tail.as_mut().next.get_mut() = Some(head);
+                    head.as_ref().prev.set(Some(g_tail));
+                    global.tail = Some(tail);
+                },
+                _ => unsafe { unreachable_unchecked() },
+            },
+            _ => unsafe { unreachable_unchecked() },
+        }
+    }
+
+    /// Moves all chunks from global ring to the front of local ring.
+    #[inline(always)]
+    fn prepend<const N: usize>(ring: &LocalRing<Chunk<N>>, global: &mut GlobalRing<Chunk<N>>) {
+        match (global.head.take(), global.tail.take()) {
+            (None, None) => {}
+            (Some(g_head), Some(mut g_tail)) => match (ring.head.get(), ring.tail.get()) {
+                (None, None) => {
+                    ring.head.set(Some(g_head));
+                    ring.tail.set(Some(g_tail));
+                }
+                (Some(head), Some(_tail)) => unsafe {
+                    *g_tail.as_mut().next.get_mut() = Some(head);
+                    head.as_ref().prev.set(Some(g_tail));
+                    ring.head.set(Some(g_head));
+                },
+                _ => unsafe { unreachable_unchecked() },
+            },
+            _ => unsafe { unreachable_unchecked() },
+        }
+    }
+}
+
+#[cfg(not(loom))]
+std::thread_local! {
+    static LOCAL_RINGS: LocalRings = const { LocalRings::new(None) };
+
+    static DEDICATED_LOCAL_RINGS: [LocalRings; DEDICATED_NAMESPACES] = const { dedicated_local_rings() };
+}
+
+/// Set when any thread enables deferred frees.
+/// Allows to skip thread-local access on deallocation otherwise.
+#[cfg(feature = "deferred-frees")]
+static DEFERRED_FREES_USED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(not(loom))]
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_GLOBAL_RINGS: GlobalRings = GlobalRings {
+    tiny_ring: SharedRing::new(),
+    small_ring: SharedRing::new(),
+    large_ring: SharedRing::new(),
+    pinned: Mutex::new(Vec::new()),
+    oversized: Mutex::new(OversizedCache::new()),
+    #[cfg(not(feature = "aligned-chunks"))]
+    tiny_enabled: AtomicBool::new(true),
+    #[cfg(not(feature = "aligned-chunks"))]
+    small_enabled: AtomicBool::new(true),
+};
+
+#[cfg(not(loom))]
+static GLOBAL_RINGS: GlobalRings = EMPTY_GLOBAL_RINGS;
+
+#[cfg(not(loom))]
+static DEDICATED_GLOBAL_RINGS: [GlobalRings; DEDICATED_NAMESPACES] =
+    [EMPTY_GLOBAL_RINGS; DEDICATED_NAMESPACES];
+
+/// Number of ring sets used by [`PerCpuOneRing`].
+/// CPUs with larger ids share ring sets.
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+const PER_CPU_SLOTS: usize = 64;
+
+/// Rings shared by threads running on the same CPU.
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+struct CpuRings {
+    rings: Mutex<LocalRings>,
+}
+
+// Safety: rings are accessed only with the lock held.
+// Chunks are handed over between threads through the lock,
+// the same way they are handed over through global rings.
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+unsafe impl Sync for CpuRings {}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+static PER_CPU_RINGS: [CpuRings; PER_CPU_SLOTS] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: CpuRings = CpuRings {
+        rings: Mutex::new(LocalRings::new(None)),
+    };
+
+    [EMPTY; PER_CPU_SLOTS]
+};
+
+/// Returns ring set of the CPU the calling thread is running on.
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+#[inline(always)]
+fn current_cpu_rings() -> &'static CpuRings {
+    // Safety: `sched_getcpu` has no preconditions.
+    let cpu = unsafe { libc::sched_getcpu() };
+
+    // `-1` is returned if CPU can't be determined, any slot works then.
+    let cpu = if cpu < 0 { 0 } else { cpu as usize };
+    &PER_CPU_RINGS[cpu % PER_CPU_SLOTS]
+}
+
+/// Returns thread-local rings of all dedicated namespaces.
+#[cfg(not(loom))]
+const fn dedicated_local_rings() -> [LocalRings; DEDICATED_NAMESPACES] {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: LocalRings = LocalRings::new(None);
+
+    let mut rings = [EMPTY; DEDICATED_NAMESPACES];
+    let mut namespace = 0;
+    while namespace < DEDICATED_NAMESPACES {
+        rings[namespace].namespace = Some(namespace);
+        namespace += 1;
+    }
+    rings
+}
+
+// `loom` primitives can't be constructed in const context.
+// Dedicated rings are not modelled.
+#[cfg(loom)]
+loom::thread_local! {
+    static LOCAL_RINGS: LocalRings = LocalRings::new(None);
+}
+
+#[cfg(loom)]
+loom::lazy_static! {
+    static ref GLOBAL_RINGS: GlobalRings = GlobalRings {
+        tiny_ring: SharedRing::new(),
+        small_ring: SharedRing::new(),
+        large_ring: SharedRing::new(),
+        pinned: Mutex::new(Vec::new()),
+        oversized: Mutex::new(OversizedCache::new()),
+        #[cfg(not(feature = "aligned-chunks"))]
+        tiny_enabled: AtomicBool::new(true),
+        #[cfg(not(feature = "aligned-chunks"))]
+        small_enabled: AtomicBool::new(true),
+    };
+}
+
+/// Number of dedicated ring sets available with [`OneRingAlloc::dedicated`].
+pub const DEDICATED_NAMESPACES: usize = 8;
+
+/// Set of thread-local and global rings.
+///
+/// `None` selects rings of [`OneRingAlloc`],
+/// `Some(namespace)` selects dedicated rings.
+#[derive(Clone, Copy, Debug)]
+struct RingSet(Option<usize>);
+
+impl RingSet {
+    #[inline(always)]
+    fn global(self) -> &'static GlobalRings {
+        match self.0 {
+            None => &GLOBAL_RINGS,
+            #[cfg(not(loom))]
+            Some(namespace) => &DEDICATED_GLOBAL_RINGS[namespace],
+            #[cfg(loom)]
+            Some(_) => unreachable!("Dedicated rings are not available with loom"),
+        }
+    }
+
+    /// Calls `f` with calling thread's rings of the set.
+    /// Returns `None` if thread-local storage is destroyed.
+    #[inline(always)]
+    fn local<R>(self, f: impl FnOnce(&LocalRings) -> R) -> Option<R> {
+        match self.0 {
+            None => LOCAL_RINGS.try_with(f).ok(),
+            #[cfg(not(loom))]
+            Some(namespace) => DEDICATED_LOCAL_RINGS
+                .try_with(|rings| f(&rings[namespace]))
+                .ok(),
+            #[cfg(loom)]
+            Some(_) => unreachable!("Dedicated rings are not available with loom"),
+        }
+    }
+
+    #[inline(always)]
+    fn allocate(self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(feature = "metrics")]
+        record_size(layout.size());
+
+        if layout_max(layout) > LARGE_ALLOCATION_MAX_SIZE {
+            return _allocate_oversized(layout, false);
+        }
+
+        let global = self.global();
+        match self.local(|rings| rings.allocate(global, layout, false)) {
+            Some(result) => result,
+            // Destructors of other thread-locals may allocate after the rings are destroyed.
+            None => self.allocate_detached(layout),
+        }
+    }
+
+    /// Allocates block without accessing thread-local rings
+    /// and without waiting for locks, as both may allocate.
+    ///
+    /// Block is allocated from temporary local rings that take chunks
+    /// from global rings of the set and move them back when dropped.
+    #[cold]
+    #[inline(never)]
+    fn allocate_detached(self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout_max(layout) > LARGE_ALLOCATION_MAX_SIZE {
+            return _allocate_oversized(layout, true);
+        }
+
+        let rings = LocalRings::new(self.0);
+        rings.allocate(self.global(), layout, true)
+    }
+
+    #[cfg(not(feature = "aligned-chunks"))]
+    fn configure_classes(self, tiny: bool, small: bool) {
+        let global = self.global();
+        global.tiny_enabled.store(tiny, Ordering::Relaxed);
+        global.small_enabled.store(small, Ordering::Relaxed);
+    }
+
+    fn clean_global(self) {
+        // Chunks with deferred frees would not be recognized as unused.
+        let _ = LOCAL_RINGS.try_with(|rings| rings.flush_deferred());
+        self.global().clean_all();
+    }
+
+    fn compact_global(self) {
+        let _ = LOCAL_RINGS.try_with(|rings| rings.flush_deferred());
+        self.global().compact_all();
+    }
+
+    fn set_global_chunk_cap(self, class: SizeClass, cap: usize) {
+        self.global().set_chunk_cap(class, cap);
+    }
+
+    #[cfg(feature = "donor-tracking")]
+    fn drain_thread_donations(self, thread_id: usize) -> usize {
+        let _ = LOCAL_RINGS.try_with(|rings| rings.flush_deferred());
+        self.global().drain_donations(thread_id)
+    }
+
+    #[cfg(test)]
+    fn global_live(self) -> [Vec<usize>; 3] {
+        fn collect<const N: usize>(mut chunk: Option<NonNull<Chunk<N>>>) -> Vec<usize> {
+            let mut live = Vec::new();
+            while let Some(c) = chunk {
+                // Safety: chunks in the ring are always valid.
+                let c = unsafe { c.as_ref() };
+                live.push(c.live());
+                chunk = c.next();
+            }
+            live
+        }
+
+        let global = self.global();
+        [
+            collect(global.tiny_ring.lock().head),
+            collect(global.small_ring.lock().head),
+            collect(global.large_ring.lock().head),
+        ]
+    }
+
+    fn clean_local(self) {
+        let _ = self.local(|rings| rings.clean_all());
+    }
+
+    fn flush_local_to_global(self) -> usize {
+        self.local(|rings| rings.donate_all()).unwrap_or(0)
+    }
+
+    fn thread_stats(self) -> RingStats {
+        let mut stats = RingStats::default();
+        let _ = self.local(|rings| {
+            ring_stats(rings.tiny_ring.head.get(), &mut stats);
+            ring_stats(rings.small_ring.head.get(), &mut stats);
+            ring_stats(rings.large_ring.head.get(), &mut stats);
+        });
+        stats
+    }
+
+    fn thread_initialized(self) -> bool {
+        self.local(|rings| {
+            rings.tiny_ring.head.get().is_some()
+                || rings.small_ring.head.get().is_some()
+                || rings.large_ring.head.get().is_some()
+        })
+        .unwrap_or(false)
+    }
+
+    fn snapshot(self) -> Snapshot {
+        Snapshot {
+            rings: self,
+            thread: self.thread_stats(),
+            global: self.global_stats(),
+        }
+    }
+
+    fn global_stats(self) -> RingStats {
+        let global = self.global();
+
+        let mut stats = RingStats::default();
+        ring_stats(global.tiny_ring.lock().head, &mut stats);
+        ring_stats(global.small_ring.lock().head, &mut stats);
+        ring_stats(global.large_ring.lock().head, &mut stats);
+
+        for pinned in global.pinned.lock().iter() {
+            ring_stats(pinned.tiny_ring.head, &mut stats);
+            ring_stats(pinned.small_ring.head, &mut stats);
+            ring_stats(pinned.large_ring.head, &mut stats);
+        }
+        stats
+    }
+}
 
 /// Global ring-allocator.
 ///
@@ -224,17 +1379,50 @@ static GLOBAL_RINGS: GlobalRings = GlobalRings {
 ///
 /// Each thread will use thread-local rings to rotate over chunks.
 /// On thread exit all unused chunks are freed and the rest is moved to global ring.
+/// If global ring of the size class is empty, one unused chunk is moved there
+/// instead of being freed, so short-lived threads don't allocate fresh chunks
+/// every time they are spawned.
 ///
 /// When thread-local ring cannot allocate memory it will steal global ring
 /// or allocate new chunk from global allocator if global ring is empty.
+///
+/// Blocks larger than 64 KiB are allocated from global allocator directly.
+/// Blocks up to 16 MiB have their sizes rounded up to a quarter
+/// of the power of two below them and, when deallocated,
+/// are kept in a global cache bounded to 64 MiB for reuse,
+/// so repeatedly allocated large buffers don't hit global allocator every time.
+/// [`OneRingAlloc::clean_global`] frees cached blocks.
+///
+/// Being ZST and [`Copy`], it is passed by value for free.
+/// `&OneRingAlloc` is an allocator as well, for generic code that takes
+/// allocators by reference.
+///
+/// ```
+/// use allocator_api2::{alloc::Allocator, vec::Vec};
+/// use ring_alloc::OneRingAlloc;
+///
+/// fn squares<A>(n: u32, alloc: A) -> Vec<u32, A>
+/// where
+///     A: Allocator + Copy,
+/// {
+///     let mut v = Vec::new_in(alloc);
+///     v.extend((0..n).map(|i| i * i));
+///     v
+/// }
+///
+/// assert_eq!(squares(4, OneRingAlloc), [0, 1, 4, 9]);
+/// assert_eq!(squares(4, &OneRingAlloc), [0, 1, 4, 9]);
+/// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OneRingAlloc;
 
 #[inline(always)]
 fn _allocate<const N: usize>(
     ring: &LocalRing<Chunk<N>>,
-    global: &Mutex<GlobalRing<Chunk<N>>>,
+    deferred: &DeferredFrees<Chunk<N>>,
+    global: &SharedRing<Chunk<N>>,
     layout: Layout,
+    detached: bool,
 ) -> Result<NonNull<[u8]>, AllocError> {
     // Try head chunk.
     if let Some(chunk_ptr) = ring.head.get() {
@@ -258,16 +1446,21 @@ fn _allocate<const N: usize>(
                     debug_assert_eq!(ring.tail.get(), ring.head.get());
                 }
                 Some(next_ptr) => {
+                    // Account deferred frees, so that next chunk may be reset.
+                    deferred.flush();
+
                     // Move head to tail and bring next one as head.
 
                     // Safety: tail is valid pointer to `Chunk` allocated by `self.allocator`.
                     let tail_chunk = unsafe { ring.tail.get().unwrap().as_ref() };
                     debug_assert_eq!(tail_chunk.next(), None);
                     tail_chunk.next.set(Some(chunk_ptr));
+                    chunk.prev.set(ring.tail.get());
                     ring.tail.set(Some(chunk_ptr));
                     ring.head.set(Some(next_ptr));
 
                     let next = unsafe { next_ptr.as_ref() };
+                    next.prev.set(None);
 
                     if next.reset() {
                         if let Some(ptr) = next.allocate(next_ptr, layout) {
@@ -291,7 +1484,13 @@ fn _allocate<const N: usize>(
     }
 
     // First grab chunks from global ring.
-    let (g_head, g_tail) = {
+    let (g_head, g_tail) = if detached {
+        // Fresh chunk is allocated if the ring is contended.
+        match global.try_lock() {
+            Some(mut global) => (global.head.take(), global.tail.take()),
+            None => (None, None),
+        }
+    } else {
         let mut global = global.lock();
 
         // Take all chunks from global ring.
@@ -310,6 +1509,7 @@ fn _allocate<const N: usize>(
                 }
                 (Some(head), Some(_tail)) => unsafe {
                     *g_tail.as_mut().next.get_mut() = Some(head);
+                    head.as_ref().prev.set(Some(g_tail));
                     ring.head.set(Some(g_head));
                 },
                 _ => unsafe { unreachable_unchecked() },
@@ -322,26 +1522,33 @@ fn _allocate<const N: usize>(
 
     let ptr = match ptr {
         None => {
-            let chunk_ptr = Chunk::<N>::new(Global)?;
+            let chunk_ptr = Chunk::<N>::new(Backing)?;
 
             // Safety: `chunk` is valid pointer to `Chunk` allocated by `self.allocator`.
             let chunk = unsafe { chunk_ptr.as_ref() };
 
-            let ptr = chunk
-                .allocate(chunk_ptr, layout)
-                .expect("Failed to allocate from fresh chunk");
+            // Safety: `chunk_ptr` is allocated by `Backing` and not linked.
+            let ptr = unsafe {
+                Chunk::fresh_block(chunk_ptr, chunk.allocate(chunk_ptr, layout), Backing)?
+            };
 
             // Put to head.
             chunk.next.set(ring.head.get());
 
             // If first chunk, put to tail.
-            if ring.tail.get().is_none() {
-                debug_assert_eq!(ring.head.get(), None);
+            match ring.head.get() {
+                None => {
+                    debug_assert_eq!(ring.tail.get(), None);
 
-                // Modify after asserts.
-                ring.tail.set(Some(chunk_ptr));
-            } else {
-                debug_assert!(ring.head.get().is_some());
+                    // Modify after asserts.
+                    ring.tail.set(Some(chunk_ptr));
+                }
+                Some(head_ptr) => {
+                    debug_assert!(ring.tail.get().is_some());
+
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { head_ptr.as_ref().prev.set(Some(chunk_ptr)) };
+                }
             }
 
             // Modify after asserts.
@@ -362,34 +1569,210 @@ fn _allocate<const N: usize>(
     })
 }
 
+#[cfg_attr(not(feature = "deferred-frees"), allow(unused_variables))]
 #[inline(always)]
-unsafe fn _deallocate<const N: usize>(ptr: NonNull<u8>, layout: Layout) {
+unsafe fn _deallocate<const N: usize>(
+    ptr: NonNull<u8>,
+    layout: Layout,
+    detached: bool,
+    deferred: fn(&LocalRings) -> &DeferredFrees<Chunk<N>>,
+) {
+    // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
+    #[cfg(feature = "deferred-frees")]
+    if !detached
+        && DEFERRED_FREES_USED.load(Ordering::Relaxed)
+        && unsafe { _deallocate_deferred(ptr, layout, deferred) }
+    {
+        return;
+    }
+
     // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
     unsafe {
         Chunk::<N>::deallocate(ptr.as_ptr(), layout);
     }
 }
 
+/// Deallocates block allocated from any rings of [`OneRingAlloc`].
+///
+/// If `detached` is `true`, thread-local rings are not accessed
+/// and locks are not waited for, as both may allocate.
+///
+/// # Safety
+///
+/// `ptr` must be allocated from the rings or by `_allocate_oversized`
+/// with layout that fits `layout`.
+#[inline(always)]
+unsafe fn _deallocate_block(ptr: NonNull<u8>, layout: Layout, detached: bool) {
+    if layout_max(layout) > LARGE_ALLOCATION_MAX_SIZE {
+        unsafe { _deallocate_oversized(ptr, layout, detached) }
+        return;
+    }
+
+    // Safety: `ptr` is allocated from a chunk with `layout`.
+    match unsafe { _chunk_size_of(ptr, layout) } {
+        TINY_ALLOCATION_CHUNK_SIZE => unsafe {
+            _deallocate::<{ TINY_ALLOCATION_CHUNK_SIZE }>(ptr, layout, detached, |rings| {
+                &rings.tiny_deferred
+            });
+        },
+        SMALL_ALLOCATION_CHUNK_SIZE => unsafe {
+            _deallocate::<{ SMALL_ALLOCATION_CHUNK_SIZE }>(ptr, layout, detached, |rings| {
+                &rings.small_deferred
+            });
+        },
+        _ => unsafe {
+            _deallocate::<{ LARGE_ALLOCATION_CHUNK_SIZE }>(ptr, layout, detached, |rings| {
+                &rings.large_deferred
+            });
+        },
+    }
+}
+
+/// Returns size of the chunk the block is allocated from.
+///
+/// # Safety
+///
+/// `ptr` must be allocated from a chunk with layout that fits `layout`.
+#[inline(always)]
+unsafe fn _chunk_size_of(ptr: NonNull<u8>, layout: Layout) -> usize {
+    let size = if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE {
+        TINY_ALLOCATION_CHUNK_SIZE
+    } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE {
+        SMALL_ALLOCATION_CHUNK_SIZE
+    } else {
+        LARGE_ALLOCATION_CHUNK_SIZE
+    };
+
+    #[cfg(not(feature = "aligned-chunks"))]
+    {
+        // Chunks of all sizes have the same header layout
+        // and block metadata points to the chunk regardless of its size.
+        let chunk = unsafe { TinyChunk::chunk_of(ptr.as_ptr(), layout).as_ref() };
+
+        // Blocks are only allocated from chunks of their size class or larger.
+        debug_assert!(
+            chunk.size >= size,
+            "Block is deallocated with layout of different size class"
+        );
+        chunk.size
+    }
+
+    #[cfg(feature = "aligned-chunks")]
+    {
+        let _ = ptr;
+        size
+    }
+}
+
+/// Number of oversized blocks allocated from `Backing`.
+#[cfg(test)]
+static OVERSIZED_ALLOCATIONS: core::sync::atomic::AtomicUsize =
+    core::sync::atomic::AtomicUsize::new(0);
+
+/// Allocates oversized block, reusing cached one if available.
+/// If `detached` is `true`, cache is skipped when contended.
+#[inline(never)]
+fn _allocate_oversized(layout: Layout, detached: bool) -> Result<NonNull<[u8]>, AllocError> {
+    match OversizedCache::bucket(layout) {
+        None => Backing.allocate(layout),
+        Some((bucket, block_layout)) => {
+            let cache = match detached {
+                false => Some(GLOBAL_RINGS.oversized.lock()),
+                true => GLOBAL_RINGS.oversized.try_lock(),
+            };
+            if let Some(block) = cache.and_then(|mut cache| cache.pop(bucket, block_layout)) {
+                return Ok(NonNull::slice_from_raw_parts(block, block_layout.size()));
+            }
+
+            #[cfg(test)]
+            OVERSIZED_ALLOCATIONS.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+
+            Backing.allocate(block_layout)
+        }
+    }
+}
+
+/// Deallocates oversized block, caching it for reuse if possible.
+/// If `detached` is `true`, cache is skipped when contended.
+///
+/// # Safety
+///
+/// `ptr` must be allocated by `_allocate_oversized` with layout that fits `layout`.
+#[inline(never)]
+unsafe fn _deallocate_oversized(ptr: NonNull<u8>, layout: Layout, detached: bool) {
+    match OversizedCache::bucket(layout) {
+        None => unsafe { Backing.deallocate(ptr, layout) },
+        Some((bucket, block_layout)) => {
+            let cache = match detached {
+                false => Some(GLOBAL_RINGS.oversized.lock()),
+                true => GLOBAL_RINGS.oversized.try_lock(),
+            };
+
+            // Safety: block is allocated with `block_layout` by `_allocate_oversized`.
+            // Size of any layout that fits the block rounds up to the same size.
+            let cached =
+                cache.is_some_and(|mut cache| unsafe { cache.push(bucket, block_layout, ptr) });
+            if !cached {
+                unsafe { Backing.deallocate(ptr, block_layout) }
+            }
+        }
+    }
+}
+
+/// Deallocates block deferring accounting if it is enabled for the calling thread.
+/// Returns `false` if block is not deallocated.
+#[cfg(feature = "deferred-frees")]
+#[inline(never)]
+unsafe fn _deallocate_deferred<const N: usize>(
+    ptr: NonNull<u8>,
+    layout: Layout,
+    deferred: fn(&LocalRings) -> &DeferredFrees<Chunk<N>>,
+) -> bool {
+    let done = LOCAL_RINGS.try_with(|rings| {
+        if !rings.defer_frees.get() {
+            return false;
+        }
+
+        // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
+        let (chunk, size) = unsafe { Chunk::<N>::deallocate_deferred(ptr.as_ptr(), layout) };
+        deferred(rings).add(chunk, size);
+        true
+    });
+
+    matches!(done, Ok(true))
+}
+
+#[cfg(feature = "metrics")]
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO_COUNTER: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+/// Histogram of requested allocation sizes.
+#[cfg(feature = "metrics")]
+static SIZE_HISTOGRAM: [core::sync::atomic::AtomicU64; OneRingAlloc::SIZE_HISTOGRAM_BUCKETS] =
+    [ZERO_COUNTER; OneRingAlloc::SIZE_HISTOGRAM_BUCKETS];
+
+/// Records allocation size in the histogram.
+#[cfg(feature = "metrics")]
+#[inline(always)]
+fn record_size(size: usize) {
+    // Number of bits required to represent `size - 1`
+    // is the power of two bucket that fits `size`.
+    let bits = (usize::BITS - size.saturating_sub(1).leading_zeros()) as usize;
+    let bucket = bits.min(OneRingAlloc::SIZE_HISTOGRAM_BUCKETS - 1);
+    SIZE_HISTOGRAM[bucket].fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+}
+
 impl OneRingAlloc {
+    /// Number of buckets in the histogram returned by [`OneRingAlloc::size_histogram`].
+    #[cfg(feature = "metrics")]
+    pub const SIZE_HISTOGRAM_BUCKETS: usize = 32;
+
     /// Attempts to allocate a block of memory with global ring-allocator.
     /// Returns a pointer to the beginning of the block if successful.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
     #[inline(always)]
     pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE {
-            LOCAL_RINGS
-                .try_with(|rings| _allocate(&rings.tiny_ring, &GLOBAL_RINGS.tiny_ring, layout))
-                .unwrap_or(Err(AllocError))
-        } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE {
-            LOCAL_RINGS
-                .try_with(|rings| _allocate(&rings.small_ring, &GLOBAL_RINGS.small_ring, layout))
-                .unwrap_or(Err(AllocError))
-        } else if layout_max(layout) <= LARGE_ALLOCATION_MAX_SIZE {
-            LOCAL_RINGS
-                .try_with(|rings| _allocate(&rings.large_ring, &GLOBAL_RINGS.large_ring, layout))
-                .unwrap_or(Err(AllocError))
-        } else {
-            Global.allocate(layout)
-        }
+        RingSet(None).allocate(layout)
     }
 
     /// Deallocates the memory referenced by `ptr`.
@@ -404,28 +1787,34 @@ impl OneRingAlloc {
     /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
     #[inline(always)]
     pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
-        if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE {
-            unsafe {
-                _deallocate::<{ TINY_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE {
-            unsafe {
-                _deallocate::<{ SMALL_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else if layout_max(layout) <= LARGE_ALLOCATION_MAX_SIZE {
-            unsafe {
-                _deallocate::<{ LARGE_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else {
-            unsafe { Global.deallocate(ptr, layout) }
-        }
+        // Safety: covered by function contract.
+        unsafe { _deallocate_block(ptr, layout, false) }
+    }
+
+    /// Enables or disables tiny and small size classes.
+    ///
+    /// Blocks of a disabled size class are allocated from the next larger enabled one,
+    /// trading memory for fewer partially used chunks.
+    /// Large size class is always enabled.
+    /// Applies to all threads, but not to dedicated namespaces.
+    ///
+    /// Blocks allocated before the change are deallocated as usual,
+    /// since deallocation finds the chunk of a block from block metadata
+    /// regardless of current configuration.
+    ///
+    /// Not available with `aligned-chunks` feature,
+    /// where chunk of a block is found from its size class.
+    #[cfg(not(feature = "aligned-chunks"))]
+    pub fn configure_classes(&self, tiny: bool, small: bool) {
+        RingSet(None).configure_classes(tiny, small);
     }
 
     /// Cleans global shared rings.
     ///
     /// When thread exists it frees all chunks that it allocated,
-    /// except those that are still in use by currently allocated blocks.
-    /// Chunks that are still in use are put to global shared rings.
+    /// except those that are still in use by currently allocated blocks
+    /// and one spare chunk per size class if global ring is empty.
+    /// Those chunks are put to global shared rings.
     ///
     /// Those get stolen by thread if thread needs new chunk.
     ///
@@ -434,8 +1823,38 @@ impl OneRingAlloc {
     ///
     /// This function may reduce memory overhead if threads exist and blocks
     /// allocated by them is freed later, while all other threads are warm.
+    ///
+    /// Oversized blocks cached for reuse are freed as well.
     pub fn clean_global(&self) {
-        GLOBAL_RINGS.clean_all();
+        RingSet(None).clean_global();
+    }
+
+    /// Cleans global shared rings and compacts chunks that remain there.
+    ///
+    /// Works like [`OneRingAlloc::clean_global`], but additionally sorts
+    /// partially-used chunks of each global ring by number of live bytes.
+    /// Threads that steal chunks then start from the most free ones,
+    /// so mostly-empty chunks are reused while chunks with many long-lived
+    /// blocks are left alone.
+    ///
+    /// Each ring is locked while it is sorted.
+    pub fn compact_global(&self) {
+        RingSet(None).compact_global();
+    }
+
+    /// Sets maximum number of chunks retained by global shared ring of the size class.
+    ///
+    /// Global rings grow as exiting threads and [`OneRingAlloc::flush_local_to_global`]
+    /// donate chunks to them.
+    /// When donation pushes the ring past the cap, oldest unused chunks
+    /// are freed instead of being retained.
+    /// Chunks with live blocks are never freed,
+    /// so the ring may hold more chunks while they are in use.
+    ///
+    /// Excess unused chunks are freed immediately.
+    /// Rings are unbounded by default, pass `usize::MAX` to remove the cap.
+    pub fn set_global_chunk_cap(&self, class: SizeClass, n: usize) {
+        RingSet(None).set_global_chunk_cap(class, n);
     }
 
     /// Cleans local rings.
@@ -449,7 +1868,380 @@ impl OneRingAlloc {
     /// Call this when thread's memory usage drops significantly
     /// and you want to reduce memory overhead.
     pub fn clean_local(&self) {
-        LOCAL_RINGS.with(|rings| rings.clean_all());
+        RingSet(None).clean_local();
+    }
+
+    /// Moves unused chunks from local rings to global shared rings.
+    ///
+    /// Unlike [`OneRingAlloc::clean_local`] chunks are not freed,
+    /// so other threads can steal them instead of allocating new chunks.
+    /// This is what thread does with its chunks on exit,
+    /// call it to donate chunks earlier, e.g. when producer thread
+    /// allocates blocks that are freed by consumer threads.
+    ///
+    /// Returns number of donated chunks.
+    pub fn flush_local_to_global(&self) -> usize {
+        RingSet(None).flush_local_to_global()
+    }
+
+    /// Enables or disables deferred accounting of deallocations on the calling thread.
+    ///
+    /// Each deallocation updates counter of freed bytes in the chunk with an atomic operation.
+    /// When many threads deallocate blocks from the same chunks,
+    /// e.g. consumers of blocks allocated by single producer thread,
+    /// those counters become a contention hotspot.
+    ///
+    /// When enabled, thread accumulates deallocated bytes in thread-local counter
+    /// and adds them to the chunk's counter at once when it deallocates
+    /// from another chunk of the same size class, when accumulated bytes reach a threshold
+    /// or when thread's own ring moves to next chunk.
+    ///
+    /// Until deferred bytes are flushed, the chunk is not considered unused
+    /// and can't be reused or freed.
+    /// [`OneRingAlloc::clean_local`], [`OneRingAlloc::clean_global`] and
+    /// [`OneRingAlloc::flush_local_to_global`] flush calling thread's deferred bytes first,
+    /// and so does thread exit.
+    /// Use [`OneRingAlloc::flush_deferred_frees`] to flush them explicitly,
+    /// e.g. before thread goes idle.
+    /// Disabling flushes deferred bytes as well.
+    ///
+    /// Disabled by default.
+    /// Requires `deferred-frees` feature, that adds a check to every deallocation.
+    #[cfg(feature = "deferred-frees")]
+    pub fn set_deferred_frees(&self, enabled: bool) {
+        LOCAL_RINGS.with(|rings| {
+            if enabled {
+                DEFERRED_FREES_USED.store(true, Ordering::Relaxed);
+            } else {
+                rings.flush_deferred();
+            }
+            rings.defer_frees.set(enabled);
+        });
+    }
+
+    /// Accounts deallocations deferred by the calling thread in their chunks.
+    ///
+    /// See [`OneRingAlloc::set_deferred_frees`].
+    #[cfg(feature = "deferred-frees")]
+    pub fn flush_deferred_frees(&self) {
+        LOCAL_RINGS.with(|rings| rings.flush_deferred());
+    }
+
+    /// Checks that calling thread's rings and global rings are consistent.
+    #[cfg(test)]
+    pub(crate) fn check_rings(&self) {
+        LOCAL_RINGS.with(|rings| {
+            check_links(rings.tiny_ring.head.get(), rings.tiny_ring.tail.get());
+            check_links(rings.small_ring.head.get(), rings.small_ring.tail.get());
+            check_links(rings.large_ring.head.get(), rings.large_ring.tail.get());
+        });
+
+        let tiny = GLOBAL_RINGS.tiny_ring.lock();
+        check_links(tiny.head, tiny.tail);
+        let small = GLOBAL_RINGS.small_ring.lock();
+        check_links(small.head, small.tail);
+        let large = GLOBAL_RINGS.large_ring.lock();
+        check_links(large.head, large.tail);
+    }
+
+    /// Holds locks of global rings until returned guard is dropped.
+    #[cfg(test)]
+    pub(crate) fn lock_global_rings(&self) -> impl Sized {
+        (
+            GLOBAL_RINGS.tiny_ring.ring.lock(),
+            GLOBAL_RINGS.small_ring.ring.lock(),
+            GLOBAL_RINGS.large_ring.ring.lock(),
+        )
+    }
+
+    /// Returns number of oversized blocks allocated from global allocator so far.
+    #[cfg(test)]
+    pub(crate) fn oversized_allocations(&self) -> usize {
+        OVERSIZED_ALLOCATIONS.load(core::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Returns number of bytes deallocated by the calling thread and not accounted yet.
+    #[cfg(all(test, feature = "deferred-frees"))]
+    pub(crate) fn deferred_bytes(&self) -> usize {
+        LOCAL_RINGS.with(|rings| {
+            rings.tiny_deferred.bytes.get()
+                + rings.small_deferred.bytes.get()
+                + rings.large_deferred.bytes.get()
+        })
+    }
+
+    /// Returns memory usage statistics of the calling thread's rings.
+    pub fn thread_stats(&self) -> RingStats {
+        RingSet(None).thread_stats()
+    }
+
+    /// Returns `true` if the calling thread's rings hold any chunks,
+    /// i.e. the thread allocated through [`OneRingAlloc`]
+    /// and its rings were not released since.
+    ///
+    /// Doesn't allocate anything, so it is safe to call for diagnostics
+    /// of threads that should not use the allocator, e.g. in thread pools.
+    /// Returns `false` if thread-local storage is already destroyed.
+    pub fn thread_initialized(&self) -> bool {
+        RingSet(None).thread_initialized()
+    }
+
+    /// Returns id of the calling thread that tags chunks it donates to global rings,
+    /// on exit or with [`OneRingAlloc::flush_local_to_global`].
+    ///
+    /// Ids are assigned to threads on first use and never reused.
+    /// Pass the id to [`OneRingAlloc::drain_thread_donations`]
+    /// to free memory parked by the thread, e.g. after it exits.
+    /// Returns `0` if thread-local storage is already destroyed.
+    #[cfg(feature = "donor-tracking")]
+    pub fn thread_donor_id(&self) -> usize {
+        donor_id()
+    }
+
+    /// Frees unused chunks of global shared rings donated by the thread with given id,
+    /// leaving chunks donated by other threads intact.
+    /// Returns number of freed chunks.
+    ///
+    /// Id is obtained with [`OneRingAlloc::thread_donor_id`] on the donating thread.
+    /// Chunk is attributed to the thread that donated it last,
+    /// chunks stolen and donated again by another thread move to that thread.
+    /// Chunks with live blocks are not freed.
+    ///
+    /// Each ring is locked while its chunks are inspected.
+    #[cfg(feature = "donor-tracking")]
+    pub fn drain_thread_donations(&self, thread_id: usize) -> usize {
+        RingSet(None).drain_thread_donations(thread_id)
+    }
+
+    /// Returns memory usage statistics of global shared rings,
+    /// including rings pinned with [`OneRingAlloc::pin_thread_rings`].
+    ///
+    /// Shows how much memory is parked in global rings,
+    /// waiting to be stolen by threads or freed with [`OneRingAlloc::clean_global`].
+    ///
+    /// Each ring is locked while its chunks are counted,
+    /// so threads that try to steal chunks at the same time are blocked.
+    pub fn global_stats(&self) -> RingStats {
+        RingSet(None).global_stats()
+    }
+
+    /// Captures statistics of the calling thread's rings and global shared rings.
+    ///
+    /// Use [`Snapshot::assert_no_growth`] on the same thread
+    /// to check that code path doesn't leak chunks.
+    pub fn snapshot(&self) -> Snapshot {
+        RingSet(None).snapshot()
+    }
+
+    /// Returns histogram of requested allocation sizes across all threads.
+    ///
+    /// Bucket `0` counts allocations of up to 1 byte
+    /// and bucket `i` counts allocations with size in `(2^(i-1), 2^i]` range.
+    /// The last bucket also counts all larger allocations.
+    ///
+    /// Useful to check how workload's allocations are distributed over size classes.
+    #[cfg(feature = "metrics")]
+    pub fn size_histogram(&self) -> [u64; Self::SIZE_HISTOGRAM_BUCKETS] {
+        let mut histogram = [0; Self::SIZE_HISTOGRAM_BUCKETS];
+        for (count, counter) in histogram.iter_mut().zip(&SIZE_HISTOGRAM) {
+            *count = counter.load(core::sync::atomic::Ordering::Relaxed);
+        }
+        histogram
+    }
+
+    /// Resets all metrics collected by [`OneRingAlloc`].
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&self) {
+        for counter in &SIZE_HISTOGRAM {
+            counter.store(0, core::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Returns ring-allocator with dedicated set of global and thread-local rings.
+    ///
+    /// Chunks of dedicated rings are never shared with [`OneRingAlloc`]
+    /// or dedicated rings of other namespaces,
+    /// so a burst of allocations in one subsystem doesn't disturb
+    /// chunk reuse of another.
+    ///
+    /// `NS` must be less than [`DEDICATED_NAMESPACES`], this is checked at compile time.
+    ///
+    /// ```
+    /// use allocator_api2::vec::Vec;
+    /// use ring_alloc::OneRingAlloc;
+    ///
+    /// const AUDIO: usize = 0;
+    ///
+    /// let mut samples = Vec::new_in(OneRingAlloc::dedicated::<AUDIO>());
+    /// samples.extend_from_slice(&[0.0f32; 64]);
+    /// ```
+    #[inline(always)]
+    pub const fn dedicated<const NS: usize>() -> DedicatedOneRing<NS> {
+        DedicatedOneRing
+    }
+
+    /// Returns ring-allocator that shares rings between threads
+    /// running on the same CPU instead of using thread-local rings.
+    ///
+    /// Experimental. See [`PerCpuOneRing`].
+    #[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+    #[inline(always)]
+    pub const fn per_cpu() -> PerCpuOneRing {
+        PerCpuOneRing
+    }
+
+    /// Returns ring-allocator without atomic operations and locks
+    /// for programs that allocate from one thread only.
+    ///
+    /// See [`SingleThreadOneRing`].
+    ///
+    /// # Safety
+    ///
+    /// All calls to this function in the program must be made from the same thread.
+    /// Checked in debug builds.
+    #[inline(always)]
+    pub unsafe fn single_thread() -> SingleThreadOneRing {
+        debug_check_single_thread();
+        SingleThreadOneRing {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Pins local rings to the slot.
+    ///
+    /// All chunks from thread-local rings, including unused ones,
+    /// are moved to the slot instead of being freed or put to global shared rings
+    /// on thread exit.
+    /// Another thread may then claim them with [`OneRingAlloc::claim_thread_rings`]
+    /// and start with warm chunks.
+    ///
+    /// Call this right before worker thread exits in thread pools that recreate workers.
+    /// If slot already holds pinned rings, chunks are appended to them.
+    /// Pinned chunks are kept until claimed.
+    pub fn pin_thread_rings(&self, slot: usize) {
+        LOCAL_RINGS.with(|rings| {
+            let mut pinned = GLOBAL_RINGS.pinned.lock();
+
+            let idx = match pinned.iter().position(|p| p.slot == slot) {
+                Some(idx) => idx,
+                None => {
+                    pinned.push(PinnedRings {
+                        slot,
+                        tiny_ring: GlobalRing::new(),
+                        small_ring: GlobalRing::new(),
+                        large_ring: GlobalRing::new(),
+                    });
+                    pinned.len() - 1
+                }
+            };
+
+            let pinned = &mut pinned[idx];
+            LocalRings::append(&rings.tiny_ring, &mut pinned.tiny_ring);
+            LocalRings::append(&rings.small_ring, &mut pinned.small_ring);
+            LocalRings::append(&rings.large_ring, &mut pinned.large_ring);
+        });
+    }
+
+    /// Claims rings pinned to the slot with [`OneRingAlloc::pin_thread_rings`].
+    ///
+    /// Claimed chunks are put in front of local rings.
+    /// Returns `false` if nothing is pinned to the slot.
+    pub fn claim_thread_rings(&self, slot: usize) -> bool {
+        let mut pinned = {
+            let mut pinned = GLOBAL_RINGS.pinned.lock();
+            match pinned.iter().position(|p| p.slot == slot) {
+                Some(idx) => pinned.swap_remove(idx),
+                None => return false,
+            }
+        };
+
+        LOCAL_RINGS.with(|rings| {
+            LocalRings::prepend(&rings.tiny_ring, &mut pinned.tiny_ring);
+            LocalRings::prepend(&rings.small_ring, &mut pinned.small_ring);
+            LocalRings::prepend(&rings.large_ring, &mut pinned.large_ring);
+        });
+
+        true
+    }
+
+    /// Registers the calling thread with the allocator.
+    ///
+    /// Initializes thread-local rings of [`OneRingAlloc`] and all dedicated namespaces,
+    /// registering their destructors where the platform supports it.
+    /// Threads spawned with `std::thread` don't need to call this,
+    /// rings are initialized on first use.
+    ///
+    /// Threads created outside Rust, for example by C code through FFI,
+    /// may call this when attached and must pair it with
+    /// [`OneRingAlloc::unregister_thread`] before they exit,
+    /// as thread-local destructors are not guaranteed to run for them.
+    pub fn register_thread(&self) {
+        let _ = LOCAL_RINGS.try_with(|_| {});
+
+        #[cfg(not(loom))]
+        let _ = DEDICATED_LOCAL_RINGS.try_with(|_| {});
+    }
+
+    /// Runs thread-exit routine for the calling thread's rings
+    /// of [`OneRingAlloc`] and all dedicated namespaces.
+    ///
+    /// Unused chunks are freed and the rest is moved to global shared rings,
+    /// the same way as when thread with Rust thread-local destructors exits.
+    /// Call this before thread created outside Rust exits.
+    ///
+    /// Thread may keep using the allocator afterwards,
+    /// its rings then start empty and must be unregistered again.
+    pub fn unregister_thread(&self) {
+        let _ = LOCAL_RINGS.try_with(|rings| rings.exit());
+
+        #[cfg(not(loom))]
+        let _ = DEDICATED_LOCAL_RINGS.try_with(|rings| rings.iter().for_each(LocalRings::exit));
+    }
+}
+
+/// [`OneRingAlloc`] may be installed with `#[global_allocator]`.
+///
+/// Chunks and oversized blocks are then allocated from [`System`](std::alloc::System).
+/// Allocations the allocator causes itself, e.g. when thread-local rings
+/// are initialized on first use, and allocations after thread-local rings
+/// are destroyed are served by global rings without touching thread-local ones,
+/// so they never recurse and are deallocated as any other block of the rings.
+#[cfg(not(loom))]
+unsafe impl core::alloc::GlobalAlloc for OneRingAlloc {
+    #[inline(always)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Chunks and oversized blocks requested by `Backing`.
+        if IN_BACKING.with(Cell::get) {
+            return unsafe { std::alloc::System.alloc(layout) };
+        }
+
+        let result = match Entered::enter() {
+            Some(_entered) => self.allocate(layout),
+            None => RingSet(None).allocate_detached(layout),
+        };
+
+        match result {
+            Ok(ptr) => ptr.as_ptr().cast(),
+            Err(AllocError) => core::ptr::null_mut(),
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Safety: inside `Backing` the only requests are its own, served by `System`,
+        // and blocks allocated by `Backing` are deallocated only by `Backing`.
+        // So `ptr` is allocated by `System` if and only if the flag is set.
+        if IN_BACKING.with(Cell::get) {
+            return unsafe { std::alloc::System.dealloc(ptr, layout) };
+        }
+
+        // Safety: `ptr` is non-null block allocated by `alloc` with `layout`
+        // outside of `Backing`, so it is allocated from the rings.
+        let ptr = unsafe { NonNull::new_unchecked(ptr) };
+        match Entered::enter() {
+            Some(_entered) => unsafe { self.deallocate(ptr, layout) },
+            None => unsafe { _deallocate_block(ptr, layout, true) },
+        }
     }
 }
 
@@ -466,3 +2258,402 @@ unsafe impl Allocator for OneRingAlloc {
         }
     }
 }
+
+/// Ring-allocator with dedicated set of global and thread-local rings.
+///
+/// Created with [`OneRingAlloc::dedicated`].
+/// Works like [`OneRingAlloc`], but rotates over its own chunks.
+/// Oversized blocks share the cache with [`OneRingAlloc`].
+///
+/// Blocks may be deallocated with either [`OneRingAlloc`] or
+/// any [`DedicatedOneRing`], as chunks are found from the block itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DedicatedOneRing<const NS: usize>;
+
+impl<const NS: usize> DedicatedOneRing<NS> {
+    const RINGS: RingSet = {
+        assert!(
+            NS < DEDICATED_NAMESPACES,
+            "Dedicated rings namespace is out of range"
+        );
+        RingSet(Some(NS))
+    };
+
+    /// Attempts to allocate a block of memory with dedicated ring-allocator.
+    /// Returns a pointer to the beginning of the block if successful.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Self::RINGS.allocate(layout)
+    }
+
+    /// Deallocates the memory referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory [*currently allocated*]
+    ///   via [`allocate`], [`DedicatedOneRing::allocate`] or [`OneRingAlloc::allocate`], and
+    /// * `layout` must [*fit*] that block of memory.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
+    #[inline(always)]
+    pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { OneRingAlloc.deallocate(ptr, layout) }
+    }
+
+    /// Enables or disables tiny and small size classes of this namespace.
+    ///
+    /// See [`OneRingAlloc::configure_classes`].
+    #[cfg(not(feature = "aligned-chunks"))]
+    pub fn configure_classes(&self, tiny: bool, small: bool) {
+        Self::RINGS.configure_classes(tiny, small);
+    }
+
+    /// Cleans global shared rings of this namespace.
+    ///
+    /// See [`OneRingAlloc::clean_global`].
+    pub fn clean_global(&self) {
+        Self::RINGS.clean_global();
+    }
+
+    /// Cleans and compacts global shared rings of this namespace.
+    ///
+    /// See [`OneRingAlloc::compact_global`].
+    pub fn compact_global(&self) {
+        Self::RINGS.compact_global();
+    }
+
+    /// Sets maximum number of chunks retained by global shared ring
+    /// of the size class of this namespace.
+    ///
+    /// See [`OneRingAlloc::set_global_chunk_cap`].
+    pub fn set_global_chunk_cap(&self, class: SizeClass, n: usize) {
+        Self::RINGS.set_global_chunk_cap(class, n);
+    }
+
+    /// Cleans local rings of this namespace.
+    ///
+    /// See [`OneRingAlloc::clean_local`].
+    pub fn clean_local(&self) {
+        Self::RINGS.clean_local();
+    }
+
+    /// Moves unused chunks from local rings to global shared rings of this namespace.
+    ///
+    /// See [`OneRingAlloc::flush_local_to_global`].
+    pub fn flush_local_to_global(&self) -> usize {
+        Self::RINGS.flush_local_to_global()
+    }
+
+    /// Returns memory usage statistics of the calling thread's rings of this namespace.
+    pub fn thread_stats(&self) -> RingStats {
+        Self::RINGS.thread_stats()
+    }
+
+    /// Returns `true` if the calling thread's rings of this namespace hold any chunks.
+    ///
+    /// See [`OneRingAlloc::thread_initialized`].
+    pub fn thread_initialized(&self) -> bool {
+        Self::RINGS.thread_initialized()
+    }
+
+    /// Frees unused chunks of global shared rings of this namespace
+    /// donated by the thread with given id.
+    ///
+    /// Threads have the same id in all namespaces.
+    /// See [`OneRingAlloc::drain_thread_donations`].
+    #[cfg(feature = "donor-tracking")]
+    pub fn drain_thread_donations(&self, thread_id: usize) -> usize {
+        Self::RINGS.drain_thread_donations(thread_id)
+    }
+
+    /// Returns memory usage statistics of global shared rings of this namespace.
+    pub fn global_stats(&self) -> RingStats {
+        Self::RINGS.global_stats()
+    }
+
+    /// Captures statistics of the calling thread's rings and global shared rings
+    /// of this namespace.
+    ///
+    /// See [`OneRingAlloc::snapshot`].
+    pub fn snapshot(&self) -> Snapshot {
+        Self::RINGS.snapshot()
+    }
+
+    /// Returns live bytes of chunks in global tiny, small and large rings
+    /// of this namespace, in ring order.
+    #[cfg(test)]
+    pub(crate) fn global_live(&self) -> [Vec<usize>; 3] {
+        Self::RINGS.global_live()
+    }
+}
+
+unsafe impl<const NS: usize> Allocator for DedicatedOneRing<NS> {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            self.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Ring-allocator with rings bound to CPUs instead of threads.
+///
+/// Created with [`OneRingAlloc::per_cpu`].
+/// Experimental, available with "per-cpu" feature on Linux.
+///
+/// Allocations are served from rings of the CPU the calling thread is running on,
+/// so many threads migrating between few CPUs keep a bounded number of rings
+/// instead of one set per thread.
+/// Rings are locked for the duration of allocation. Thread migrating to
+/// another CPU in the middle of allocation only makes it contend with
+/// threads of that CPU.
+///
+/// Chunks are shared with global rings of [`OneRingAlloc`],
+/// and blocks may be deallocated with either allocator.
+///
+/// ```
+/// use allocator_api2::boxed::Box;
+/// use ring_alloc::OneRingAlloc;
+///
+/// let value = Box::new_in(42u32, OneRingAlloc::per_cpu());
+/// assert_eq!(*value, 42);
+/// ```
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PerCpuOneRing;
+
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+impl PerCpuOneRing {
+    /// Attempts to allocate a block of memory with rings of the current CPU.
+    /// Returns a pointer to the beginning of the block if successful.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        #[cfg(feature = "metrics")]
+        record_size(layout.size());
+
+        if layout_max(layout) > LARGE_ALLOCATION_MAX_SIZE {
+            return _allocate_oversized(layout, false);
+        }
+
+        let rings = current_cpu_rings().rings.lock();
+        rings.allocate(&GLOBAL_RINGS, layout, false)
+    }
+
+    /// Deallocates the memory referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory [*currently allocated*]
+    ///   via [`allocate`], [`PerCpuOneRing::allocate`] or [`OneRingAlloc::allocate`], and
+    /// * `layout` must [*fit*] that block of memory.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
+    #[inline(always)]
+    pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { OneRingAlloc.deallocate(ptr, layout) }
+    }
+
+    /// Frees unused chunks of rings of all CPUs.
+    pub fn clean(&self) {
+        for cpu in PER_CPU_RINGS.iter() {
+            cpu.rings.lock().clean_all();
+        }
+    }
+
+    /// Returns memory usage statistics of rings of all CPUs.
+    pub fn stats(&self) -> RingStats {
+        let mut stats = RingStats::default();
+        for cpu in PER_CPU_RINGS.iter() {
+            let rings = cpu.rings.lock();
+            ring_stats(rings.tiny_ring.head.get(), &mut stats);
+            ring_stats(rings.small_ring.head.get(), &mut stats);
+            ring_stats(rings.large_ring.head.get(), &mut stats);
+        }
+        stats
+    }
+}
+
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+unsafe impl Allocator for PerCpuOneRing {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            self.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// Rings shared by all [`SingleThreadOneRing`] values.
+struct SingleThreadRings(LazyRingAlloc<Global>);
+
+// Safety: `OneRingAlloc::single_thread` contract confines all values
+// of `SingleThreadOneRing` to one thread.
+unsafe impl Sync for SingleThreadRings {}
+
+static SINGLE_THREAD_RINGS: SingleThreadRings = SingleThreadRings(RingAlloc::lazy_in(Global));
+
+std::thread_local! {
+    /// Address of this thread-local identifies the thread
+    /// that uses [`SingleThreadOneRing`].
+    static SINGLE_THREAD_MARK: u8 = const { 0 };
+}
+
+/// Mark of the first thread that used [`SingleThreadOneRing`], `0` until then.
+static SINGLE_THREAD_OWNER: core::sync::atomic::AtomicUsize =
+    core::sync::atomic::AtomicUsize::new(0);
+
+/// Panics in debug builds if the calling thread is not the first one
+/// that used [`SingleThreadOneRing`].
+/// Threads are not checked once their thread-local storage is destroyed.
+#[inline(always)]
+fn debug_check_single_thread() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let Ok(mark) = SINGLE_THREAD_MARK.try_with(|mark| mark as *const u8 as usize) else {
+        return;
+    };
+
+    if let Err(owner) =
+        SINGLE_THREAD_OWNER.compare_exchange(0, mark, Ordering::Relaxed, Ordering::Relaxed)
+    {
+        assert_eq!(
+            owner, mark,
+            "`SingleThreadOneRing` is used from multiple threads"
+        );
+    }
+}
+
+/// Ring-allocator for programs that allocate from one thread only.
+///
+/// Created with unsafe [`OneRingAlloc::single_thread`].
+/// Like [`OneRingAlloc`] it is ZST with a single set of rings
+/// shared by all its values, but rings and chunks are not synchronized,
+/// removing atomic operations, locks and thread-local storage access
+/// from allocation and deallocation paths.
+///
+/// It is neither [`Send`] nor [`Sync`], so blocks allocated with it
+/// can't leave the thread. Chunks are never shared with [`OneRingAlloc`].
+/// Debug builds panic if it is used from a thread other than the first one.
+///
+/// ```
+/// use allocator_api2::boxed::Box;
+/// use ring_alloc::OneRingAlloc;
+///
+/// // Safety: no other thread calls `single_thread`.
+/// let alloc = unsafe { OneRingAlloc::single_thread() };
+/// let value = Box::new_in(42u32, alloc);
+/// assert_eq!(*value, 42);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SingleThreadOneRing {
+    _not_send: PhantomData<*const u8>,
+}
+
+impl SingleThreadOneRing {
+    /// Frees unused chunks of the rings.
+    pub fn clean(&self) {
+        if let Some(ring) = SINGLE_THREAD_RINGS.0.get() {
+            ring.flush();
+        }
+    }
+
+    /// Returns memory usage report of the rings.
+    pub fn memory_report(&self) -> MemoryReport {
+        match SINGLE_THREAD_RINGS.0.get() {
+            Some(ring) => ring.memory_report(),
+            None => MemoryReport::default(),
+        }
+    }
+}
+
+unsafe impl Allocator for SingleThreadOneRing {
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        debug_check_single_thread();
+        SINGLE_THREAD_RINGS.0.allocate(layout)
+    }
+
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        debug_check_single_thread();
+        SINGLE_THREAD_RINGS.0.allocate_zeroed(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        debug_check_single_thread();
+        // Safety: covered by `Allocator::deallocate` contract.
+        unsafe { SINGLE_THREAD_RINGS.0.deallocate(ptr, layout) }
+    }
+
+    #[inline(always)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_check_single_thread();
+        // Safety: covered by `Allocator::grow` contract.
+        unsafe { SINGLE_THREAD_RINGS.0.grow(ptr, old_layout, new_layout) }
+    }
+}
+
+/// Statistics of thread's and global rings captured by [`OneRingAlloc::snapshot`].
+///
+/// Testing aid to check that code path leaks no chunks.
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    rings: RingSet,
+
+    /// Statistics of the rings of the thread that took the snapshot.
+    pub thread: RingStats,
+
+    /// Statistics of global shared rings.
+    pub global: RingStats,
+}
+
+impl Snapshot {
+    /// Returns total number of chunks in thread's and global rings.
+    pub fn chunks(&self) -> usize {
+        self.thread.chunks + self.global.chunks
+    }
+
+    /// Panics if total number of chunks in thread's and global rings
+    /// grew since the snapshot was taken.
+    ///
+    /// Chunks moving between thread's and global rings are not counted as growth.
+    /// Must be called on the thread that took the snapshot.
+    /// Global rings are shared with other threads, so their allocations
+    /// may be seen as growth too.
+    #[track_caller]
+    pub fn assert_no_growth(&self) {
+        let now = self.rings.snapshot();
+        assert!(
+            now.chunks() <= self.chunks(),
+            "Number of chunks grew from {} to {}: thread {:?} -> {:?}, global {:?} -> {:?}",
+            self.chunks(),
+            now.chunks(),
+            self.thread,
+            now.thread,
+            self.global,
+            now.global
+        );
+    }
+}
@@ -8,16 +8,67 @@ extern crate alloc;
 
 mod chunk;
 mod local;
+mod vec;
+
+#[cfg(not(no_global_oom_handling))]
+mod dropping;
+
+#[cfg(feature = "alloc")]
+#[cfg(not(no_global_oom_handling))]
+pub mod compat;
 
 #[cfg(feature = "std")]
 mod global;
 
+#[cfg(feature = "std")]
+mod sync;
+
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+
 use core::{alloc::Layout, cell::Cell, sync::atomic::Ordering};
 
-pub use self::local::RingAlloc;
+pub use self::{
+    local::{
+        Classes, FrozenRing, InlineRingAlloc, LazyRingAlloc, MemoryReport, RingAlloc,
+        RingAllocError, RingAllocHandle, SizeClass, SizeClasses, WeakRingAlloc,
+    },
+    vec::ArenaVec,
+};
+
+#[cfg(not(no_global_oom_handling))]
+pub use self::dropping::DroppingRing;
+
+#[cfg(feature = "debug-introspect")]
+pub use self::local::ChunkLiveInfo;
+
+#[cfg(feature = "const-generic-config")]
+pub use self::local::Thresholds;
+
+#[cfg(feature = "std")]
+pub use self::global::{
+    DedicatedOneRing, OneRingAlloc, SingleThreadOneRing, Snapshot, DEDICATED_NAMESPACES,
+};
+
+#[cfg(all(feature = "per-cpu", target_os = "linux", not(loom)))]
+pub use self::global::PerCpuOneRing;
+
+#[cfg(all(feature = "mmap", unix))]
+pub use self::mmap::MmapFileAlloc;
 
+/// Memory usage statistics of a set of rings.
 #[cfg(feature = "std")]
-pub use self::global::OneRingAlloc;
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RingStats {
+    /// Number of chunks in the rings.
+    pub chunks: usize,
+
+    /// Total size of the chunks in bytes.
+    pub reserved: usize,
+
+    /// Number of bytes occupied by blocks that are not deallocated yet.
+    pub live: usize,
+}
 
 #[allow(clippy::transmutes_expressible_as_ptr_casts)]
 fn addr<T: ?Sized>(ptr: *const T) -> usize {
@@ -37,6 +88,9 @@ unsafe fn with_addr_mut<T>(ptr: *mut T, dest_addr: usize) -> *mut T {
 }
 
 trait ImUsize {
+    /// Pointer cell with the same sharing guarantees.
+    type Ptr: ImPtr;
+
     fn new(value: usize) -> Self;
     fn load(&self, ordering: Ordering) -> usize;
     fn store(&self, value: usize, ordering: Ordering);
@@ -44,6 +98,8 @@ trait ImUsize {
 }
 
 impl ImUsize for Cell<usize> {
+    type Ptr = Cell<*mut u8>;
+
     #[inline(always)]
     fn new(value: usize) -> Self {
         Cell::new(value)
@@ -68,7 +124,9 @@ impl ImUsize for Cell<usize> {
 }
 
 #[cfg(feature = "std")]
-impl ImUsize for core::sync::atomic::AtomicUsize {
+impl ImUsize for sync::AtomicUsize {
+    type Ptr = sync::AtomicPtr<u8>;
+
     #[inline(always)]
     fn new(value: usize) -> Self {
         Self::new(value)
@@ -90,9 +148,55 @@ impl ImUsize for core::sync::atomic::AtomicUsize {
     }
 }
 
+trait ImPtr {
+    fn new(value: *mut u8) -> Self;
+    fn load(&self, ordering: Ordering) -> *mut u8;
+    fn store(&self, value: *mut u8, ordering: Ordering);
+}
+
+impl ImPtr for Cell<*mut u8> {
+    #[inline(always)]
+    fn new(value: *mut u8) -> Self {
+        Cell::new(value)
+    }
+
+    #[inline(always)]
+    fn load(&self, _ordering: Ordering) -> *mut u8 {
+        self.get()
+    }
+
+    #[inline(always)]
+    fn store(&self, value: *mut u8, _ordering: Ordering) {
+        self.set(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ImPtr for sync::AtomicPtr<u8> {
+    #[inline(always)]
+    fn new(value: *mut u8) -> Self {
+        Self::new(value)
+    }
+
+    #[inline(always)]
+    fn load(&self, ordering: Ordering) -> *mut u8 {
+        self.load(ordering)
+    }
+
+    #[inline(always)]
+    fn store(&self, value: *mut u8, ordering: Ordering) {
+        self.store(value, ordering)
+    }
+}
+
 #[inline(always)]
-fn layout_max(layout: Layout) -> usize {
-    layout.align().max(layout.size())
+const fn layout_max(layout: Layout) -> usize {
+    let (align, size) = (layout.align(), layout.size());
+    if align > size {
+        align
+    } else {
+        size
+    }
 }
 
 #[inline(always)]
@@ -1,13 +1,21 @@
 use core::{
-    cell::Cell,
+    any::Any,
+    cell::{Cell, OnceCell},
+    fmt,
     hash::{Hash, Hasher},
+    marker::PhantomData,
     mem::ManuallyDrop,
     ptr::NonNull,
 };
 
 use allocator_api2::alloc::{AllocError, Allocator, Layout};
 
-use crate::layout_max;
+#[cfg(feature = "alloc")]
+use allocator_api2::{boxed::Box, vec::Vec};
+
+#[cfg(all(test, feature = "alloc"))]
+use crate::chunk::check_links;
+use crate::{chunk::FreeRun, layout_max};
 
 type Chunk<const N: usize> = crate::chunk::Chunk<Cell<usize>, { N }>;
 
@@ -29,13 +37,313 @@ const LARGE_ALLOCATION_MAX_SIZE: usize = 65536;
 /// Size of the chunk for allocations larger than `SMALL_ALLOCATION_MAX_SIZE`.
 const LARGE_ALLOCATION_CHUNK_SIZE: usize = 2097152;
 
+/// Chunks grown by growth factor are at most this many times larger
+/// than the chunk size of their class.
+#[cfg(not(feature = "aligned-chunks"))]
+const MAX_CHUNK_GROWTH: usize = 16;
+
+/// Maximum alignment of chunk memory that can be requested.
+/// Leaves most of the smallest chunk for blocks.
+const MAX_MIN_ALIGN: usize = 4096;
+
+#[inline(always)]
+fn assert_min_align(align: usize) {
+    assert!(
+        align.is_power_of_two() && align <= MAX_MIN_ALIGN,
+        "Minimum chunk alignment must be power of two not larger than {}",
+        MAX_MIN_ALIGN
+    );
+}
+
+#[inline(always)]
+fn assert_size_granularity(granularity: usize) {
+    assert!(
+        granularity.is_power_of_two(),
+        "Size granularity must be power of two"
+    );
+}
+
+/// Returns layout of header `H` followed by `count` values of type `T`
+/// and offset of the values from the beginning of the block.
+#[inline(always)]
+fn trailing_layout<H, T>(count: usize) -> Option<(Layout, usize)> {
+    let array = Layout::array::<T>(count).ok()?;
+    Layout::new::<H>().extend(array).ok()
+}
+
+/// Ring that serves blocks of a layout.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Class {
+    Tiny,
+    Small,
+    Large,
+
+    /// Served by the underlying allocator.
+    Oversized,
+}
+
+impl Class {
+    /// Classifies layout by maximum of its size and alignment.
+    ///
+    /// Allocation path uses this classification.
+    /// Deallocation path uses [`Class::of_block`],
+    /// so block is always returned to the ring it was allocated from.
+    #[inline(always)]
+    const fn of<C: SizeClasses>(layout: Layout) -> Self {
+        let max = layout_max(layout);
+
+        // Count thresholds below `max` instead of branching on each one.
+        let idx = (max > C::TINY_MAX_SIZE) as usize
+            + (max > C::SMALL_MAX_SIZE) as usize
+            + (max > C::LARGE_MAX_SIZE) as usize;
+
+        match idx {
+            0 => Class::Tiny,
+            1 => Class::Small,
+            2 => Class::Large,
+            _ => Class::Oversized,
+        }
+    }
+
+    /// Returns smallest class not smaller than this one that is enabled in `C`.
+    #[inline(always)]
+    const fn route<C: SizeClasses>(self) -> Self {
+        match self {
+            Class::Tiny if C::TinyRing::ENABLED => Class::Tiny,
+            Class::Tiny | Class::Small if C::SmallRing::ENABLED => Class::Small,
+            Class::Tiny | Class::Small | Class::Large if C::LargeRing::ENABLED => Class::Large,
+            _ => Class::Oversized,
+        }
+    }
+
+    /// Classifies block by the chunk it was allocated from.
+    ///
+    /// Differs from [`Class::of`] only for blocks that [`RingAlloc`]
+    /// grew in place beyond the size class of their chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block currently allocated by ring-allocator
+    /// and `layout` must fit that block.
+    #[inline(always)]
+    unsafe fn of_block<C: SizeClasses>(ptr: NonNull<u8>, layout: Layout) -> Self {
+        let class = Class::of::<C>(layout).route::<C>();
+
+        #[cfg(not(feature = "aligned-chunks"))]
+        {
+            if class != Class::Oversized {
+                // Chunks of all sizes have the same header layout
+                // and block metadata points to the chunk regardless of its size.
+                let chunk = unsafe { TinyChunk::chunk_of(ptr.as_ptr(), layout).as_ref() };
+                let chunk_class = match chunk.size {
+                    TINY_ALLOCATION_CHUNK_SIZE => Class::Tiny,
+                    SMALL_ALLOCATION_CHUNK_SIZE => Class::Small,
+                    _ => Class::Large,
+                };
+
+                // Blocks only grow beyond size class of their chunk.
+                debug_assert!(
+                    chunk_class <= class,
+                    "Block is used with layout of different size class"
+                );
+                return chunk_class;
+            }
+        }
+
+        #[cfg(feature = "aligned-chunks")]
+        let _ = ptr;
+
+        class
+    }
+}
+
+/// Size class of blocks of type `T`, computed at compile time.
+struct TypeClass<T, C>(PhantomData<(T, C)>);
+
+impl<T, C: SizeClasses> TypeClass<T, C> {
+    const CLASS: Class = Class::of::<C>(Layout::new::<T>()).route::<C>();
+}
+
+/// Size class of a chunk and its blocks,
+/// determined by maximum of size and alignment of the blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SizeClass {
+    /// Blocks up to 16 bytes.
+    Tiny,
+
+    /// Blocks up to 256 bytes.
+    Small,
+
+    /// Blocks up to 65536 bytes.
+    Large,
+}
+
+/// Chunk with blocks that are not deallocated yet.
+///
+/// Returned by [`RingAlloc::live_summary`].
+#[cfg(feature = "debug-introspect")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkLiveInfo {
+    /// Address of the chunk.
+    /// Live blocks are located within `chunk_addr..chunk_addr + chunk_size`.
+    pub chunk_addr: usize,
+
+    /// Size of the chunk in bytes.
+    pub chunk_size: usize,
+
+    /// Size class of the chunk.
+    pub size_class: SizeClass,
+
+    /// Number of bytes occupied by blocks that are not deallocated yet,
+    /// including per-block metadata.
+    pub live_bytes: usize,
+
+    /// Source locations where blocks that are not deallocated yet were allocated,
+    /// most recent first.
+    ///
+    /// Blocks allocated with [`RingAlloc::allocate`] or [`RingAlloc::allocate_zeroed`],
+    /// directly or through [`Allocator`] trait, report location of the call.
+    /// Blocks allocated by other methods report location inside this crate.
+    ///
+    /// Locations are recorded in block metadata only in debug builds.
+    /// Always empty with `aligned-chunks` feature that removes block metadata.
+    #[cfg(debug_assertions)]
+    pub locations: alloc::vec::Vec<&'static core::panic::Location<'static>>,
+}
+
+/// Breakdown of memory used by a ring-allocator.
+///
+/// Returned by [`RingAlloc::memory_report`].
+/// Oversized blocks allocated directly from the underlying allocator are not included.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Total size of the chunks in bytes, chunk headers included.
+    pub reserved: usize,
+
+    /// Number of bytes occupied by blocks that are not deallocated yet,
+    /// including per-block metadata.
+    pub live: usize,
+
+    /// Number of blocks that are not deallocated yet.
+    pub live_blocks: usize,
+
+    /// Number of chunks for blocks up to 16 bytes.
+    pub tiny_chunks: usize,
+
+    /// Number of chunks for blocks up to 256 bytes.
+    pub small_chunks: usize,
+
+    /// Number of chunks for blocks up to 65536 bytes.
+    pub large_chunks: usize,
+
+    /// Number of bytes spent on chunk headers, metadata of live blocks
+    /// and alignment padding since chunks were last reset.
+    pub overhead: usize,
+}
+
+impl MemoryReport {
+    /// Returns total number of chunks.
+    pub fn chunks(&self) -> usize {
+        self.tiny_chunks + self.small_chunks + self.large_chunks
+    }
+
+    /// Returns fraction of reserved memory occupied by live blocks,
+    /// from `0.0` to `1.0`.
+    pub fn utilization(&self) -> f64 {
+        if self.reserved == 0 {
+            return 0.0;
+        }
+        self.live as f64 / self.reserved as f64
+    }
+
+    /// Returns fraction of reserved memory not occupied by live blocks,
+    /// from `0.0` to `1.0`.
+    ///
+    /// High fragmentation means chunks are held by few straggling blocks,
+    /// signalling that the allocator should be reset
+    /// or that lifetimes of its blocks vary too much.
+    /// Zero when no memory is reserved.
+    pub fn fragmentation(&self) -> f64 {
+        if self.reserved == 0 {
+            return 0.0;
+        }
+        (self.reserved - self.live) as f64 / self.reserved as f64
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "reserved {} bytes in {} chunks (tiny {}, small {}, large {}), \
+             live {} bytes in {} blocks, overhead {} bytes, utilization {:.1}%",
+            self.reserved,
+            self.chunks(),
+            self.tiny_chunks,
+            self.small_chunks,
+            self.large_chunks,
+            self.live,
+            self.live_blocks,
+            self.overhead,
+            self.utilization() * 100.0
+        )
+    }
+}
+
+/// Reason why [`RingAlloc`] failed to allocate a block.
+///
+/// Returned by [`RingAlloc::try_allocate_detailed`]
+/// and [`RingAlloc::try_allocate_slice_detailed`].
+/// Converts into [`AllocError`] for use with [`Allocator`] API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RingAllocError {
+    /// Underlying allocator failed to allocate a chunk or an oversized block.
+    BackingOom,
+
+    /// Layout is too large for any chunk and strict mode rejects oversized blocks.
+    /// See [`RingAlloc::set_strict`].
+    LayoutTooLarge,
+
+    /// Size of requested array overflows `isize::MAX`.
+    Overflow,
+}
+
+impl fmt::Display for RingAllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RingAllocError::BackingOom => f.write_str("underlying allocator is out of memory"),
+            RingAllocError::LayoutTooLarge => {
+                f.write_str("layout is too large for chunks in strict mode")
+            }
+            RingAllocError::Overflow => f.write_str("requested size overflows `isize::MAX`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RingAllocError {}
+
+impl From<RingAllocError> for AllocError {
+    #[inline(always)]
+    fn from(_: RingAllocError) -> Self {
+        AllocError
+    }
+}
+
 #[cfg(not(feature = "alloc"))]
 macro_rules! ring_alloc {
     ($(#[$meta:meta])* pub struct $ring_alloc:ident;) => {
         $(#[$meta])*
         #[repr(transparent)]
-        pub struct $ring_alloc<A: Allocator> {
-            inner: NonNull<Rings<A>>,
+        pub struct $ring_alloc<A: Allocator, C: SizeClasses = Classes> {
+            inner: NonNull<Rings<A, C>>,
+        }
+    };
+    ($(#[$meta:meta])* pub struct $ring_alloc:ident inline;) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        pub struct $ring_alloc<A: Allocator, C: SizeClasses = Classes> {
+            inner: Rings<A, C>,
         }
     };
 }
@@ -46,8 +354,16 @@ macro_rules! ring_alloc {
         $(#[$meta])*
         #[repr(transparent)]
         #[must_use]
-        pub struct $ring_alloc<A: Allocator = allocator_api2::alloc::Global> {
-            inner: NonNull<Rings<A>>,
+        pub struct $ring_alloc<A: Allocator = allocator_api2::alloc::Global, C: SizeClasses = Classes> {
+            inner: NonNull<Rings<A, C>>,
+        }
+    };
+    ($(#[$meta:meta])* pub struct $ring_alloc:ident inline;) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        #[must_use]
+        pub struct $ring_alloc<A: Allocator = allocator_api2::alloc::Global, C: SizeClasses = Classes> {
+            inner: Rings<A, C>,
         }
     };
 }
@@ -61,12 +377,59 @@ ring_alloc! {
     /// moving it to back if chunk is full.
     /// If next chunk is still occupied by previous allocation, allocator will
     /// allocate new chunk.
+    ///
+    /// Underlying allocator is stored together with the rings in a block
+    /// allocated from the underlying allocator itself,
+    /// so [`RingAlloc`] is a single pointer regardless of the size of `A`.
+    /// Allocator types with large state are supported,
+    /// but are moved through the stack on construction.
+    /// Consider passing a reference to such allocator instead.
+    ///
+    /// Dropping last handle frees all chunks, so blocks must be deallocated
+    /// while at least one handle is alive.
+    /// Collections and boxes that store a handle, like `Vec<T, RingAlloc>`,
+    /// deallocate their memory before dropping the handle,
+    /// so they may safely hold the last one.
+    ///
+    /// Blocks larger than the largest size class are allocated
+    /// from the underlying allocator directly and are not tracked by the rings.
+    /// Dropping last handle frees all chunks, but not such oversized blocks.
+    /// They must be deallocated explicitly, through a handle of this allocator
+    /// while one is alive or through the underlying allocator afterwards.
+    ///
+    /// Size classes served by the rings are selected with `C` parameter,
+    /// see [`Classes`].
     pub struct RingAlloc;
 }
 
-impl<A> Clone for RingAlloc<A>
+ring_alloc! {
+    /// Non-owning handle to a [`RingAlloc`].
+    ///
+    /// Created with [`RingAlloc::downgrade`].
+    /// Weak handle does not keep chunks alive and can't be used to allocate memory.
+    /// Call [`WeakRingAlloc::upgrade`] to get [`RingAlloc`] back
+    /// while at least one [`RingAlloc`] handle is alive.
+    ///
+    /// Chunks are freed when last [`RingAlloc`] handle is dropped.
+    /// Block that holds the rings and the underlying allocator
+    /// is freed when last weak handle is dropped as well.
+    pub struct WeakRingAlloc;
+}
+
+ring_alloc! {
+    /// Read-only [`RingAlloc`] that may be shared between threads.
+    ///
+    /// Created with [`RingAlloc::freeze`].
+    /// Keeps chunks alive, so blocks allocated before freezing
+    /// can be read from any thread, e.g. immutable data structure built in the arena.
+    /// No blocks can be allocated or deallocated while frozen.
+    pub struct FrozenRing;
+}
+
+impl<A, C> Clone for RingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
     fn clone(&self) -> Self {
@@ -81,9 +444,13 @@ where
     }
 }
 
-impl<A> PartialEq for RingAlloc<A>
+/// Two [`RingAlloc`]s are equal iff they share the same rings.
+/// Equal allocators are interchangeable, block allocated with one
+/// can be deallocated with another.
+impl<A, C> PartialEq for RingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
@@ -91,9 +458,32 @@ where
     }
 }
 
-impl<A> Hash for RingAlloc<A>
+impl<A, C> PartialEq<&RingAlloc<A, C>> for RingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn eq(&self, other: &&Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<A, C> PartialEq<RingAlloc<A, C>> for &RingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn eq(&self, other: &RingAlloc<A, C>) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<A, C> Hash for RingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -101,9 +491,10 @@ where
     }
 }
 
-impl<A> Drop for RingAlloc<A>
+impl<A, C> Drop for RingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
     fn drop(&mut self) {
@@ -111,387 +502,3598 @@ where
     }
 }
 
-type TinyChunk = Chunk<{ TINY_ALLOCATION_CHUNK_SIZE }>;
-type SmallChunk = Chunk<{ SMALL_ALLOCATION_CHUNK_SIZE }>;
-type LargeChunk = Chunk<{ LARGE_ALLOCATION_CHUNK_SIZE }>;
-
-struct Ring<T> {
-    // Head of the ring.
-    // This is the current chunk.
-    // When chunk is full, this chunk is moved to the end.
-    head: Cell<Option<NonNull<T>>>,
+impl<A, C> Clone for WeakRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Rings::inc_weak(self.inner);
+        WeakRingAlloc { inner: self.inner }
+    }
+}
 
-    // Tail of the ring.
-    tail: Cell<Option<NonNull<T>>>,
+impl<A, C> Drop for WeakRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        Rings::dec_weak(self.inner);
+    }
 }
 
-impl<T> Ring<T> {
-    const fn new() -> Self {
-        Ring {
-            head: Cell::new(None),
-            tail: Cell::new(None),
+impl<A, C> WeakRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    /// Returns [`RingAlloc`] handle if the ring-allocator is still alive.
+    ///
+    /// Returns `None` if all [`RingAlloc`] handles were dropped.
+    #[inline(always)]
+    pub fn upgrade(&self) -> Option<RingAlloc<A, C>> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        // while weak handle is alive.
+        let inner = unsafe { self.inner.as_ref() };
+        if inner.ref_cnt.get() == 0 {
+            return None;
         }
+        Rings::inc_ref(self.inner);
+        Some(RingAlloc { inner: self.inner })
     }
 }
 
-struct Rings<A: Allocator> {
-    tiny_ring: Ring<TinyChunk>,
-    small_ring: Ring<SmallChunk>,
-    large_ring: Ring<LargeChunk>,
-    allocator: ManuallyDrop<A>,
-    ref_cnt: Cell<usize>,
+/// Borrowed handle to a [`RingAlloc`].
+///
+/// Created with [`RingAlloc::handle`].
+/// Unlike cloned [`RingAlloc`], handle is copied without touching
+/// the reference counter, and unlike `&RingAlloc` it points to the rings directly.
+/// Collections created within a scope can use handle instead of cloned allocator
+/// to avoid reference counting traffic.
+///
+/// Handle can't outlive the [`RingAlloc`] it is borrowed from.
+///
+/// ```compile_fail
+/// # use ring_alloc::RingAlloc;
+/// let handle;
+/// {
+///     let alloc = RingAlloc::new();
+///     handle = alloc.handle();
+/// }
+/// drop(handle);
+/// ```
+pub struct RingAllocHandle<'a, A: Allocator, C: SizeClasses = Classes> {
+    inner: NonNull<Rings<A, C>>,
+    marker: PhantomData<&'a RingAlloc<A, C>>,
 }
 
-impl<A> Rings<A>
+impl<A, C> Clone for RingAllocHandle<'_, A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
-    fn try_new_in(allocator: A) -> Result<NonNull<Self>, AllocError> {
-        let ptr = allocator.allocate(Layout::new::<Self>())?;
-        let inner = Rings {
-            tiny_ring: Ring::new(),
-            small_ring: Ring::new(),
-            large_ring: Ring::new(),
-            allocator: ManuallyDrop::new(allocator),
-            ref_cnt: Cell::new(1),
-        };
-
-        let ptr = ptr.cast::<Self>();
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
-        // Safety: `ptr` is valid pointer to `Self` allocated by `allocator`.
-        unsafe {
-            core::ptr::write(ptr.as_ptr(), inner);
-        }
+impl<A, C> Copy for RingAllocHandle<'_, A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+}
 
-        Ok(ptr)
+impl<A, C> RingAllocHandle<'_, A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn inner(&self) -> &Rings<A, C> {
+        // Safety: rings are alive while borrowed `RingAlloc` is.
+        unsafe { self.inner.as_ref() }
     }
+}
 
+unsafe impl<A, C> Allocator for RingAllocHandle<'_, A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
     #[inline(always)]
-    #[cfg(not(no_global_oom_handling))]
-    fn new_in(allocator: A) -> NonNull<Self> {
-        match Self::try_new_in(allocator) {
-            Ok(ptr) => ptr,
-            #[cfg(feature = "alloc")]
-            Err(AllocError) => {
-                alloc::alloc::handle_alloc_error(Layout::new::<Self>());
-            }
-            #[cfg(not(feature = "alloc"))]
-            Err(AllocError) => {
-                core::panic!("Failed to allocate Rings");
-            }
-        }
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.inner().allocate(layout)
     }
 
-    fn inc_ref(ptr: NonNull<Self>) {
-        // Safety: `ptr` is valid pointer to `Self`.
-        let me = unsafe { ptr.as_ref() };
-        me.ref_cnt.set(me.ref_cnt.get() + 1);
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.inner().allocate_zeroed(layout)
     }
 
-    fn dec_ref(ptr: NonNull<Self>) {
-        // Safety: `ptr` is valid pointer to `Self`.
-        let me = unsafe { ptr.as_ref() };
-
-        debug_assert_ne!(me.ref_cnt.get(), 0);
-        let new_ref_cnt = me.ref_cnt.get() - 1;
-        me.ref_cnt.set(new_ref_cnt);
-
-        if new_ref_cnt == 0 {
-            Self::free(ptr);
-        }
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: covered by `Allocator::deallocate` contract.
+        unsafe { self.inner().deallocate(ptr, layout) }
     }
 
-    #[cold]
-    fn free(ptr: NonNull<Self>) {
-        // Safety: `ptr` is valid pointer to `Self`.
-        let me = unsafe { ptr.as_ref() };
+    #[inline(always)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: covered by `Allocator::grow` contract.
+        unsafe { self.inner().grow(ptr, old_layout, new_layout) }
+    }
+}
 
-        me.free_all();
+// Safety: frozen ring-allocator is the only handle to the rings
+// and never touches them until unfrozen or dropped.
+unsafe impl<A, C> Send for FrozenRing<A, C>
+where
+    A: Allocator + Send,
+    C: SizeClasses,
+{
+}
 
-        // Safety: taking allocator out `ManuallyDrop`.
-        // The value is dropped immediately after.
-        let allocator = unsafe { core::ptr::read(&*me.allocator) };
+// Safety: shared frozen ring-allocator provides no access to the rings.
+unsafe impl<A, C> Sync for FrozenRing<A, C>
+where
+    A: Allocator + Sync,
+    C: SizeClasses,
+{
+}
 
-        // Safety: `ptr` was allocated by `me.allocator`.
-        unsafe {
-            allocator.deallocate(ptr.cast(), Layout::new::<Self>());
-        }
+impl<A, C> FrozenRing<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    /// Returns ring-allocator back, so blocks can be allocated and deallocated again.
+    ///
+    /// Frozen ring-allocator shared with `Arc` is unfrozen
+    /// once exclusively owned, e.g. after `Arc::try_unwrap`.
+    #[inline(always)]
+    pub fn unfreeze(self) -> RingAlloc<A, C> {
+        let me = ManuallyDrop::new(self);
+        RingAlloc { inner: me.inner }
     }
+}
 
+impl<A, C> Drop for FrozenRing<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
     #[inline(always)]
-    fn clean_all(&self) {
-        Self::clean(&self.tiny_ring, &self.allocator);
-        Self::clean(&self.small_ring, &self.allocator);
-        Self::clean(&self.large_ring, &self.allocator);
+    fn drop(&mut self) {
+        Rings::dec_ref(self.inner);
     }
+}
+
+type TinyChunk = Chunk<{ TINY_ALLOCATION_CHUNK_SIZE }>;
+type SmallChunk = Chunk<{ SMALL_ALLOCATION_CHUNK_SIZE }>;
+type LargeChunk = Chunk<{ LARGE_ALLOCATION_CHUNK_SIZE }>;
+
+struct Ring<T> {
+    // Head of the ring.
+    // This is the current chunk.
+    // When chunk is full, this chunk is moved to the end.
+    head: Cell<Option<NonNull<T>>>,
+
+    // Tail of the ring.
+    tail: Cell<Option<NonNull<T>>>,
+
+    // Number of chunks allocated for the ring so far.
+    // Determines capacity of the next chunk when chunk sizes grow.
+    allocated: Cell<usize>,
+
+    // Number of times head chunk was moved to tail.
+    #[cfg(feature = "metrics")]
+    rotations: Cell<u64>,
+
+    // Allocator for new chunks when the underlying allocator fails.
+    overflow: Cell<Option<&'static dyn Allocator>>,
+}
+
+impl<T> Ring<T> {
+    const fn new() -> Self {
+        Ring {
+            head: Cell::new(None),
+            tail: Cell::new(None),
+            allocated: Cell::new(0),
+            #[cfg(feature = "metrics")]
+            rotations: Cell::new(0),
+            overflow: Cell::new(None),
+        }
+    }
+}
+
+/// Ring of size class enabled in [`SizeClasses`].
+pub struct EnabledRing<const N: usize>(Ring<Chunk<N>>);
+
+/// Placeholder of size class disabled in [`SizeClasses`].
+pub struct NoRing;
+
+/// Storage of a size class ring, either [`EnabledRing`] or [`NoRing`].
+pub trait ClassRing: 'static {
+    /// Whether the class is served by the ring.
+    const ENABLED: bool;
+
+    fn new() -> Self;
+}
+
+impl<const N: usize> ClassRing for EnabledRing<N> {
+    const ENABLED: bool = true;
+
+    #[inline(always)]
+    fn new() -> Self {
+        EnabledRing(Ring::new())
+    }
+}
+
+impl ClassRing for NoRing {
+    const ENABLED: bool = false;
+
+    #[inline(always)]
+    fn new() -> Self {
+        NoRing
+    }
+}
+
+/// Returns ring of chunks of size `N` if the class is enabled.
+#[inline(always)]
+fn class_ring<R: ClassRing, const N: usize>(ring: &R) -> Option<&Ring<Chunk<N>>> {
+    let ring: &dyn Any = ring;
+    ring.downcast_ref::<EnabledRing<N>>().map(|ring| &ring.0)
+}
+
+/// Returns ring of the class that blocks are routed to.
+///
+/// Blocks are routed to enabled classes only, see [`Class::route`].
+#[inline(always)]
+fn routed<T>(ring: Option<&T>) -> &T {
+    match ring {
+        Some(ring) => ring,
+        None => unreachable!("Block is routed to disabled size class"),
+    }
+}
+
+/// Size classes that [`RingAlloc`] serves from its own rings.
+///
+/// Implemented by [`Classes`] for every combination of flags.
+pub trait SizeClasses {
+    #[doc(hidden)]
+    type TinyRing: ClassRing;
+
+    #[doc(hidden)]
+    type SmallRing: ClassRing;
+
+    #[doc(hidden)]
+    type LargeRing: ClassRing;
+
+    /// Largest size and alignment of blocks of tiny class.
+    #[doc(hidden)]
+    const TINY_MAX_SIZE: usize = TINY_ALLOCATION_MAX_SIZE;
+
+    /// Largest size and alignment of blocks of small class.
+    #[doc(hidden)]
+    const SMALL_MAX_SIZE: usize = SMALL_ALLOCATION_MAX_SIZE;
+
+    /// Largest size and alignment of blocks of large class.
+    #[doc(hidden)]
+    const LARGE_MAX_SIZE: usize = LARGE_ALLOCATION_MAX_SIZE;
+}
+
+/// Selects size classes served by [`RingAlloc`] at compile time.
+///
+/// Flags enable rings for tiny (up to 16 bytes), small (up to 256 bytes)
+/// and large (up to 64KiB) blocks respectively.
+/// Rings of disabled classes are not stored at all,
+/// so an arena that allocates blocks of single class
+/// is smaller and dispatches allocations without branches.
+///
+/// Blocks of disabled class are served by next larger enabled class,
+/// or by the underlying allocator if there's none.
+///
+/// All classes are enabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Classes<const TINY: bool = true, const SMALL: bool = true, const LARGE: bool = true>;
+
+macro_rules! class_ring {
+    (true, $size:expr) => { EnabledRing<{ $size }> };
+    (false, $size:expr) => { NoRing };
+}
+
+macro_rules! size_classes {
+    ($($tiny:tt $small:tt $large:tt;)*) => {$(
+        impl SizeClasses for Classes<$tiny, $small, $large> {
+            type TinyRing = class_ring!($tiny, TINY_ALLOCATION_CHUNK_SIZE);
+            type SmallRing = class_ring!($small, SMALL_ALLOCATION_CHUNK_SIZE);
+            type LargeRing = class_ring!($large, LARGE_ALLOCATION_CHUNK_SIZE);
+        }
+    )*};
+}
+
+size_classes! {
+    true true true;
+    true true false;
+    true false true;
+    true false false;
+    false true true;
+    false true false;
+    false false true;
+    false false false;
+}
+
+/// Selects thresholds between size classes served by [`RingAlloc`] at compile time.
+///
+/// Blocks with size and alignment up to `TINY` bytes are tiny,
+/// up to `SMALL` bytes are small and up to `LARGE` bytes are large.
+/// Larger blocks are served by the underlying allocator.
+/// Chunk sizes of the classes are not affected.
+///
+/// Thresholds must be non-decreasing and each must not exceed eighth
+/// of the chunk size of its class, which is checked at compile time.
+/// Defaults match [`Classes`].
+/// All classes are enabled.
+///
+/// Intended to tune thresholds for particular workload,
+/// see `thresholds` benchmarks.
+/// Defaults are the recommended starting point, raising `SMALL` to `512`
+/// helps workloads dominated by blocks of a few hundred bytes.
+#[cfg(feature = "const-generic-config")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Thresholds<
+    const TINY: usize = TINY_ALLOCATION_MAX_SIZE,
+    const SMALL: usize = SMALL_ALLOCATION_MAX_SIZE,
+    const LARGE: usize = LARGE_ALLOCATION_MAX_SIZE,
+>;
+
+#[cfg(feature = "const-generic-config")]
+impl<const TINY: usize, const SMALL: usize, const LARGE: usize> SizeClasses
+    for Thresholds<TINY, SMALL, LARGE>
+{
+    type TinyRing = EnabledRing<{ TINY_ALLOCATION_CHUNK_SIZE }>;
+    type SmallRing = EnabledRing<{ SMALL_ALLOCATION_CHUNK_SIZE }>;
+    type LargeRing = EnabledRing<{ LARGE_ALLOCATION_CHUNK_SIZE }>;
+
+    const TINY_MAX_SIZE: usize = checked_thresholds(TINY, SMALL, LARGE)[0];
+    const SMALL_MAX_SIZE: usize = checked_thresholds(TINY, SMALL, LARGE)[1];
+    const LARGE_MAX_SIZE: usize = checked_thresholds(TINY, SMALL, LARGE)[2];
+}
+
+/// Returns thresholds of [`Thresholds`], panicking if they are invalid.
+#[cfg(feature = "const-generic-config")]
+const fn checked_thresholds(tiny: usize, small: usize, large: usize) -> [usize; 3] {
+    assert!(
+        tiny <= small && small <= large,
+        "Size class thresholds must be non-decreasing"
+    );
+    assert!(
+        tiny <= TINY_ALLOCATION_CHUNK_SIZE / 8
+            && small <= SMALL_ALLOCATION_CHUNK_SIZE / 8
+            && large <= LARGE_ALLOCATION_CHUNK_SIZE / 8,
+        "Size class threshold must not exceed eighth of the chunk size"
+    );
+    [tiny, small, large]
+}
+
+/// Strategy of memory reuse.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Chunks are rotated in FIFO order.
+    /// Chunk is reused when it comes back to the head and all its blocks are freed.
+    Ring,
+
+    /// Deallocation of the last block in the chunk rewinds chunk's cursor.
+    /// Chunks are not rotated.
+    Lifo,
+
+    /// Deallocation of blocks from chunks is no-op.
+    /// Chunks are reused only after reset.
+    Bump,
+}
+
+struct Rings<A: Allocator, C: SizeClasses> {
+    tiny: C::TinyRing,
+    small: C::SmallRing,
+    large: C::LargeRing,
+    allocator: ManuallyDrop<A>,
+    ref_cnt: Cell<usize>,
+
+    /// Number of weak handles plus one held by all strong handles together.
+    weak_cnt: Cell<usize>,
+    mode: Mode,
+
+    /// Free adjacent unused chunks with single call to underlying allocator.
+    merge_frees: Cell<bool>,
+
+    /// Fail oversized allocations instead of forwarding them to the underlying allocator.
+    strict: Cell<bool>,
+
+    /// Free non-head chunks as soon as they become unused.
+    eager_reclaim: bool,
+
+    /// Memory of every chunk begins at address aligned to this value.
+    min_align: usize,
+
+    /// Sizes of blocks are rounded up to multiple of this value.
+    size_granularity: usize,
+
+    /// Small chunk that the rings are embedded into.
+    /// It is freed only when the rings are deallocated.
+    home: Option<NonNull<SmallChunk>>,
+
+    /// Block returned by last [`RingAlloc::scratch`] call.
+    scratch: Cell<Option<(NonNull<u8>, Layout)>>,
+
+    /// Each new chunk of a ring is this many times larger than previous one.
+    growth_factor: Cell<usize>,
+
+    /// Layouts of live blocks by their addresses.
+    #[cfg(feature = "debug-validate")]
+    live: core::cell::RefCell<std::collections::BTreeMap<usize, Layout>>,
+}
+
+impl<A, C> Rings<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn tiny_ring(&self) -> Option<&Ring<TinyChunk>> {
+        class_ring(&self.tiny)
+    }
+
+    #[inline(always)]
+    fn small_ring(&self) -> Option<&Ring<SmallChunk>> {
+        class_ring(&self.small)
+    }
+
+    #[inline(always)]
+    fn large_ring(&self) -> Option<&Ring<LargeChunk>> {
+        class_ring(&self.large)
+    }
+
+    #[inline(always)]
+    fn new_inline(allocator: A, mode: Mode, eager_reclaim: bool) -> Self {
+        Rings {
+            tiny: ClassRing::new(),
+            small: ClassRing::new(),
+            large: ClassRing::new(),
+            allocator: ManuallyDrop::new(allocator),
+            ref_cnt: Cell::new(1),
+            weak_cnt: Cell::new(1),
+            mode,
+            merge_frees: Cell::new(false),
+            strict: Cell::new(false),
+            eager_reclaim,
+            min_align: 1,
+            size_granularity: 1,
+            home: None,
+            scratch: Cell::new(None),
+            growth_factor: Cell::new(1),
+            #[cfg(feature = "debug-validate")]
+            live: core::cell::RefCell::new(std::collections::BTreeMap::new()),
+        }
+    }
+
+    #[inline(always)]
+    fn try_new_in(
+        allocator: A,
+        mode: Mode,
+        eager_reclaim: bool,
+    ) -> Result<NonNull<Self>, AllocError> {
+        let ptr = allocator.allocate(Layout::new::<Self>())?;
+        let inner = Rings::new_inline(allocator, mode, eager_reclaim);
+
+        let ptr = ptr.cast::<Self>();
+
+        // Safety: `ptr` is valid pointer to `Self` allocated by `allocator`.
+        unsafe {
+            core::ptr::write(ptr.as_ptr(), inner);
+        }
+
+        Ok(ptr)
+    }
+
+    #[inline(always)]
+    #[cfg(not(no_global_oom_handling))]
+    fn new_in(allocator: A, mode: Mode, eager_reclaim: bool) -> NonNull<Self> {
+        match Self::try_new_in(allocator, mode, eager_reclaim) {
+            Ok(ptr) => ptr,
+            #[cfg(feature = "alloc")]
+            Err(AllocError) => {
+                alloc::alloc::handle_alloc_error(Layout::new::<Self>());
+            }
+            #[cfg(not(feature = "alloc"))]
+            Err(AllocError) => {
+                core::panic!("Failed to allocate Rings");
+            }
+        }
+    }
+
+    /// Sets minimum alignment of chunk memory of freshly created rings.
+    fn set_min_align(ptr: NonNull<Self>, align: usize) {
+        debug_assert!(align.is_power_of_two() && align <= MAX_MIN_ALIGN);
+
+        // Safety: `ptr` is valid pointer to `Self` that is not shared yet.
+        unsafe { (*ptr.as_ptr()).min_align = align };
+    }
+
+    /// Sets size granularity of freshly created rings.
+    fn set_size_granularity(ptr: NonNull<Self>, granularity: usize) {
+        debug_assert!(granularity.is_power_of_two());
+
+        // Safety: `ptr` is valid pointer to `Self` that is not shared yet.
+        unsafe { (*ptr.as_ptr()).size_granularity = granularity };
+    }
+
+    /// Rounds size of the layout up to size granularity,
+    /// but not beyond the size class of the layout.
+    ///
+    /// Applied to layouts on both allocation and deallocation,
+    /// so block is always accounted with the same size.
+    #[inline(always)]
+    fn round_layout(&self, layout: Layout) -> Layout {
+        let granularity = self.size_granularity;
+        if granularity == 1 {
+            return layout;
+        }
+
+        let max = match Class::of::<C>(layout) {
+            Class::Tiny => C::TINY_MAX_SIZE,
+            Class::Small => C::SMALL_MAX_SIZE,
+            Class::Large => C::LARGE_MAX_SIZE,
+            // Underlying allocator gains nothing from rounding.
+            Class::Oversized => return layout,
+        };
+
+        let size = ((layout.size() + granularity - 1) & !(granularity - 1)).min(max);
+
+        // Safety: `size` is not smaller than size of the valid layout
+        // and not larger than maximum size of its class.
+        unsafe { Layout::from_size_align_unchecked(size, layout.align()) }
+    }
+
+    fn inc_ref(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+        me.ref_cnt.set(me.ref_cnt.get() + 1);
+    }
+
+    fn dec_ref(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+
+        debug_assert_ne!(me.ref_cnt.get(), 0);
+        let new_ref_cnt = me.ref_cnt.get() - 1;
+        me.ref_cnt.set(new_ref_cnt);
+
+        if new_ref_cnt == 0 {
+            Self::free(ptr);
+        }
+    }
+
+    fn inc_weak(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+        me.weak_cnt.set(me.weak_cnt.get() + 1);
+    }
+
+    fn dec_weak(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+
+        debug_assert_ne!(me.weak_cnt.get(), 0);
+        let new_weak_cnt = me.weak_cnt.get() - 1;
+        me.weak_cnt.set(new_weak_cnt);
+
+        if new_weak_cnt == 0 {
+            Self::dealloc(ptr);
+        }
+    }
+
+    /// Frees all chunks when last strong handle is dropped.
+    #[cold]
+    fn free(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+
+        me.release_scratch();
+        me.free_all();
+
+        // Rings are deallocated without running destructors of their fields.
+        #[cfg(feature = "debug-validate")]
+        drop(me.live.take());
+
+        // Release weak reference held by strong handles.
+        Self::dec_weak(ptr);
+    }
+
+    /// Frees block with the rings when last handle of any kind is dropped.
+    #[cold]
+    fn dealloc(ptr: NonNull<Self>) {
+        // Safety: `ptr` is valid pointer to `Self`.
+        let me = unsafe { ptr.as_ref() };
+
+        // Safety: taking allocator out `ManuallyDrop`.
+        // The value is dropped immediately after.
+        let allocator = unsafe { core::ptr::read(&*me.allocator) };
+
+        match me.home {
+            // Safety: `ptr` was allocated by `me.allocator`.
+            None => unsafe {
+                allocator.deallocate(ptr.cast(), Layout::new::<Self>());
+            },
+            // Safety: home chunk was allocated by `me.allocator`
+            // and is not linked into rings anymore.
+            Some(home) => unsafe {
+                Chunk::free(home, &allocator);
+            },
+        }
+    }
+
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match Class::of::<C>(layout).route::<C>() {
+            Class::Tiny => RingAlloc::_allocate(routed(self.tiny_ring()), layout, self, false),
+            Class::Small => RingAlloc::_allocate(routed(self.small_ring()), layout, self, false),
+            Class::Large => RingAlloc::_allocate(routed(self.large_ring()), layout, self, false),
+            Class::Oversized if self.strict.get() => Err(AllocError),
+            Class::Oversized => {
+                let ptr = self.allocator.allocate(layout)?;
+
+                #[cfg(feature = "debug-validate")]
+                self.validate_allocate(ptr.cast(), layout);
+
+                Ok(ptr)
+            }
+        }
+    }
+
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match Class::of::<C>(layout).route::<C>() {
+            Class::Tiny => RingAlloc::_allocate(routed(self.tiny_ring()), layout, self, true),
+            Class::Small => RingAlloc::_allocate(routed(self.small_ring()), layout, self, true),
+            Class::Large => RingAlloc::_allocate(routed(self.large_ring()), layout, self, true),
+            Class::Oversized if self.strict.get() => Err(AllocError),
+            Class::Oversized => {
+                let ptr = self.allocator.allocate_zeroed(layout)?;
+
+                #[cfg(feature = "debug-validate")]
+                self.validate_allocate(ptr.cast(), layout);
+
+                Ok(ptr)
+            }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must be allocated with `self.allocate(layout)`.
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+        self.validate_generation(ptr, self.round_layout(layout));
+
+        // Safety: `ptr` is allocated with `self.allocate(layout)`.
+        match unsafe { Class::of_block::<C>(ptr, layout) } {
+            Class::Tiny => unsafe {
+                RingAlloc::_deallocate(ptr, layout, routed(self.tiny_ring()), self);
+            },
+            Class::Small => unsafe {
+                RingAlloc::_deallocate(ptr, layout, routed(self.small_ring()), self);
+            },
+            Class::Large => unsafe {
+                RingAlloc::_deallocate(ptr, layout, routed(self.large_ring()), self);
+            },
+            // Safety: `ptr` is valid pointer allocated by `self.allocator`.
+            Class::Oversized => unsafe {
+                #[cfg(feature = "debug-validate")]
+                self.validate_deallocate(ptr);
+
+                self.allocator.deallocate(ptr, layout);
+            },
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Same as for [`Allocator::grow`].
+    #[inline(always)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (old_rounded, new_rounded) =
+            (self.round_layout(old_layout), self.round_layout(new_layout));
+
+        #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+        self.validate_generation(ptr, old_rounded);
+
+        // Safety: `ptr` is allocated with `self.allocate(old_layout)`.
+        if unsafe { RingAlloc::<A, C>::grow_in_chunk(ptr, old_rounded, new_rounded) } {
+            #[cfg(feature = "debug-validate")]
+            self.validate_resize(ptr, new_rounded);
+
+            return Ok(NonNull::slice_from_raw_parts(ptr, new_rounded.size()));
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+
+        // Safety: new block is at least `old_layout.size()` bytes
+        // and doesn't overlap with the old one.
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                ptr.as_ptr(),
+                new_ptr.as_ptr().cast::<u8>(),
+                old_layout.size(),
+            );
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new_ptr)
+    }
+
+    /// Records newly allocated block as live.
+    ///
+    /// # Panics
+    ///
+    /// Panics if block overlaps any live block.
+    #[cfg(feature = "debug-validate")]
+    fn validate_allocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let start = crate::addr(ptr.as_ptr());
+        // Zero-sized blocks still occupy their address.
+        let end = start + layout.size().max(1);
+
+        let mut live = self.live.borrow_mut();
+
+        let prev = live.range(..=start).next_back();
+        let next = live.range(start..).next();
+
+        for (&other, other_layout) in prev.into_iter().chain(next) {
+            let other_end = other + other_layout.size().max(1);
+            if other < end && start < other_end {
+                panic!(
+                    "Block {:#x}..{:#x} overlaps live block {:#x}..{:#x}",
+                    start, end, other, other_end
+                );
+            }
+        }
+
+        live.insert(start, layout);
+    }
+
+    /// Removes block from live blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if block is not live, i.e. it is deallocated twice
+    /// or was never allocated by the ring-allocator.
+    #[cfg(feature = "debug-validate")]
+    fn validate_deallocate(&self, ptr: NonNull<u8>) {
+        let start = crate::addr(ptr.as_ptr());
+        if self.live.borrow_mut().remove(&start).is_none() {
+            panic!(
+                "Block {:#x} is deallocated twice or was never allocated",
+                start
+            );
+        }
+    }
+
+    /// Updates layout of live block grown in place.
+    #[cfg(feature = "debug-validate")]
+    fn validate_resize(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.validate_deallocate(ptr);
+        self.validate_allocate(ptr, layout);
+    }
+
+    /// Panics if block was allocated before its chunk was reset.
+    ///
+    /// Chunk is looked up by block address in the rings,
+    /// since metadata of stale block may be overwritten.
+    #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+    fn validate_generation(&self, ptr: NonNull<u8>, layout: Layout) {
+        fn check<const N: usize>(
+            ring: Option<&Ring<Chunk<N>>>,
+            ptr: NonNull<u8>,
+            layout: Layout,
+        ) -> bool {
+            let mut chunk = ring.and_then(|ring| ring.head.get());
+            while let Some(c) = chunk {
+                // Safety: chunks in the ring are always valid.
+                let c = unsafe { c.as_ref() };
+
+                // Safety: `ptr` is allocated with `layout` by the ring-allocator.
+                if unsafe { c.check_generation(ptr.as_ptr(), layout) } {
+                    return true;
+                }
+                chunk = c.next();
+            }
+            false
+        }
+
+        let _ = check(self.tiny_ring(), ptr, layout)
+            || check(self.small_ring(), ptr, layout)
+            || check(self.large_ring(), ptr, layout);
+    }
+
+    /// Forgets live blocks that don't survive reset.
+    ///
+    /// # Safety
+    ///
+    /// Must be called right before chunks are reset.
+    #[cfg(feature = "debug-validate")]
+    unsafe fn validate_reset(&self) {
+        self.live.borrow_mut().retain(|&start, layout| {
+            let ptr = start as *mut u8;
+
+            // Safety: live blocks are allocated by the ring-allocator
+            // and their chunks are not reset yet.
+            unsafe {
+                match Class::of_block::<C>(NonNull::new_unchecked(ptr), *layout) {
+                    Class::Tiny => start < TinyChunk::chunk_of(ptr, *layout).as_ref().floor.get(),
+                    Class::Small => start < SmallChunk::chunk_of(ptr, *layout).as_ref().floor.get(),
+                    Class::Large => start < LargeChunk::chunk_of(ptr, *layout).as_ref().floor.get(),
+                    Class::Oversized => true,
+                }
+            }
+        });
+    }
+
+    #[inline(always)]
+    fn clean_all(&self) {
+        if let Some(ring) = self.tiny_ring() {
+            self.clean(ring);
+        }
+        if let Some(ring) = self.small_ring() {
+            self.clean(ring);
+        }
+        if let Some(ring) = self.large_ring() {
+            self.clean(ring);
+        }
+    }
+
+    /// Returns `true` if the chunk holds the rings.
+    #[inline(always)]
+    fn is_home<const N: usize>(&self, chunk: NonNull<Chunk<N>>) -> bool {
+        self.home.map(NonNull::cast) == Some(chunk.cast::<u8>())
+    }
+
+    /// Allocates next chunk for the ring.
+    ///
+    /// Chunk capacity grows by growth factor with each chunk allocated for the ring
+    /// up to [`MAX_CHUNK_GROWTH`] times the size of the class.
+    #[inline(always)]
+    fn new_chunk<const N: usize>(
+        &self,
+        ring: &Ring<Chunk<N>>,
+        zeroed: bool,
+    ) -> Result<NonNull<Chunk<N>>, AllocError> {
+        // Chunks are found by masking block address, so they can't be larger.
+        #[cfg(feature = "aligned-chunks")]
+        let capacity = N;
+
+        #[cfg(not(feature = "aligned-chunks"))]
+        let capacity = {
+            let exp = ring.allocated.get().min(MAX_CHUNK_GROWTH) as u32;
+            N * self
+                .growth_factor
+                .get()
+                .saturating_pow(exp)
+                .min(MAX_CHUNK_GROWTH)
+        };
+
+        let chunk_ptr =
+            match Chunk::<N>::with_capacity(&*self.allocator, capacity, self.min_align, zeroed) {
+                Ok(chunk_ptr) => chunk_ptr,
+                Err(AllocError) => self.new_overflow_chunk(ring, capacity, zeroed)?,
+            };
+        ring.allocated.set(ring.allocated.get() + 1);
+        Ok(chunk_ptr)
+    }
+
+    /// Frees unused chunks of all rings and retries chunk allocation once.
+    ///
+    /// Last resort when underlying allocator is exhausted,
+    /// memory held by idle size classes may be enough for the new chunk.
+    #[cold]
+    fn new_chunk_after_clean<const N: usize>(
+        &self,
+        ring: &Ring<Chunk<N>>,
+        zeroed: bool,
+    ) -> Result<NonNull<Chunk<N>>, AllocError> {
+        self.clean_all();
+        self.new_chunk(ring, zeroed)
+    }
+
+    /// Allocates chunk from overflow allocator of the ring, if any.
+    #[cold]
+    fn new_overflow_chunk<const N: usize>(
+        &self,
+        ring: &Ring<Chunk<N>>,
+        capacity: usize,
+        zeroed: bool,
+    ) -> Result<NonNull<Chunk<N>>, AllocError> {
+        let overflow = ring.overflow.get().ok_or(AllocError)?;
+        let chunk_ptr = Chunk::<N>::with_capacity(overflow, capacity, self.min_align, zeroed)?;
+
+        // Safety: chunk is just allocated and not shared yet.
+        unsafe { (*chunk_ptr.as_ptr()).overflow = Some(overflow) };
+        Ok(chunk_ptr)
+    }
+
+    #[inline(always)]
+    fn clean<const N: usize>(&self, ring: &Ring<Chunk<N>>) {
+        let allocator = &*self.allocator;
+        let merge = self.merge_frees.get();
 
-    #[inline(always)]
-    fn clean<const N: usize>(ring: &Ring<Chunk<N>>, allocator: &A) {
         let mut chunk = &ring.head;
+        let mut tail = None;
+        let mut run = FreeRun::new();
+
+        while let Some(c) = chunk.get() {
+            if unsafe { c.as_ref().unused() } && !self.is_home(c) {
+                // Safety: chunks in the ring are always valid.
+                let next = unsafe { c.as_ref().next() };
+                chunk.set(next);
+                if let Some(next) = next {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { next.as_ref().prev.set(tail) };
+                }
+
+                // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
+                // Merged frees are enabled only if `allocator` accepts them.
+                unsafe {
+                    if merge {
+                        run.push(c, allocator);
+                    } else {
+                        Chunk::free(c, allocator);
+                    }
+                }
+            } else {
+                tail = Some(c);
+
+                // Safety: chunks in the ring are always valid.
+                chunk = unsafe { &c.as_ref().next };
+            }
+        }
+
+        // Safety: same as above.
+        unsafe {
+            run.flush(allocator);
+        }
+
+        ring.tail.set(tail);
+    }
+
+    /// # Safety
+    ///
+    /// Blocks allocated from the rings must not be used afterwards.
+    unsafe fn reset_all(&self) {
+        // Safety: chunks are reset below.
+        #[cfg(feature = "debug-validate")]
+        unsafe {
+            self.validate_reset()
+        };
+
+        // Safety: guaranteed by caller.
+        unsafe {
+            if let Some(ring) = self.tiny_ring() {
+                Self::reset_chunks(ring);
+            }
+            if let Some(ring) = self.small_ring() {
+                Self::reset_chunks(ring);
+            }
+            if let Some(ring) = self.large_ring() {
+                Self::reset_chunks(ring);
+            }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Blocks allocated from the ring must not be used afterwards.
+    unsafe fn reset_chunks<const N: usize>(ring: &Ring<Chunk<N>>) {
+        // Chunks with committed blocks are moved to the tail,
+        // so that rewound chunks are reused first.
+        let mut committed: Option<(NonNull<Chunk<N>>, NonNull<Chunk<N>>)> = None;
+
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            // Safety: guaranteed by caller.
+            // Local chunks are never shared with other threads.
+            unsafe { c_ref.reset_all() };
+
+            chunk = c_ref.next();
+
+            if c_ref.committed() {
+                RingAlloc::<A, C>::unlink(ring, c);
+                committed = match committed {
+                    None => Some((c, c)),
+                    Some((first, last)) => {
+                        // Safety: chunks in the ring are always valid.
+                        unsafe { last.as_ref().next.set(Some(c)) };
+                        c_ref.prev.set(Some(last));
+                        Some((first, c))
+                    }
+                };
+            }
+        }
+
+        if let Some((first, last)) = committed {
+            match ring.tail.get() {
+                None => ring.head.set(Some(first)),
+                Some(tail) => {
+                    // Safety: chunks in the ring are always valid.
+                    unsafe { tail.as_ref().next.set(Some(first)) };
+                    unsafe { first.as_ref().prev.set(Some(tail)) };
+                }
+            }
+            ring.tail.set(Some(last));
+        }
+    }
+
+    fn commit_all(&self) {
+        if let Some(ring) = self.tiny_ring() {
+            Self::commit_chunks(ring);
+        }
+        if let Some(ring) = self.small_ring() {
+            Self::commit_chunks(ring);
+        }
+        if let Some(ring) = self.large_ring() {
+            Self::commit_chunks(ring);
+        }
+    }
+
+    fn commit_chunks<const N: usize>(ring: &Ring<Chunk<N>>) {
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            // Local chunks are never shared with other threads.
+            c_ref.commit();
+
+            chunk = c_ref.next();
+        }
+    }
+
+    /// Deallocates block returned by last [`RingAlloc::scratch`] call.
+    #[inline(always)]
+    fn release_scratch(&self) {
+        if let Some((ptr, layout)) = self.scratch.take() {
+            // Safety: scratch block is allocated from these rings with `layout`
+            // and owned by them.
+            unsafe { self.deallocate(ptr, layout) }
+        }
+    }
+
+    /// Frees all chunks except the one that holds the rings.
+    fn free_all(&self) {
+        if let Some(ring) = self.tiny_ring() {
+            self.free_chunks(ring);
+        }
+        if let Some(ring) = self.small_ring() {
+            self.free_chunks(ring);
+        }
+        if let Some(ring) = self.large_ring() {
+            self.free_chunks(ring);
+        }
+    }
+
+    #[inline(always)]
+    fn free_chunks<const N: usize>(&self, ring: &Ring<Chunk<N>>) {
+        let allocator = &*self.allocator;
+        let merge = self.merge_frees.get();
+
+        let mut chunk = ring.head.take();
+        let mut run = FreeRun::new();
+
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            chunk = unsafe { c.as_ref().next() };
+
+            // Freed with the rings.
+            if self.is_home(c) {
+                continue;
+            }
+
+            // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
+            // Merged frees are enabled only if `allocator` accepts them.
+            unsafe {
+                if merge {
+                    run.push(c, allocator);
+                } else {
+                    Chunk::free(c, allocator);
+                }
+            }
+        }
+
+        // Safety: same as above.
+        unsafe {
+            run.flush(allocator);
+        }
+
+        ring.tail.set(None);
+    }
+}
+
+impl<A> Rings<A, Classes>
+where
+    A: Allocator,
+{
+    /// Allocates small chunk and places the rings at its beginning.
+    /// The chunk is linked into small ring and serves allocations as usual.
+    ///
+    /// Falls back to separate allocation if the rings don't fit into the chunk.
+    fn try_new_embedded_in(allocator: A) -> Result<NonNull<Self>, AllocError> {
+        let chunk_ptr = SmallChunk::new(&allocator)?;
+
+        // Safety: `chunk_ptr` is fresh chunk.
+        let ptr = match unsafe { SmallChunk::reserve_front(chunk_ptr, Layout::new::<Self>()) } {
+            Some(ptr) => ptr.cast::<Self>(),
+            None => {
+                // Safety: `chunk_ptr` is allocated by `allocator` and not linked.
+                unsafe { Chunk::free(chunk_ptr, &allocator) };
+                return Self::try_new_in(allocator, Mode::Ring, false);
+            }
+        };
+
+        let mut inner = Self::new_inline(allocator, Mode::Ring, false);
+        let ring = routed(inner.small_ring());
+        ring.head.set(Some(chunk_ptr));
+        ring.tail.set(Some(chunk_ptr));
+        inner.home = Some(chunk_ptr);
+
+        // Safety: `ptr` is reserved in the chunk for `Self`.
+        unsafe {
+            core::ptr::write(ptr.as_ptr(), inner);
+        }
+
+        Ok(ptr)
+    }
+}
+
+#[cfg(not(no_global_oom_handling))]
+#[cfg(feature = "alloc")]
+impl RingAlloc {
+    /// Returns new [`RingAlloc`] that uses [`Global`] allocator.
+    #[inline(always)]
+    pub fn new() -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator_api2::alloc::Global, Mode::Ring, false),
+        }
+    }
+
+    /// Returns new [`RingAlloc`] that uses [`Global`] allocator
+    /// and keeps its rings at the beginning of the first chunk.
+    ///
+    /// See [`RingAlloc::new_embedded_in`] for details.
+    #[inline(always)]
+    pub fn new_embedded() -> Self {
+        RingAlloc::new_embedded_in(allocator_api2::alloc::Global)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl RingAlloc {
+    /// Returns new [`LazyRingAlloc`] that uses [`Global`] allocator.
+    ///
+    /// See [`RingAlloc::lazy_in`] for details.
+    #[inline(always)]
+    pub fn lazy() -> LazyRingAlloc<allocator_api2::alloc::Global> {
+        RingAlloc::lazy_in(allocator_api2::alloc::Global)
+    }
+}
+
+#[cfg(not(no_global_oom_handling))]
+impl<A> Default for RingAlloc<A>
+where
+    A: Allocator + Default,
+{
+    #[inline(always)]
+    fn default() -> Self {
+        RingAlloc::new_in(A::default())
+    }
+}
+
+/// Wraps allocator into new [`RingAlloc`].
+///
+/// Same as [`RingAlloc::new_in`] and calls [`handle_alloc_error`] if memory for rings can't be allocated.
+/// Use [`RingAlloc::try_new_in`] for fallible construction.
+/// Note that `TryFrom` implementation derived from this one is infallible.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use allocator_api2::alloc::Global;
+/// use ring_alloc::RingAlloc;
+///
+/// let alloc: RingAlloc<Global> = Global.into();
+/// let fallible = RingAlloc::try_new_in(Global).unwrap();
+/// assert!(alloc != fallible);
+/// # }
+/// ```
+///
+/// [`handle_alloc_error`]: allocator_api2::alloc::handle_alloc_error
+#[cfg(not(no_global_oom_handling))]
+impl<A> From<A> for RingAlloc<A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn from(allocator: A) -> Self {
+        RingAlloc::new_in(allocator)
+    }
+}
+
+impl<'a, A> RingAlloc<&'a A>
+where
+    A: Allocator,
+{
+    /// Returns new [`RingAlloc`] that borrows given allocator instead of owning it.
+    ///
+    /// Chunks are allocated from and returned to the borrowed allocator,
+    /// including when last handle is dropped.
+    /// The borrow is part of the type, so [`RingAlloc`] and collections
+    /// that use it can't outlive the borrowed allocator.
+    ///
+    /// ```compile_fail
+    /// # use allocator_api2::alloc::Global;
+    /// # use ring_alloc::RingAlloc;
+    /// let alloc;
+    /// {
+    ///     let pool = Global;
+    ///     alloc = RingAlloc::borrowing_in(&pool);
+    /// }
+    /// drop(alloc);
+    /// ```
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn borrowing_in(allocator: &'a A) -> Self {
+        RingAlloc::new_in(allocator)
+    }
+
+    /// Attempts to create new [`RingAlloc`] that borrows given allocator.
+    ///
+    /// See [`RingAlloc::borrowing_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_borrowing_in(allocator: &'a A) -> Result<Self, AllocError> {
+        RingAlloc::try_new_in(allocator)
+    }
+}
+
+impl<A> RingAlloc<A>
+where
+    A: Allocator,
+{
+    /// Returns new [`RingAlloc`] that uses given allocator.
+    ///
+    /// Block for the rings is allocated from `allocator` right away,
+    /// while chunks are allocated on first use.
+    /// So the first allocation from the ring-allocator takes
+    /// two calls to `allocator` in total and lands in a separate region of memory.
+    /// See [`RingAlloc::new_embedded_in`] to allocate the rings together with the first chunk
+    /// and [`RingAlloc::lazy_in`] to defer the rings allocation as well.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new_in(allocator: A) -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator, Mode::Ring, false),
+        }
+    }
+
+    /// Returns new [`LazyRingAlloc`] that uses given allocator.
+    ///
+    /// Unlike [`RingAlloc::new_in`] this doesn't allocate anything
+    /// until the first allocation is made.
+    #[inline(always)]
+    pub const fn lazy_in(allocator: A) -> LazyRingAlloc<A> {
+        LazyRingAlloc {
+            ring: OnceCell::new(),
+            allocator: Cell::new(Some(allocator)),
+        }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_new_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_in(allocator, Mode::Ring, false)?,
+        })
+    }
+
+    /// Returns new [`RingAlloc`] that uses given allocator
+    /// and frees chunks as soon as they become unused.
+    ///
+    /// When last block allocated from a chunk is deallocated and that chunk
+    /// is not the current head of its ring, the chunk is immediately
+    /// returned to the underlying allocator.
+    /// This reduces memory usage of workloads that free blocks in bulk
+    /// at the cost of more calls to the underlying allocator.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new_eager_in(allocator: A) -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator, Mode::Ring, true),
+        }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator
+    /// and frees chunks as soon as they become unused.
+    ///
+    /// See [`RingAlloc::new_eager_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_new_eager_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_in(allocator, Mode::Ring, true)?,
+        })
+    }
+
+    /// Returns new [`RingAlloc`] that uses given allocator
+    /// and keeps its rings at the beginning of the first chunk.
+    ///
+    /// [`RingAlloc::new_in`] allocates rings separately and each chunk lazily.
+    /// This constructor allocates a chunk for allocations of up to 256 bytes
+    /// right away and places the rings in front of it,
+    /// saving one call to the underlying allocator per ring-allocator.
+    ///
+    /// That chunk is never freed by [`RingAlloc::flush`] or eager reclamation
+    /// and is released when last handle, including weak ones, is dropped.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new_embedded_in(allocator: A) -> Self {
+        match Self::try_new_embedded_in(allocator) {
+            Ok(alloc) => alloc,
+            #[cfg(feature = "alloc")]
+            Err(AllocError) => {
+                alloc::alloc::handle_alloc_error(Layout::new::<Rings<A, Classes>>());
+            }
+            #[cfg(not(feature = "alloc"))]
+            Err(AllocError) => {
+                core::panic!("Failed to allocate Rings");
+            }
+        }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator
+    /// and keeps its rings at the beginning of the first chunk.
+    ///
+    /// See [`RingAlloc::new_embedded_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_new_embedded_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_embedded_in(allocator)?,
+        })
+    }
+
+    /// Returns new [`RingAlloc`] that uses given allocator
+    /// and aligns beginning of every chunk's memory to `align`.
+    ///
+    /// First block allocated from a fresh or reset chunk is aligned to `align`
+    /// without padding, and so are following blocks if all of them
+    /// have sizes that are multiples of `align`.
+    /// Unlike over-aligning each allocation this is a chunk-wide guarantee,
+    /// so consistently aligned workloads, e.g. of 32-byte SIMD types,
+    /// waste no memory on alignment padding.
+    ///
+    /// By default chunk memory is aligned to the pointer alignment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not power of two or is larger than 4096.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn with_min_alignment_in(allocator: A, align: usize) -> Self {
+        assert_min_align(align);
+        let inner = Rings::new_in(allocator, Mode::Ring, false);
+        Rings::set_min_align(inner, align);
+        RingAlloc { inner }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator
+    /// and aligns beginning of every chunk's memory to `align`.
+    ///
+    /// See [`RingAlloc::with_min_alignment_in`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not power of two or is larger than 4096.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_with_min_alignment_in(allocator: A, align: usize) -> Result<Self, AllocError> {
+        assert_min_align(align);
+        let inner = Rings::try_new_in(allocator, Mode::Ring, false)?;
+        Rings::set_min_align(inner, align);
+        Ok(RingAlloc { inner })
+    }
+
+    /// Returns new [`RingAlloc`] that uses given allocator
+    /// and rounds sizes of blocks up to multiple of `granularity`.
+    ///
+    /// Fewer distinct block sizes pack and reuse memory better after reset
+    /// in exchange for a few bytes per block.
+    /// Rounding never moves block to a larger size class,
+    /// sizes are rounded at most to the maximum size of their class.
+    /// Blocks are deallocated with sizes rounded the same way.
+    ///
+    /// Oversized blocks served by the underlying allocator are not rounded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `granularity` is not power of two.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn with_size_rounding_in(allocator: A, granularity: usize) -> Self {
+        assert_size_granularity(granularity);
+        let inner = Rings::new_in(allocator, Mode::Ring, false);
+        Rings::set_size_granularity(inner, granularity);
+        RingAlloc { inner }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator
+    /// and rounds sizes of blocks up to multiple of `granularity`.
+    ///
+    /// See [`RingAlloc::with_size_rounding_in`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `granularity` is not power of two.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_with_size_rounding_in(allocator: A, granularity: usize) -> Result<Self, AllocError> {
+        assert_size_granularity(granularity);
+        let inner = Rings::try_new_in(allocator, Mode::Ring, false)?;
+        Rings::set_size_granularity(inner, granularity);
+        Ok(RingAlloc { inner })
+    }
+
+    /// Returns new [`RingAlloc`] with stack discipline that uses given allocator.
+    ///
+    /// Deallocation of the most recently allocated block in a chunk
+    /// rewinds chunk's cursor, so memory is reused immediately.
+    /// Chunks are never rotated, new chunk is added only when current one is full.
+    ///
+    /// This mode is faster and uses less memory for strictly stack-like (LIFO)
+    /// allocation patterns, e.g. in recursive or scoped workloads.
+    /// Blocks deallocated out of order are reclaimed when whole chunk becomes unused.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new_lifo_in(allocator: A) -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator, Mode::Lifo, false),
+        }
+    }
+
+    /// Attempts to create new [`RingAlloc`] with stack discipline that uses given allocator.
+    ///
+    /// See [`RingAlloc::new_lifo_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_new_lifo_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_in(allocator, Mode::Lifo, false)?,
+        })
+    }
+
+    /// Returns new bump-only [`RingAlloc`] that uses given allocator.
+    ///
+    /// Deallocation of blocks from chunks is no-op,
+    /// skipping all bookkeeping, so memory is reclaimed only by
+    /// [`RingAlloc::reset`] or when ring-allocator is dropped.
+    /// Oversized blocks are still deallocated with underlying allocator.
+    ///
+    /// This mode is the fastest for arena workloads
+    /// that allocate a batch of values and discard them all at once.
+    ///
+    /// Beware that collections that reallocate, e.g. growing `Vec`,
+    /// leave their old blocks behind, wasting chunk memory until reset.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new_bump_only_in(allocator: A) -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator, Mode::Bump, false),
+        }
+    }
+
+    /// Attempts to create new bump-only [`RingAlloc`] that uses given allocator.
+    ///
+    /// See [`RingAlloc::new_bump_only_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_new_bump_only_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_in(allocator, Mode::Bump, false)?,
+        })
+    }
+}
+
+impl<A, C> RingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    /// Returns new [`RingAlloc`] that uses given allocator
+    /// and serves only size classes enabled by `C`.
+    ///
+    /// See [`Classes`] for details.
+    ///
+    /// ```
+    /// # use allocator_api2::alloc::Global;
+    /// # use ring_alloc::{Classes, RingAlloc};
+    /// let alloc = RingAlloc::<Global, Classes<false, true, false>>::with_classes_in(Global);
+    /// let b = allocator_api2::boxed::Box::new_in(1u8, alloc);
+    /// assert_eq!(*b, 1);
+    /// ```
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn with_classes_in(allocator: A) -> Self {
+        RingAlloc {
+            inner: Rings::new_in(allocator, Mode::Ring, false),
+        }
+    }
+
+    /// Attempts to create new [`RingAlloc`] that uses given allocator
+    /// and serves only size classes enabled by `C`.
+    ///
+    /// See [`RingAlloc::with_classes_in`] for details.
+    #[must_use = "dropping the allocator frees its rings"]
+    #[inline(always)]
+    pub fn try_with_classes_in(allocator: A) -> Result<Self, AllocError> {
+        Ok(RingAlloc {
+            inner: Rings::try_new_in(allocator, Mode::Ring, false)?,
+        })
+    }
+
+    /// Attempts to allocate a block of memory with this ring-allocator.
+    /// Returns a pointer to the beginning of the block if successful.
+    ///
+    /// Leaked block keeps its chunk from being reused,
+    /// so ignoring the result is a warning.
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// # use allocator_api2::alloc::{Global, Layout};
+    /// # use ring_alloc::RingAlloc;
+    /// let alloc = RingAlloc::new_in(Global);
+    /// alloc.allocate(Layout::new::<u32>());
+    /// ```
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.allocate(layout)
+    }
+
+    /// Attempts to allocate a block of memory with this ring-allocator,
+    /// reporting why allocation failed.
+    ///
+    /// Same as [`RingAlloc::allocate`], but tells apart
+    /// [`RingAllocError::LayoutTooLarge`] for oversized layouts rejected in strict mode
+    /// from [`RingAllocError::BackingOom`] when underlying allocator fails.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    pub fn try_allocate_detailed(&self, layout: Layout) -> Result<NonNull<[u8]>, RingAllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        match Class::of::<C>(layout).route::<C>() {
+            Class::Oversized if inner.strict.get() => Err(RingAllocError::LayoutTooLarge),
+            _ => inner
+                .allocate(layout)
+                .map_err(|AllocError| RingAllocError::BackingOom),
+        }
+    }
+
+    /// Attempts to allocate a block of zeroed memory with this ring-allocator.
+    ///
+    /// Reused chunks keep track of how far they were handed out,
+    /// so only the part of the block that may contain stale bytes is zeroed.
+    /// Chunks for zeroed allocations are requested from underlying allocator
+    /// with [`Allocator::allocate_zeroed`].
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    pub fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.allocate_zeroed(layout)
+    }
+
+    /// Deallocates the memory referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory [*currently allocated*] via [`RingAlloc::allocate`], and
+    /// * `layout` must [*fit*] that block of memory.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
+    #[inline(always)]
+    pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Safety: `ptr` is allocated by `inner.allocate(layout)`.
+        unsafe { inner.deallocate(ptr, layout) }
+    }
+
+    /// Returns `true` if blocks of `layout` are allocated
+    /// from the underlying allocator directly and are not tracked by the rings.
+    #[inline(always)]
+    pub(crate) fn is_oversized(layout: Layout) -> bool {
+        Class::of::<C>(layout).route::<C>() == Class::Oversized
+    }
+
+    /// Attempts to allocate a block of memory for value of type `T`.
+    ///
+    /// Same as [`RingAlloc::allocate`] with `Layout::new::<T>()`,
+    /// except that size class is selected at compile time.
+    /// Block must be deallocated with [`RingAlloc::deallocate_for`]
+    /// or [`RingAlloc::deallocate`].
+    #[inline(always)]
+    pub fn allocate_for<T>(&self) -> Result<NonNull<T>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let layout = Layout::new::<T>();
+
+        let ptr = match TypeClass::<T, C>::CLASS {
+            Class::Tiny => Self::_allocate(routed(inner.tiny_ring()), layout, inner, false),
+            Class::Small => Self::_allocate(routed(inner.small_ring()), layout, inner, false),
+            Class::Large => Self::_allocate(routed(inner.large_ring()), layout, inner, false),
+            Class::Oversized => inner.allocate(layout),
+        }?;
+        Ok(ptr.cast())
+    }
+
+    /// Deallocates the memory for value of type `T` referenced by `ptr`.
+    ///
+    /// Same as [`RingAlloc::deallocate`] with `Layout::new::<T>()`,
+    /// except that size class is selected at compile time.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block of memory [*currently allocated*] via
+    /// [`RingAlloc::allocate_for::<T>`] or [`RingAlloc::allocate`]
+    /// with `Layout::new::<T>()`, and not grown since.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    #[inline(always)]
+    pub unsafe fn deallocate_for<T>(&self, ptr: NonNull<T>) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let layout = Layout::new::<T>();
+
+        // Safety: `ptr` is allocated from the ring of the size class of `T`.
+        unsafe {
+            match TypeClass::<T, C>::CLASS {
+                Class::Tiny => {
+                    Self::_deallocate(ptr.cast(), layout, routed(inner.tiny_ring()), inner)
+                }
+                Class::Small => {
+                    Self::_deallocate(ptr.cast(), layout, routed(inner.small_ring()), inner)
+                }
+                Class::Large => {
+                    Self::_deallocate(ptr.cast(), layout, routed(inner.large_ring()), inner)
+                }
+                Class::Oversized => inner.deallocate(ptr.cast(), layout),
+            }
+        }
+    }
+
+    /// Attempts to allocate a block of memory from the ring of given size class.
+    ///
+    /// Unlike [`RingAlloc::allocate`], block is placed into chunks of `class`
+    /// even if layout belongs to smaller size class.
+    /// This allows to keep blocks with different lifetimes apart,
+    /// e.g. long-lived values from churny tiny allocations.
+    ///
+    /// Fails if layout doesn't fit `class` or `class` is disabled in `C`.
+    /// Block must be deallocated with [`RingAlloc::deallocate_in_class`]
+    /// with the same class.
+    #[inline(always)]
+    pub fn allocate_in_class(
+        &self,
+        layout: Layout,
+        class: SizeClass,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let fits = Class::of::<C>(layout);
+
+        match class {
+            SizeClass::Tiny if fits <= Class::Tiny => {
+                Self::_allocate(inner.tiny_ring().ok_or(AllocError)?, layout, inner, false)
+            }
+            SizeClass::Small if fits <= Class::Small => {
+                Self::_allocate(inner.small_ring().ok_or(AllocError)?, layout, inner, false)
+            }
+            SizeClass::Large if fits <= Class::Large => {
+                Self::_allocate(inner.large_ring().ok_or(AllocError)?, layout, inner, false)
+            }
+            _ => Err(AllocError),
+        }
+    }
+
+    /// Deallocates the memory referenced by `ptr`
+    /// allocated from the ring of given size class.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block of memory [*currently allocated*] via
+    /// [`RingAlloc::allocate_in_class`] with `layout` and `class`.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    #[inline(always)]
+    pub unsafe fn deallocate_in_class(&self, ptr: NonNull<u8>, layout: Layout, class: SizeClass) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Safety: `ptr` is allocated from the ring of `class`, so the ring is enabled.
+        unsafe {
+            match class {
+                SizeClass::Tiny => Self::_deallocate(ptr, layout, routed(inner.tiny_ring()), inner),
+                SizeClass::Small => {
+                    Self::_deallocate(ptr, layout, routed(inner.small_ring()), inner)
+                }
+                SizeClass::Large => {
+                    Self::_deallocate(ptr, layout, routed(inner.large_ring()), inner)
+                }
+            }
+        }
+    }
+
+    /// Attempts to allocate a block of memory for `len` values of type `T`.
+    ///
+    /// Returns [`AllocError`] instead of panicking
+    /// if size of the array overflows `isize::MAX`.
+    /// Block must be deallocated with [`RingAlloc::deallocate_slice`]
+    /// or [`RingAlloc::deallocate`] with `Layout::array::<T>(len)`.
+    #[inline(always)]
+    pub fn allocate_slice<T>(&self, len: usize) -> Result<NonNull<[T]>, AllocError> {
+        Ok(self.try_allocate_slice_detailed(len)?)
+    }
+
+    /// Attempts to allocate a block of memory for `len` values of type `T`,
+    /// reporting why allocation failed.
+    ///
+    /// Same as [`RingAlloc::allocate_slice`], but returns
+    /// [`RingAllocError::Overflow`] if size of the array overflows `isize::MAX`.
+    /// See [`RingAlloc::try_allocate_detailed`] for other errors.
+    #[inline(always)]
+    pub fn try_allocate_slice_detailed<T>(
+        &self,
+        len: usize,
+    ) -> Result<NonNull<[T]>, RingAllocError> {
+        let layout = Layout::array::<T>(len).map_err(|_| RingAllocError::Overflow)?;
+        let ptr = self.try_allocate_detailed(layout)?;
+
+        // Safety: `ptr` is not null and allocated to fit `len` values of `T`.
+        Ok(unsafe {
+            NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                ptr.as_ptr().cast::<T>(),
+                len,
+            ))
+        })
+    }
+
+    /// Deallocates the memory for `len` values of type `T` referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block of memory [*currently allocated*] via
+    /// [`RingAlloc::allocate_slice::<T>`] with the same `len`
+    /// or [`RingAlloc::allocate`] with `Layout::array::<T>(len)`.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    #[inline(always)]
+    pub unsafe fn deallocate_slice<T>(&self, ptr: NonNull<T>, len: usize) {
+        // Safety: block was allocated with this layout, so it doesn't overflow.
+        let layout = unsafe { Layout::array::<T>(len).unwrap_unchecked() };
+
+        // Safety: `ptr` is allocated with `layout`.
+        unsafe { self.deallocate(ptr.cast(), layout) }
+    }
+
+    /// Attempts to allocate a block for header of type `H`
+    /// followed by `count` values of type `T`.
+    ///
+    /// Returns pointers to the header and the trailing values
+    /// that live in the same block, e.g. for inline strings
+    /// or dynamic arrays with length stored in the header.
+    /// Returns [`AllocError`] if size of the block overflows `isize::MAX`.
+    /// Block must be deallocated with [`RingAlloc::deallocate_with_trailing`].
+    ///
+    /// ```
+    /// # #[cfg(feature = "alloc")] {
+    /// use ring_alloc::RingAlloc;
+    ///
+    /// let alloc = RingAlloc::new();
+    /// let (header, values) = alloc.allocate_with_trailing::<usize, u8>(5).unwrap();
+    /// unsafe {
+    ///     header.as_ptr().write(5);
+    ///     values.cast::<u8>().as_ptr().write_bytes(1, 5);
+    ///     alloc.deallocate_with_trailing::<usize, u8>(header, 5);
+    /// }
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn allocate_with_trailing<H, T>(
+        &self,
+        count: usize,
+    ) -> Result<(NonNull<H>, NonNull<[T]>), AllocError> {
+        let (layout, offset) = trailing_layout::<H, T>(count).ok_or(AllocError)?;
+        let ptr = self.allocate(layout)?.cast::<u8>();
+
+        // Safety: `ptr` is not null and allocated to fit `count` values of `T` at `offset`.
+        unsafe {
+            let values = ptr.as_ptr().add(offset).cast::<T>();
+            Ok((
+                ptr.cast(),
+                NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(values, count)),
+            ))
+        }
+    }
+
+    /// Deallocates the block with header of type `H`
+    /// followed by `count` values of type `T` referenced by `header`.
+    ///
+    /// # Safety
+    ///
+    /// `header` must denote a block of memory [*currently allocated*] via
+    /// [`RingAlloc::allocate_with_trailing::<H, T>`] with the same `count`.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    #[inline(always)]
+    pub unsafe fn deallocate_with_trailing<H, T>(&self, header: NonNull<H>, count: usize) {
+        // Safety: block was allocated with this layout, so it doesn't overflow.
+        let (layout, _) = unsafe { trailing_layout::<H, T>(count).unwrap_unchecked() };
+
+        // Safety: `header` is allocated with `layout`.
+        unsafe { self.deallocate(header.cast(), layout) }
+    }
+
+    /// Moves values of the iterator into boxes allocated from this ring-allocator.
+    ///
+    /// Size class of `T` is selected once at compile time and boxes
+    /// are allocated back to back, so values are placed close in memory.
+    /// Vector of boxes is allocated from this ring-allocator as well,
+    /// with capacity reserved from iterator's size hint.
+    ///
+    /// Calls [`handle_alloc_error`] on allocation failure.
+    /// See [`RingAlloc::try_boxed_iter`] for fallible version.
+    ///
+    /// ```
+    /// use ring_alloc::RingAlloc;
+    ///
+    /// let alloc = RingAlloc::new();
+    /// let boxes = alloc.boxed_iter(0..10u32);
+    /// assert_eq!(*boxes[3], 3);
+    /// ```
+    ///
+    /// [`handle_alloc_error`]: allocator_api2::alloc::handle_alloc_error
+    #[cfg(feature = "alloc")]
+    #[cfg(not(no_global_oom_handling))]
+    pub fn boxed_iter<T, I>(&self, iter: I) -> Vec<Box<T, Self>, Self>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        match self.try_boxed_iter(iter) {
+            Ok(boxes) => boxes,
+            Err(AllocError) => alloc::alloc::handle_alloc_error(Layout::new::<T>()),
+        }
+    }
+
+    /// Attempts to move values of the iterator into boxes
+    /// allocated from this ring-allocator.
+    ///
+    /// See [`RingAlloc::boxed_iter`].
+    /// On failure values already moved into boxes and the rest
+    /// of the iterator are dropped.
+    #[cfg(feature = "alloc")]
+    pub fn try_boxed_iter<T, I>(&self, iter: I) -> Result<Vec<Box<T, Self>, Self>, AllocError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+
+        let mut boxes = Vec::new_in(self.clone());
+        boxes
+            .try_reserve_exact(iter.size_hint().0)
+            .map_err(|_| AllocError)?;
+
+        for value in iter {
+            // Reserve first, so that push never allocates.
+            if boxes.len() == boxes.capacity() {
+                boxes.try_reserve(1).map_err(|_| AllocError)?;
+            }
+
+            let ptr = self.allocate_for::<T>()?;
+
+            // Safety: `ptr` is allocated for value of type `T`,
+            // it is initialized before box is constructed.
+            let boxed = unsafe {
+                ptr.as_ptr().write(value);
+                Box::from_raw_in(ptr.as_ptr(), self.clone())
+            };
+            boxes.push(boxed);
+        }
+        Ok(boxes)
+    }
+
+    /// Returns boxed slice of `n` clones of `value` allocated from this ring-allocator.
+    ///
+    /// Slice is always allocated as one contiguous block.
+    /// If it doesn't fit into the rest of the head chunk, it is placed into
+    /// the next chunk that has room, and slices larger than the largest size class
+    /// are allocated from the underlying allocator.
+    /// Empty slices and slices of zero-sized values take zero-sized block.
+    ///
+    /// Calls [`handle_alloc_error`] on allocation failure.
+    /// Panics if size of the slice overflows `isize::MAX`.
+    ///
+    /// ```
+    /// use ring_alloc::RingAlloc;
+    ///
+    /// let alloc = RingAlloc::new();
+    /// let slice = alloc.boxed_slice(7u16, 100);
+    /// assert_eq!(slice.len(), 100);
+    /// assert!(slice.iter().all(|v| *v == 7));
+    /// ```
+    ///
+    /// [`handle_alloc_error`]: allocator_api2::alloc::handle_alloc_error
+    #[cfg(feature = "alloc")]
+    #[cfg(not(no_global_oom_handling))]
+    pub fn boxed_slice<T: Clone>(&self, value: T, n: usize) -> Box<[T], Self> {
+        let layout = Layout::array::<T>(n).expect("Boxed slice size overflows `isize::MAX`");
+
+        // Box deallocates its block even if it is empty,
+        // so zero-sized block is allocated instead of using dangling pointer.
+        let ptr = match self.allocate(layout) {
+            Ok(ptr) => ptr.cast::<core::mem::MaybeUninit<T>>(),
+            Err(AllocError) => alloc::alloc::handle_alloc_error(layout),
+        };
+
+        // Safety: block is allocated from this ring-allocator
+        // and fits `n` values of `T`, which may be uninitialized.
+        let mut slots = unsafe {
+            Box::from_raw_in(
+                core::ptr::slice_from_raw_parts_mut(ptr.as_ptr(), n),
+                self.clone(),
+            )
+        };
+
+        if let Some((last, rest)) = slots.split_last_mut() {
+            for slot in rest {
+                slot.write(value.clone());
+            }
+            last.write(value);
+        }
+
+        // Safety: all `n` values are initialized.
+        unsafe { slots.assume_init() }
+    }
+
+    /// Moves value into a block allocated from this ring-allocator
+    /// and returns raw pointer to it.
+    ///
+    /// Returned handle keeps the ring-allocator alive,
+    /// hold it for as long as the pointer is in use,
+    /// e.g. when the pointer is passed through FFI.
+    /// Value is dropped and its block is deallocated with [`RingAlloc::reclaim_raw`].
+    ///
+    /// Calls [`handle_alloc_error`] on allocation failure.
+    ///
+    /// ```
+    /// use ring_alloc::RingAlloc;
+    ///
+    /// let alloc = RingAlloc::new();
+    /// let (ptr, handle) = alloc.leak_raw(42u32);
+    /// drop(alloc);
+    ///
+    /// assert_eq!(unsafe { *ptr.as_ptr() }, 42);
+    /// unsafe { handle.reclaim_raw(ptr) };
+    /// ```
+    ///
+    /// [`handle_alloc_error`]: allocator_api2::alloc::handle_alloc_error
+    #[cfg(feature = "alloc")]
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn leak_raw<T>(&self, value: T) -> (NonNull<T>, Self) {
+        let ptr = match self.allocate_for::<T>() {
+            Ok(ptr) => ptr,
+            Err(AllocError) => alloc::alloc::handle_alloc_error(Layout::new::<T>()),
+        };
+
+        // Safety: `ptr` is allocated for value of type `T`.
+        unsafe { ptr.as_ptr().write(value) };
+        (ptr, self.clone())
+    }
+
+    /// Drops value referenced by `ptr` and deallocates its block.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must be returned by [`RingAlloc::leak_raw`]
+    ///   called on this ring-allocator or any of its clones.
+    /// * It must be reclaimed exactly once
+    ///   and the value must not be used afterwards.
+    #[inline(always)]
+    pub unsafe fn reclaim_raw<T>(&self, ptr: NonNull<T>) {
+        // Safety: `ptr` points to initialized value of type `T`
+        // in a block allocated for it from this ring-allocator.
+        unsafe {
+            core::ptr::drop_in_place(ptr.as_ptr());
+            self.deallocate_for(ptr);
+        }
+    }
+
+    /// Returns scratch buffer that fits `layout`.
+    ///
+    /// Buffer returned by previous call is deallocated before the new one
+    /// is allocated, so memory for working buffers is reused without
+    /// tracking their lifetime.
+    /// Last scratch buffer is deallocated when the ring-allocator is dropped or reset.
+    ///
+    /// Returned pointer is invalidated by the next `scratch` call
+    /// with this ring-allocator or any of its clones.
+    #[inline(always)]
+    pub fn scratch(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        inner.release_scratch();
+        let ptr = inner.allocate(layout)?;
+        inner.scratch.set(Some((ptr.cast(), layout)));
+        Ok(ptr)
+    }
+
+    /// Attempts to allocate a block of memory in the same chunk as `hint`,
+    /// falling back to regular allocation if that chunk has no room.
+    ///
+    /// Keeps related blocks, e.g. nodes of a tree or graph, close in memory.
+    /// `hint` is looked up only among chunks of the size class of `layout`,
+    /// so it should point to a block of similar size.
+    /// Pointers not in such chunks of this allocator are ignored.
+    #[inline(always)]
+    pub fn allocate_near(
+        &self,
+        hint: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let layout = inner.round_layout(layout);
+
+        let ptr = match Class::of::<C>(layout).route::<C>() {
+            Class::Tiny => Self::_allocate_near(routed(inner.tiny_ring()), hint, layout),
+            Class::Small => Self::_allocate_near(routed(inner.small_ring()), hint, layout),
+            Class::Large => Self::_allocate_near(routed(inner.large_ring()), hint, layout),
+            Class::Oversized => None,
+        };
+
+        #[cfg(feature = "debug-validate")]
+        if let Some(ptr) = ptr {
+            inner.validate_allocate(ptr, layout);
+        }
+
+        match ptr {
+            // Safety: `ptr` is allocated to fit `layout.size()` bytes.
+            Some(ptr) => Ok(unsafe {
+                NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                    ptr.as_ptr(),
+                    layout.size(),
+                ))
+            }),
+            None => inner.allocate(layout),
+        }
+    }
+
+    fn _allocate_near<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        hint: NonNull<u8>,
+        layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        let hint = hint.as_ptr() as usize;
+
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+            if c_ref.contains(hint) {
+                return Self::_allocate_from(c_ref, c, layout, false);
+            }
+            chunk = c_ref.next();
+        }
+        None
+    }
+
+    /// Touches every page of unused memory in head chunks of all size classes,
+    /// so that following allocations don't stall on page faults.
+    ///
+    /// Only memory after chunk's cursor is written,
+    /// chunk headers and allocated blocks are left intact.
+    /// Call before entering latency-critical loop.
+    pub fn prefault_head_chunks(&self) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        if let Some(ring) = inner.tiny_ring() {
+            Self::_prefault_head(ring);
+        }
+        if let Some(ring) = inner.small_ring() {
+            Self::_prefault_head(ring);
+        }
+        if let Some(ring) = inner.large_ring() {
+            Self::_prefault_head(ring);
+        }
+    }
+
+    fn _prefault_head<const N: usize>(ring: &Ring<Chunk<N>>) {
+        if let Some(chunk) = ring.head.get() {
+            // Safety: chunks in the ring are always valid.
+            // Local chunks are never shared with other threads.
+            unsafe { chunk.as_ref().prefault() }
+        }
+    }
+
+    /// Attempts to grow block in place without moving it.
+    ///
+    /// Succeeds only if block is the last one allocated from its chunk,
+    /// chunk has enough space after it and new layout belongs
+    /// to the same size class with the same alignment.
+    /// On success the block fits `new` layout and must be deallocated with it.
+    ///
+    /// Unlike [`Allocator::grow`] this never falls back to allocating a new block
+    /// and copying, so collections can try cheap growth first
+    /// and choose their own strategy otherwise.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory [*currently allocated*] via this allocator, and
+    /// * `old` must [*fit*] that block of memory.
+    ///
+    /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
+    /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
+    #[inline(always)]
+    pub unsafe fn try_grow_in_place(&self, ptr: NonNull<u8>, old: Layout, new: Layout) -> bool {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let (old, new) = (inner.round_layout(old), inner.round_layout(new));
+
+        if old.align() != new.align() || old.size() > new.size() {
+            return false;
+        }
+
+        if Class::of::<C>(new).route::<C>() != Class::of::<C>(old).route::<C>() {
+            return false;
+        }
+
+        // Safety: covered by this method's contract.
+        let grown = unsafe { Self::_grow_in_place(ptr, old, new) };
+
+        #[cfg(feature = "debug-validate")]
+        if grown {
+            inner.validate_resize(ptr, new);
+        }
+
+        grown
+    }
+
+    /// Grows block in place within its chunk,
+    /// letting it outgrow size class of the chunk if there's room.
+    ///
+    /// Keeps data of a growing collection in one chunk instead of moving it
+    /// to the ring of the next size class.
+    /// The block then occupies chunk of smaller size class until deallocated,
+    /// which may keep that chunk from being reused a bit longer.
+    ///
+    /// With `aligned-chunks` feature chunk of a block is found by its size class,
+    /// so block may grow only within its size class.
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`RingAlloc::try_grow_in_place`].
+    #[inline(always)]
+    unsafe fn grow_in_chunk(ptr: NonNull<u8>, old: Layout, new: Layout) -> bool {
+        if old.align() != new.align() || old.size() > new.size() {
+            return false;
+        }
+
+        match Class::of::<C>(new).route::<C>() {
+            // Oversized blocks are deallocated directly to underlying allocator.
+            Class::Oversized => return false,
+            class
+                if cfg!(feature = "aligned-chunks")
+                    && class != Class::of::<C>(old).route::<C>() =>
+            {
+                return false
+            }
+            _ => {}
+        }
+
+        // Safety: covered by this method's contract.
+        unsafe { Self::_grow_in_place(ptr, old, new) }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must denote a block currently allocated via this allocator
+    /// and `old` must fit that block.
+    /// `old` and `new` must have the same alignment.
+    #[inline(always)]
+    unsafe fn _grow_in_place(ptr: NonNull<u8>, old: Layout, new: Layout) -> bool {
+        // Safety: `ptr` is allocated via this allocator with `old` layout.
+        let class = unsafe { Class::of_block::<C>(ptr, old) };
+
+        // Safety: `ptr` is allocated from chunk of `class`
+        // and the allocator is not shared with other threads.
+        unsafe {
+            match class {
+                Class::Tiny => Chunk::<{ TINY_ALLOCATION_CHUNK_SIZE }>::grow_in_place(
+                    ptr.as_ptr(),
+                    old,
+                    new.size(),
+                ),
+                Class::Small => Chunk::<{ SMALL_ALLOCATION_CHUNK_SIZE }>::grow_in_place(
+                    ptr.as_ptr(),
+                    old,
+                    new.size(),
+                ),
+                Class::Large => Chunk::<{ LARGE_ALLOCATION_CHUNK_SIZE }>::grow_in_place(
+                    ptr.as_ptr(),
+                    old,
+                    new.size(),
+                ),
+                Class::Oversized => false,
+            }
+        }
+    }
+
+    /// Checks if block of memory with given layout can be allocated
+    /// from the current head chunk of the corresponding ring.
+    ///
+    /// Returns `false` if allocation would require moving to the next chunk
+    /// or allocating new one, wasting the tail of the current chunk.
+    /// Also returns `false` for layouts that are too large for any chunk
+    /// and always served by underlying allocator.
+    #[inline(always)]
+    pub fn available_contiguous(&self, layout: Layout) -> bool {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let layout = inner.round_layout(layout);
+        match Class::of::<C>(layout).route::<C>() {
+            Class::Tiny => Self::_available_contiguous(routed(inner.tiny_ring()), layout),
+            Class::Small => Self::_available_contiguous(routed(inner.small_ring()), layout),
+            Class::Large => Self::_available_contiguous(routed(inner.large_ring()), layout),
+            Class::Oversized => false,
+        }
+    }
+
+    #[inline(always)]
+    fn _available_contiguous<const N: usize>(ring: &Ring<Chunk<N>>, layout: Layout) -> bool {
+        match ring.head.get() {
+            None => false,
+            Some(chunk_ptr) => {
+                // Safety: chunks in the ring are always valid.
+                let chunk = unsafe { chunk_ptr.as_ref() };
+                chunk.fits(layout)
+            }
+        }
+    }
+
+    /// Attempts to allocate a block of memory from the current head chunk
+    /// of the corresponding ring.
+    ///
+    /// Returns `None` if block doesn't fit into the head chunk.
+    /// Unlike [`RingAlloc::allocate`] this method never rotates the ring
+    /// and never calls underlying allocator, making it suitable for
+    /// real-time code. Layouts that are too large for any chunk
+    /// always yield `None`.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    pub fn try_allocate_in_head_only(&self, layout: Layout) -> Option<NonNull<[u8]>> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        let layout = inner.round_layout(layout);
+        let ptr = match Class::of::<C>(layout).route::<C>() {
+            Class::Tiny => Self::_allocate_in_head(routed(inner.tiny_ring()), layout),
+            Class::Small => Self::_allocate_in_head(routed(inner.small_ring()), layout),
+            Class::Large => Self::_allocate_in_head(routed(inner.large_ring()), layout),
+            Class::Oversized => None,
+        }?;
+
+        #[cfg(feature = "debug-validate")]
+        inner.validate_allocate(ptr.cast(), layout);
+
+        Some(ptr)
+    }
+
+    #[inline(always)]
+    fn _allocate_in_head<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        layout: Layout,
+    ) -> Option<NonNull<[u8]>> {
+        let chunk_ptr = ring.head.get()?;
+
+        // Safety: chunks in the ring are always valid.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+        let ptr = chunk.allocate(chunk_ptr, layout)?;
+        chunk.blocks.set(chunk.blocks.get() + 1);
+
+        #[cfg(all(
+            feature = "debug-introspect",
+            debug_assertions,
+            not(feature = "aligned-chunks")
+        ))]
+        // Safety: `ptr` is just allocated from the chunk with `layout`.
+        unsafe {
+            chunk.list_live(ptr.as_ptr(), layout)
+        };
+
+        // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+        // ptr is allocated to fit `layout.size()` bytes.
+        Some(unsafe {
+            NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                ptr.as_ptr(),
+                layout.size(),
+            ))
+        })
+    }
+
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn _allocate_from<const N: usize>(
+        chunk: &Chunk<N>,
+        chunk_ptr: NonNull<Chunk<N>>,
+        layout: Layout,
+        zeroed: bool,
+    ) -> Option<NonNull<u8>> {
+        let ptr = if zeroed {
+            chunk.allocate_zeroed(chunk_ptr, layout)
+        } else {
+            chunk.allocate(chunk_ptr, layout)
+        }?;
+        chunk.blocks.set(chunk.blocks.get() + 1);
+
+        #[cfg(all(
+            feature = "debug-introspect",
+            debug_assertions,
+            not(feature = "aligned-chunks")
+        ))]
+        // Safety: `ptr` is just allocated from the chunk with `layout`.
+        unsafe {
+            chunk.list_live(ptr.as_ptr(), layout)
+        };
+
+        Some(ptr)
+    }
+
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn _allocate<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        layout: Layout,
+        inner: &Rings<A, C>,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let layout = inner.round_layout(layout);
+        let ptr = Self::_allocate_in_ring(ring, layout, inner, zeroed)?;
+
+        #[cfg(feature = "debug-validate")]
+        inner.validate_allocate(ptr.cast(), layout);
+
+        Ok(ptr)
+    }
+
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn _allocate_in_ring<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        layout: Layout,
+        inner: &Rings<A, C>,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Try head chunk.
+        if let Some(chunk_ptr) = ring.head.get() {
+            // Safety: `chunk` is valid pointer to `Chunk` allocated by `self.allocator`.
+            let chunk = unsafe { chunk_ptr.as_ref() };
+
+            let ptr = Self::_allocate_from(chunk, chunk_ptr, layout, zeroed);
+
+            #[cfg(feature = "front-reuse")]
+            let ptr = match ptr {
+                None if inner.mode == Mode::Ring => {
+                    Self::_reclaim_and_allocate(chunk, chunk_ptr, layout, zeroed)
+                }
+                ptr => ptr,
+            };
+
+            match ptr {
+                Some(ptr) => {
+                    // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+                    // ptr is allocated to fit `layout.size()` bytes.
+                    return Ok(unsafe {
+                        NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                            ptr.as_ptr(),
+                            layout.size(),
+                        ))
+                    });
+                }
+                // Chunk is full. In LIFO mode chunks are not rotated.
+                // Swap with next chunk if it's unused, otherwise allocate new chunk.
+                None if inner.mode == Mode::Lifo => {
+                    if let Some(next_ptr) = chunk.next() {
+                        // Safety: chunks in the ring are always valid.
+                        let next = unsafe { next_ptr.as_ref() };
+
+                        if next.reset() {
+                            chunk.next.set(next.next());
+                            if let Some(after_ptr) = next.next() {
+                                // Safety: chunks in the ring are always valid.
+                                unsafe { after_ptr.as_ref().prev.set(Some(chunk_ptr)) };
+                            }
+                            chunk.prev.set(Some(next_ptr));
+                            next.next.set(Some(chunk_ptr));
+                            next.prev.set(None);
+                            ring.head.set(Some(next_ptr));
+                            if ring.tail.get() == Some(next_ptr) {
+                                ring.tail.set(Some(chunk_ptr));
+                            }
+
+                            if let Some(ptr) = Self::_allocate_from(next, next_ptr, layout, zeroed)
+                            {
+                                // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+                                // ptr is allocated to fit `layout.size()` bytes.
+                                return Ok(unsafe {
+                                    NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                                        ptr.as_ptr(),
+                                        layout.size(),
+                                    ))
+                                });
+                            }
+                        }
+                    }
+                }
+                // Chunk is full. Try next one.
+                None => match chunk.next.take() {
+                    None => {
+                        debug_assert_eq!(ring.tail.get(), ring.head.get());
+                    }
+                    Some(next_ptr) => {
+                        // Move head to tail and bring next one as head.
+
+                        // Safety: tail is valid pointer to `Chunk` allocated by `self.allocator`.
+                        let tail_chunk = unsafe { ring.tail.get().unwrap().as_ref() };
+                        debug_assert_eq!(tail_chunk.next(), None);
+                        tail_chunk.next.set(Some(chunk_ptr));
+                        chunk.prev.set(ring.tail.get());
+                        ring.tail.set(Some(chunk_ptr));
+                        ring.head.set(Some(next_ptr));
+
+                        #[cfg(feature = "metrics")]
+                        ring.rotations.set(ring.rotations.get() + 1);
+
+                        let next = unsafe { next_ptr.as_ref() };
+                        next.prev.set(None);
+
+                        if next.reset() {
+                            if let Some(ptr) = Self::_allocate_from(next, next_ptr, layout, zeroed)
+                            {
+                                // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+                                // ptr is allocated to fit `layout.size()` bytes.
+                                return Ok(unsafe {
+                                    NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                                        ptr.as_ptr(),
+                                        layout.size(),
+                                    ))
+                                });
+                            }
+                        }
+
+                        #[cfg(feature = "front-reuse")]
+                        if let Some(ptr) =
+                            Self::_reclaim_and_allocate(next, next_ptr, layout, zeroed)
+                        {
+                            // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+                            // ptr is allocated to fit `layout.size()` bytes.
+                            return Ok(unsafe {
+                                NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                                    ptr.as_ptr(),
+                                    layout.size(),
+                                ))
+                            });
+                        }
+
+                        // Not ready yet. Allocate new chunk.
+                    }
+                },
+            }
+        } else {
+            debug_assert_eq!(ring.tail.get(), None);
+        }
+
+        let chunk_ptr = match inner.new_chunk(ring, zeroed) {
+            Ok(chunk_ptr) => chunk_ptr,
+            Err(AllocError) => inner.new_chunk_after_clean(ring, zeroed)?,
+        };
+
+        // Safety: `chunk` is valid pointer to `Chunk` allocated by `self.allocator`.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        let block = Self::_allocate_from(chunk, chunk_ptr, layout, zeroed);
+
+        // Safety: `chunk_ptr` is allocated by `inner.allocator` and not linked.
+        let ptr = unsafe { Chunk::fresh_block(chunk_ptr, block, &*inner.allocator)? };
+
+        // Put to head.
+        chunk.next.set(ring.head.get());
+
+        // If first chunk, put to tail.
+        match ring.head.get() {
+            None => {
+                debug_assert_eq!(ring.tail.get(), None);
+
+                // Modify after asserts.
+                ring.tail.set(Some(chunk_ptr));
+            }
+            Some(head_ptr) => {
+                debug_assert!(ring.tail.get().is_some());
+
+                // Safety: chunks in the ring are always valid.
+                unsafe { head_ptr.as_ref().prev.set(Some(chunk_ptr)) };
+            }
+        }
+
+        // Modify after asserts.
+        ring.head.set(Some(chunk_ptr));
+
+        // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
+        // ptr is allocated to fit `layout.size()` bytes.
+        Ok(unsafe {
+            NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
+                ptr.as_ptr(),
+                layout.size(),
+            ))
+        })
+    }
+
+    /// Reclaims memory of deallocated blocks in the chunk
+    /// until block with `layout` fits.
+    #[cfg(feature = "front-reuse")]
+    #[cold]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn _reclaim_and_allocate<const N: usize>(
+        chunk: &Chunk<N>,
+        chunk_ptr: NonNull<Chunk<N>>,
+        layout: Layout,
+        zeroed: bool,
+    ) -> Option<NonNull<u8>> {
+        while chunk.reclaim() {
+            if let Some(ptr) = Self::_allocate_from(chunk, chunk_ptr, layout, zeroed) {
+                return Some(ptr);
+            }
+        }
+        None
+    }
+
+    #[inline(always)]
+    unsafe fn _deallocate<const N: usize>(
+        ptr: NonNull<u8>,
+        layout: Layout,
+        ring: &Ring<Chunk<N>>,
+        inner: &Rings<A, C>,
+    ) {
+        let layout = inner.round_layout(layout);
+
+        #[cfg(feature = "debug-validate")]
+        inner.validate_deallocate(ptr);
+
+        // Memory is reclaimed only by reset.
+        if inner.mode == Mode::Bump {
+            return;
+        }
+
+        // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
+        let chunk_ptr = unsafe { Chunk::<N>::chunk_of(ptr.as_ptr(), layout) };
+
+        // Safety: chunk is alive since `ptr` is alive.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+        chunk.blocks.set(chunk.blocks.get() - 1);
+
+        #[cfg(all(
+            feature = "debug-introspect",
+            debug_assertions,
+            not(feature = "aligned-chunks")
+        ))]
+        // Safety: `ptr` is allocated from the chunk with `layout`.
+        unsafe {
+            chunk.unlist_live(ptr.as_ptr(), layout)
+        };
+
+        // Safety: `ptr` is allocated from the chunk with `layout`.
+        unsafe { chunk.release_committed(ptr.as_ptr(), layout) };
+
+        // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
+        unsafe {
+            match inner.mode {
+                Mode::Ring => Chunk::<N>::deallocate(ptr.as_ptr(), layout),
+                Mode::Lifo => Chunk::<N>::deallocate_lifo(ptr.as_ptr(), layout),
+                Mode::Bump => unreachable!(),
+            }
+        }
+
+        if inner.eager_reclaim && ring.head.get() != Some(chunk_ptr) && !inner.is_home(chunk_ptr) {
+            // Safety: chunk is alive until it is unlinked and freed.
+            if unsafe { chunk_ptr.as_ref().unused() } {
+                Self::unlink(ring, chunk_ptr);
+
+                // Safety: `chunk_ptr` is valid pointer to `Chunk` allocated by `inner.allocator`
+                // and it is not linked into the ring anymore.
+                unsafe { Chunk::free(chunk_ptr, &*inner.allocator) };
+            }
+        }
+    }
+
+    /// Removes chunk from the ring.
+    #[inline(always)]
+    fn unlink<const N: usize>(ring: &Ring<Chunk<N>>, chunk_ptr: NonNull<Chunk<N>>) {
+        // Safety: chunks in the ring are always valid.
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        let prev = chunk.prev.take();
+        let next = chunk.next.take();
+
+        match prev {
+            // Safety: chunks in the ring are always valid.
+            Some(prev_ptr) => unsafe { prev_ptr.as_ref().next.set(next) },
+            None => ring.head.set(next),
+        }
+
+        match next {
+            // Safety: chunks in the ring are always valid.
+            Some(next_ptr) => unsafe { next_ptr.as_ref().prev.set(prev) },
+            None => ring.tail.set(prev),
+        }
+    }
+
+    /// Frees the chunk that block denoted by `ptr` was allocated from,
+    /// if that chunk is unused.
+    ///
+    /// Chunk is found from metadata embedded next to the block,
+    /// unlinked from its ring and returned to the underlying allocator
+    /// right away, without waiting for [`RingAlloc::flush`].
+    ///
+    /// Returns `true` if chunk was freed.
+    /// Returns `false` if chunk still has allocated blocks,
+    /// is a home chunk or `layout` is too large for any chunk.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a block allocated from this ring-allocator with `layout`.
+    /// Block may be already deallocated, but then no other method
+    /// of this ring-allocator or any of its clones may be called
+    /// between deallocation of the block and this call,
+    /// as otherwise the chunk may be already freed or its memory reused.
+    pub unsafe fn reclaim_chunk(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Safety: chunk of the block is alive and block metadata is intact.
+        match unsafe { Class::of_block::<C>(ptr, layout) } {
+            Class::Tiny => unsafe {
+                Self::_reclaim_chunk(ptr, layout, routed(inner.tiny_ring()), inner)
+            },
+            Class::Small => unsafe {
+                Self::_reclaim_chunk(ptr, layout, routed(inner.small_ring()), inner)
+            },
+            Class::Large => unsafe {
+                Self::_reclaim_chunk(ptr, layout, routed(inner.large_ring()), inner)
+            },
+            Class::Oversized => false,
+        }
+    }
+
+    unsafe fn _reclaim_chunk<const N: usize>(
+        ptr: NonNull<u8>,
+        layout: Layout,
+        ring: &Ring<Chunk<N>>,
+        inner: &Rings<A, C>,
+    ) -> bool {
+        // Safety: `ptr` was allocated from alive `Chunk` and its metadata is intact.
+        let chunk_ptr = unsafe { Chunk::<N>::chunk_of(ptr.as_ptr(), layout) };
+
+        if inner.is_home(chunk_ptr) {
+            return false;
+        }
+
+        // Safety: chunk is alive until it is unlinked and freed.
+        if !unsafe { chunk_ptr.as_ref().unused() } {
+            return false;
+        }
+
+        Self::unlink(ring, chunk_ptr);
+
+        // Safety: `chunk_ptr` is valid pointer to `Chunk` allocated by `inner.allocator`
+        // and it is not linked into the ring anymore.
+        unsafe { Chunk::free(chunk_ptr, &*inner.allocator) };
+        true
+    }
+
+    /// Allocates `n` chunks for the size class that `layout_hint` routes to
+    /// and links them into the ring right after the head chunk.
+    ///
+    /// Up to `n` following chunk rotations are served by reserved chunks
+    /// without calling underlying allocator.
+    /// Useful to pay the cost of chunk allocation upfront before a burst of allocations.
+    ///
+    /// Does nothing for layouts that are too large for any chunk.
+    pub fn reserve_chunks(&self, layout_hint: Layout, n: usize) -> Result<(), AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        match Class::of::<C>(layout_hint).route::<C>() {
+            Class::Tiny => Self::_reserve_chunks(routed(inner.tiny_ring()), n, inner),
+            Class::Small => Self::_reserve_chunks(routed(inner.small_ring()), n, inner),
+            Class::Large => Self::_reserve_chunks(routed(inner.large_ring()), n, inner),
+            Class::Oversized => Ok(()),
+        }
+    }
+
+    fn _reserve_chunks<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        n: usize,
+        inner: &Rings<A, C>,
+    ) -> Result<(), AllocError> {
+        for _ in 0..n {
+            let chunk_ptr = inner.new_chunk(ring, false)?;
+
+            // Safety: `chunk` is valid pointer to `Chunk` allocated by `inner.allocator`.
+            let chunk = unsafe { chunk_ptr.as_ref() };
+
+            match ring.head.get() {
+                None => {
+                    debug_assert_eq!(ring.tail.get(), None);
+                    ring.head.set(Some(chunk_ptr));
+                    ring.tail.set(Some(chunk_ptr));
+                }
+                Some(head_ptr) => {
+                    // Safety: chunks in the ring are always valid.
+                    let head = unsafe { head_ptr.as_ref() };
+
+                    // Put right after head, so it is next to be used.
+                    chunk.next.set(head.next());
+                    chunk.prev.set(Some(head_ptr));
+                    if let Some(next_ptr) = head.next() {
+                        // Safety: chunks in the ring are always valid.
+                        unsafe { next_ptr.as_ref().prev.set(Some(chunk_ptr)) };
+                    }
+                    head.next.set(Some(chunk_ptr));
+
+                    if ring.tail.get() == Some(head_ptr) {
+                        ring.tail.set(Some(chunk_ptr));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables or disables merging of frees of unused chunks.
+    ///
+    /// When enabled, runs of unused chunks with adjacent memory
+    /// are returned to the underlying allocator with single `deallocate` call
+    /// with layout spanning all chunks in the run.
+    /// This is cheaper for allocators that hand out contiguous chunks,
+    /// e.g. bump or arena allocators.
+    ///
+    /// Disabled by default.
+    ///
+    /// # Safety
+    ///
+    /// When enabling, underlying allocator must accept deallocation
+    /// of memory region composed of adjacent blocks it allocated separately,
+    /// as if it was allocated as single block.
+    #[inline(always)]
+    pub unsafe fn set_merge_frees(&self, enabled: bool) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.merge_frees.set(enabled);
+    }
+
+    /// Enables or disables strict mode.
+    ///
+    /// By default allocations larger than 65536 bytes
+    /// are forwarded to the underlying allocator.
+    /// In strict mode such allocations fail with [`AllocError`] instead,
+    /// so all memory handed out by this allocator comes from the rings
+    /// and is reclaimed by [`RingAlloc::reset`].
+    ///
+    /// Oversized blocks allocated before strict mode was enabled
+    /// can still be deallocated.
+    ///
+    /// Disabled by default.
+    #[inline(always)]
+    pub fn set_strict(&self, enabled: bool) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.strict.set(enabled);
+    }
+
+    /// Sets overflow allocator of the size class.
+    ///
+    /// When the underlying allocator fails to allocate new chunk for the size class,
+    /// the chunk is allocated from overflow allocator instead of failing the allocation.
+    /// E.g. rings backed by a small fixed pool may overflow into slower but larger memory.
+    /// Chunks are returned to the allocator they were allocated from.
+    ///
+    /// Replacing or removing overflow allocator doesn't affect chunks
+    /// already allocated from it.
+    /// Has no effect for size classes disabled in `C`.
+    ///
+    /// No overflow allocator is set by default.
+    #[inline(always)]
+    pub fn set_overflow_allocator(
+        &self,
+        class: SizeClass,
+        allocator: Option<&'static dyn Allocator>,
+    ) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        match class {
+            SizeClass::Tiny => inner.tiny_ring().map(|r| r.overflow.set(allocator)),
+            SizeClass::Small => inner.small_ring().map(|r| r.overflow.set(allocator)),
+            SizeClass::Large => inner.large_ring().map(|r| r.overflow.set(allocator)),
+        };
+    }
+
+    /// Sets growth factor of chunk sizes.
+    ///
+    /// Each new chunk allocated for a size class is `factor` times
+    /// larger than the previous one, up to 16 times the default chunk size.
+    /// First chunks stay small for small workloads,
+    /// while large workloads rotate through fewer chunks.
+    /// Already allocated chunks keep their size.
+    ///
+    /// Factor of `1` keeps all chunks of the default size and is the default.
+    /// Has no effect with `aligned-chunks` feature,
+    /// since chunks must have the same size to be found by block address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is zero.
+    #[inline(always)]
+    pub fn set_growth_factor(&self, factor: usize) {
+        assert!(factor > 0, "Growth factor must be positive");
+
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.growth_factor.set(factor);
+    }
+
+    /// Checks that all rings are consistent and returns total number of chunks.
+    #[cfg(all(test, feature = "alloc"))]
+    pub(crate) fn check_rings(&self) -> usize {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        let tiny = inner.tiny_ring();
+        let small = inner.small_ring();
+        let large = inner.large_ring();
+
+        tiny.map_or(0, |r| check_links(r.head.get(), r.tail.get()))
+            + small.map_or(0, |r| check_links(r.head.get(), r.tail.get()))
+            + large.map_or(0, |r| check_links(r.head.get(), r.tail.get()))
+    }
+
+    /// Returns number of times head chunk was rotated to the tail
+    /// in tiny, small and large rings.
+    ///
+    /// High count relative to the number of allocations
+    /// means that chunks are too small for the workload.
+    /// Disabled size classes always report zero.
+    #[cfg(feature = "metrics")]
+    pub fn rotation_counts(&self) -> [u64; 3] {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        [
+            inner.tiny_ring().map_or(0, |r| r.rotations.get()),
+            inner.small_ring().map_or(0, |r| r.rotations.get()),
+            inner.large_ring().map_or(0, |r| r.rotations.get()),
+        ]
+    }
+
+    /// Returns number of live handles that share this ring-allocator.
+    ///
+    /// Rings and chunks are freed when last handle is dropped.
+    /// Useful to find stray clones that keep the allocator alive.
+    #[inline(always)]
+    pub fn handle_count(&self) -> usize {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.ref_cnt.get()
+    }
+
+    /// Returns [`RingAllocHandle`] borrowing this ring-allocator.
+    ///
+    /// Blocks allocated via the handle are the same as blocks allocated
+    /// via this [`RingAlloc`] and may be deallocated through either.
+    #[inline(always)]
+    pub fn handle(&self) -> RingAllocHandle<'_, A, C> {
+        RingAllocHandle {
+            inner: self.inner,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates non-owning [`WeakRingAlloc`] handle to this ring-allocator.
+    ///
+    /// Weak handles are not counted by [`RingAlloc::handle_count`]
+    /// and do not keep chunks alive.
+    #[inline(always)]
+    pub fn downgrade(&self) -> WeakRingAlloc<A, C> {
+        Rings::inc_weak(self.inner);
+        WeakRingAlloc { inner: self.inner }
+    }
+
+    /// Freezes this ring-allocator, so that memory of its blocks
+    /// can be read from multiple threads.
+    ///
+    /// Frozen ring-allocator keeps all chunks alive, but can't allocate
+    /// or deallocate blocks until it is unfrozen with [`FrozenRing::unfreeze`].
+    ///
+    /// Returns this handle back if there are other handles to the ring-allocator,
+    /// including weak ones, as they could allocate concurrently.
+    #[inline]
+    pub fn freeze(self) -> Result<FrozenRing<A, C>, Self> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        if inner.ref_cnt.get() != 1 || inner.weak_cnt.get() != 1 {
+            return Err(self);
+        }
+        let me = ManuallyDrop::new(self);
+        Ok(FrozenRing { inner: me.inner })
+    }
+
+    /// Writes human-readable description of all rings and chunks into `out`.
+    ///
+    /// For every chunk it reports chunk address, chunk size,
+    /// number of bytes consumed since last reset, number of bytes
+    /// occupied by blocks that are not deallocated yet and link to the next chunk.
+    /// Head and tail chunks of each ring are marked.
+    ///
+    /// Intended for debugging dumps and logs.
+    pub fn dump_state<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        writeln!(
+            out,
+            "RingAlloc {:p} ({} handles)",
+            self.inner,
+            inner.ref_cnt.get()
+        )?;
+        if let Some(ring) = inner.tiny_ring() {
+            Self::dump_ring("tiny", ring, out)?;
+        }
+        if let Some(ring) = inner.small_ring() {
+            Self::dump_ring("small", ring, out)?;
+        }
+        if let Some(ring) = inner.large_ring() {
+            Self::dump_ring("large", ring, out)?;
+        }
+        Ok(())
+    }
+
+    fn dump_ring<const N: usize, W: fmt::Write>(
+        name: &str,
+        ring: &Ring<Chunk<N>>,
+        out: &mut W,
+    ) -> fmt::Result {
+        writeln!(out, "  {name} ring:")?;
+
+        let mut chunk = ring.head.get();
+        if chunk.is_none() {
+            writeln!(out, "    empty")?;
+        }
+
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            write!(
+                out,
+                "    chunk {:p}: size {}, used {}, live {}",
+                c,
+                c_ref.capacity.get(),
+                c_ref.used(),
+                c_ref.live()
+            )?;
+            match c_ref.next() {
+                Some(next) => write!(out, ", next {:p}", next)?,
+                None => write!(out, ", next none")?,
+            }
+            if ring.head.get() == Some(c) {
+                write!(out, " [head]")?;
+            }
+            if ring.tail.get() == Some(c) {
+                write!(out, " [tail]")?;
+            }
+            writeln!(out)?;
+
+            chunk = c_ref.next();
+        }
+
+        Ok(())
+    }
+
+    /// Returns breakdown of memory used by this ring-allocator.
+    ///
+    /// Computed in one walk over the rings.
+    pub fn memory_report(&self) -> MemoryReport {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        let mut report = MemoryReport::default();
+        if let Some(ring) = inner.tiny_ring() {
+            report.tiny_chunks = Self::report_ring(ring, &mut report);
+        }
+        if let Some(ring) = inner.small_ring() {
+            report.small_chunks = Self::report_ring(ring, &mut report);
+        }
+        if let Some(ring) = inner.large_ring() {
+            report.large_chunks = Self::report_ring(ring, &mut report);
+        }
+        report
+    }
+
+    /// Returns fraction of reserved memory not occupied by live blocks,
+    /// from `0.0` to `1.0`.
+    ///
+    /// See [`MemoryReport::fragmentation`].
+    pub fn fragmentation(&self) -> f64 {
+        self.memory_report().fragmentation()
+    }
+
+    /// Adds chunks of the ring to the report and returns their number.
+    fn report_ring<const N: usize>(ring: &Ring<Chunk<N>>, report: &mut MemoryReport) -> usize {
+        let mut chunks = 0;
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            chunks += 1;
+            report.reserved += c_ref.capacity.get();
+            report.live += c_ref.live();
+            report.live_blocks += c_ref.blocks.get();
+            report.overhead += (c_ref.base - c.as_ptr() as usize)
+                + c_ref.blocks.get() * Chunk::<N>::META_SIZE
+                + c_ref.padding.get();
+
+            chunk = c_ref.next();
+        }
+        chunks
+    }
+
+    /// Returns chunks that contain blocks that are not deallocated yet.
+    ///
+    /// Unlike aggregate statistics this pinpoints which chunks hold
+    /// stray blocks, which helps to find allocations that are never freed.
+    /// Chunks are reported in ring order, starting with tiny ring.
+    #[cfg(feature = "debug-introspect")]
+    pub fn live_summary(&self) -> alloc::vec::Vec<ChunkLiveInfo> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        let mut summary = alloc::vec::Vec::new();
+        if let Some(ring) = inner.tiny_ring() {
+            Self::live_chunks(ring, SizeClass::Tiny, &mut summary);
+        }
+        if let Some(ring) = inner.small_ring() {
+            Self::live_chunks(ring, SizeClass::Small, &mut summary);
+        }
+        if let Some(ring) = inner.large_ring() {
+            Self::live_chunks(ring, SizeClass::Large, &mut summary);
+        }
+        summary
+    }
+
+    #[cfg(feature = "debug-introspect")]
+    fn live_chunks<const N: usize>(
+        ring: &Ring<Chunk<N>>,
+        size_class: SizeClass,
+        summary: &mut alloc::vec::Vec<ChunkLiveInfo>,
+    ) {
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c_ref = unsafe { c.as_ref() };
+
+            let live_bytes = c_ref.live();
+            if live_bytes > 0 {
+                #[cfg(debug_assertions)]
+                #[allow(unused_mut)]
+                let mut locations = alloc::vec::Vec::new();
+
+                #[cfg(all(debug_assertions, not(feature = "aligned-chunks")))]
+                c_ref.live_locations(|location| locations.push(location));
+
+                summary.push(ChunkLiveInfo {
+                    chunk_addr: c.as_ptr() as usize,
+                    chunk_size: c_ref.capacity.get(),
+                    size_class,
+                    live_bytes,
+                    #[cfg(debug_assertions)]
+                    locations,
+                });
+            }
+
+            chunk = c_ref.next();
+        }
+    }
+
+    /// Deallocates all blocks allocated from this ring-allocator at once.
+    ///
+    /// Every chunk is rewound to its beginning and kept for reuse.
+    /// Destructors of values in the blocks are not run.
+    ///
+    /// Blocks committed with [`RingAlloc::commit`] are not deallocated,
+    /// chunks that hold them are rewound only to the commit point.
+    ///
+    /// # Safety
+    ///
+    /// Blocks allocated from this ring-allocator, including via its clones,
+    /// must not be used or deallocated afterwards, unless they are committed.
+    ///
+    /// With `debug-validate` feature blocks record generation of their chunk,
+    /// so deallocating or growing a block allocated before reset panics,
+    /// unless `aligned-chunks` feature removes block metadata
+    /// or the chunk holds committed blocks.
+    #[inline(always)]
+    pub unsafe fn reset(&self) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Oversized scratch block would not be reclaimed by reset.
+        inner.release_scratch();
+
+        // Safety: guaranteed by caller.
+        unsafe { inner.reset_all() }
+    }
+
+    /// Makes blocks allocated so far survive [`RingAlloc::reset`].
+    ///
+    /// Reset rewinds chunks only to their positions at last commit,
+    /// so memory allocated after commit is reused while committed blocks stay intact.
+    /// This turns single ring-allocator into two-tier arena,
+    /// e.g. persistent syntax tree allocated before commit
+    /// and scratch data allocated after it.
+    ///
+    /// Committed blocks can still be deallocated as usual.
+    /// Chunk forgets its commit point once all its blocks are deallocated.
+    #[inline]
+    pub fn commit(&self) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Scratch block is not meant to outlive reset.
+        inner.release_scratch();
+        inner.commit_all();
+    }
+
+    /// Free all unused chunks back to underlying allocator.
+    ///
+    /// This also happens automatically, once, when underlying allocator
+    /// fails to allocate new chunk, before the allocation is retried.
+    pub fn flush(&self) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+        inner.clean_all();
+    }
+
+    /// Returns `true` if no block allocated from chunks is live.
+    ///
+    /// Oversized blocks served by underlying allocator directly are not tracked.
+    pub fn is_empty(&self) -> bool {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        inner.tiny_ring().is_none_or(Self::_ring_unused)
+            && inner.small_ring().is_none_or(Self::_ring_unused)
+            && inner.large_ring().is_none_or(Self::_ring_unused)
+    }
+
+    fn _ring_unused<const N: usize>(ring: &Ring<Chunk<N>>) -> bool {
+        let mut chunk = ring.head.get();
+        while let Some(c) = chunk {
+            // Safety: chunks in the ring are always valid.
+            let c = unsafe { c.as_ref() };
+            if !c.unused() {
+                return false;
+            }
+            chunk = c.next();
+        }
+        true
+    }
+
+    /// Frees all chunks through current underlying allocator
+    /// and replaces it with `new`.
+    ///
+    /// Allows phased applications to move a long-lived ring-allocator
+    /// to another backing allocator, e.g. from temporary bump allocator
+    /// to persistent pool, once all its blocks are deallocated.
+    ///
+    /// The rings themselves stay in memory allocated by current allocator
+    /// and are deallocated through `new` when last handle is dropped.
+    /// With [`RingAlloc::new_embedded_in`] the same applies to the chunk
+    /// that holds the rings, which is kept.
+    ///
+    /// # Safety
+    ///
+    /// * No block allocated via this ring-allocator may be live,
+    ///   including oversized blocks that [`RingAlloc::is_empty`] doesn't track.
+    /// * `new` must be able to deallocate memory allocated by current allocator.
+    pub unsafe fn replace_backing(&self, new: A) {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        unsafe { self.inner.as_ref() }.release_scratch();
 
-        while let Some(c) = chunk.get() {
-            if unsafe { c.as_ref().unused() } {
-                // Safety: chunks in the ring are always valid.
-                chunk.set(unsafe { c.as_ref().next() });
+        debug_assert!(
+            self.is_empty(),
+            "Backing allocator is replaced with live blocks"
+        );
 
-                // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
-                unsafe {
-                    Chunk::free(c, allocator);
-                }
-            } else {
-                // Safety: chunks in the ring are always valid.
-                chunk = unsafe { &c.as_ref().next };
+        {
+            // Safety: `self.inner` is valid pointer to `Rings`
+            let inner = unsafe { self.inner.as_ref() };
+            inner.free_all();
+
+            // Home chunk is the only one left.
+            if let Some(home) = inner.home {
+                // Safety: home chunk is alive while the rings are.
+                let chunk = unsafe { home.as_ref() };
+                chunk.next.set(None);
+                chunk.prev.set(None);
+                // Only rings with all classes enabled are embedded.
+                let ring = routed(inner.small_ring());
+                ring.head.set(Some(home));
+                ring.tail.set(Some(home));
             }
         }
 
-        if ring.head.get().is_none() {
-            ring.tail.set(None);
+        // Safety: the only chunk allocated by the old allocator left in the rings
+        // is the home chunk, which `new` can deallocate by the contract.
+        // The allocator is not borrowed.
+        unsafe {
+            let allocator = core::ptr::addr_of_mut!((*self.inner.as_ptr()).allocator);
+            ManuallyDrop::drop(&mut *allocator);
+            allocator.write(ManuallyDrop::new(new));
         }
     }
 
-    fn free_all(&self) {
-        Self::free_chunks(&self.tiny_ring, &self.allocator);
-        Self::free_chunks(&self.small_ring, &self.allocator);
-        Self::free_chunks(&self.large_ring, &self.allocator);
+    /// Returns unused tails of head chunks back to underlying allocator.
+    ///
+    /// After a burst that used only a fraction of a freshly allocated chunk
+    /// the rest of it is wasted until the chunk is filled.
+    /// This method shrinks head chunks to the memory used so far,
+    /// rounded up to one eighth of the chunk size.
+    /// Trimmed chunks keep their reduced size until they are freed.
+    ///
+    /// Returns total number of bytes returned to underlying allocator.
+    ///
+    /// # Safety
+    ///
+    /// Underlying allocator must shrink blocks in place,
+    /// i.e. [`Allocator::shrink`] must return the same pointer it was given.
+    pub unsafe fn trim_head_chunks(&self) -> usize {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        let allocator = &*inner.allocator;
+        let mut trimmed = 0;
+
+        // Safety: guaranteed by caller.
+        unsafe {
+            if let Some(ring) = inner.tiny_ring() {
+                trimmed += Self::_trim_head(ring, allocator);
+            }
+            if let Some(ring) = inner.small_ring() {
+                trimmed += Self::_trim_head(ring, allocator);
+            }
+            if let Some(ring) = inner.large_ring() {
+                trimmed += Self::_trim_head(ring, allocator);
+            }
+        }
+        trimmed
+    }
+
+    unsafe fn _trim_head<const N: usize>(ring: &Ring<Chunk<N>>, allocator: &A) -> usize {
+        match ring.head.get() {
+            // Safety: chunks in the ring are allocated by `allocator`.
+            // `allocator` shrinks in place as guaranteed by caller.
+            Some(head) => unsafe { Chunk::trim(head, allocator) },
+            None => 0,
+        }
     }
+}
 
+unsafe impl<A, C> Allocator for RingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
     #[inline(always)]
-    fn free_chunks<const N: usize>(ring: &Ring<Chunk<N>>, allocator: &A) {
-        let mut chunk = ring.head.take();
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate(layout)
+    }
 
-        while let Some(c) = chunk {
-            // Safety: chunks in the ring are always valid.
-            chunk = unsafe { c.as_ref().next() };
-            // Safety: `c` is valid pointer to `Chunk` allocated by `allocator`.
-            unsafe {
-                Chunk::free(c, allocator);
-            }
+    #[inline(always)]
+    #[cfg_attr(all(feature = "debug-introspect", debug_assertions), track_caller)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_zeroed(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: covered by `Allocator::deallocate` contract.
+        unsafe { self.deallocate(ptr, layout) }
+    }
+
+    /// Grows block in place if its chunk has room after it,
+    /// even beyond the size class of the chunk.
+    /// Otherwise allocates new block and moves data there.
+    #[inline(always)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: `self.inner` is valid pointer to `Rings`
+        let inner = unsafe { self.inner.as_ref() };
+
+        // Safety: covered by `Allocator::grow` contract.
+        unsafe { inner.grow(ptr, old_layout, new_layout) }
+    }
+}
+
+/// [`RingAlloc`] that allocates its rings on first allocation.
+///
+/// Created with [`RingAlloc::lazy`] or [`RingAlloc::lazy_in`].
+/// Allocators that are constructed but never used allocate nothing.
+/// Like [`InlineRingAlloc`] it is not [`Clone`],
+/// collections should use `&LazyRingAlloc` as their allocator.
+///
+/// If the rings can't be allocated, underlying allocator is dropped
+/// and all following allocations fail.
+pub struct LazyRingAlloc<A: Allocator, C: SizeClasses = Classes> {
+    ring: OnceCell<RingAlloc<A, C>>,
+    allocator: Cell<Option<A>>,
+}
+
+impl<A, C> LazyRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    /// Returns ring-allocator if it was allocated already.
+    #[inline(always)]
+    pub fn get(&self) -> Option<&RingAlloc<A, C>> {
+        self.ring.get()
+    }
+
+    /// Returns ring-allocator, allocating it on first call.
+    #[inline(always)]
+    pub fn force(&self) -> Result<&RingAlloc<A, C>, AllocError> {
+        match self.ring.get() {
+            Some(ring) => Ok(ring),
+            None => self.init(),
         }
+    }
 
-        ring.tail.set(None);
+    /// Returns ring-allocator that allocated existing block.
+    #[inline(always)]
+    fn allocated(&self) -> &RingAlloc<A, C> {
+        match self.ring.get() {
+            Some(ring) => ring,
+            None => unreachable!("Block is used before the rings are allocated"),
+        }
+    }
+
+    #[cold]
+    fn init(&self) -> Result<&RingAlloc<A, C>, AllocError> {
+        let allocator = self.allocator.take().ok_or(AllocError)?;
+        let ring = RingAlloc::try_with_classes_in(allocator)?;
+        Ok(self.ring.get_or_init(|| ring))
     }
 }
 
-#[cfg(not(no_global_oom_handling))]
+unsafe impl<A, C> Allocator for LazyRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    #[inline(always)]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.force()?.allocate(layout)
+    }
+
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.force()?.allocate_zeroed(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Safety: covered by `Allocator::deallocate` contract.
+        unsafe { self.allocated().deallocate(ptr, layout) }
+    }
+
+    #[inline(always)]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        // Safety: covered by `Allocator::grow` contract.
+        unsafe { Allocator::grow(self.allocated(), ptr, old_layout, new_layout) }
+    }
+}
+
+ring_alloc! {
+    /// Ring-allocator that stores its rings inline.
+    ///
+    /// Unlike [`RingAlloc`] this type doesn't allocate rings from underlying allocator
+    /// and doesn't maintain reference counter, saving an allocation and
+    /// pointer indirection on each allocation.
+    /// In exchange it is not [`Clone`] and collections should use `&InlineRingAlloc`
+    /// as their allocator.
+    ///
+    /// Suitable for short-lived single-owner arenas.
+    pub struct InlineRingAlloc inline;
+}
+
 #[cfg(feature = "alloc")]
-impl RingAlloc {
-    /// Returns new [`RingAlloc`] that uses [`Global`] allocator.
+impl InlineRingAlloc {
+    /// Returns new [`InlineRingAlloc`] that uses [`Global`] allocator.
     #[inline(always)]
     pub fn new() -> Self {
-        RingAlloc {
-            inner: Rings::new_in(allocator_api2::alloc::Global),
-        }
+        InlineRingAlloc::new_in(allocator_api2::alloc::Global)
     }
 }
 
-#[cfg(not(no_global_oom_handling))]
-impl<A> Default for RingAlloc<A>
+impl<A> Default for InlineRingAlloc<A>
 where
     A: Allocator + Default,
 {
     #[inline(always)]
     fn default() -> Self {
-        RingAlloc::new_in(A::default())
+        InlineRingAlloc::new_in(A::default())
     }
 }
 
-impl<A> RingAlloc<A>
+impl<A, C> Drop for InlineRingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
-    /// Returns new [`RingAlloc`] that uses given allocator.
-    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.inner.free_all();
+
+        // Safety: allocator is not used after this point.
+        unsafe { ManuallyDrop::drop(&mut self.inner.allocator) }
+    }
+}
+
+impl<A> InlineRingAlloc<A>
+where
+    A: Allocator,
+{
+    /// Returns new [`InlineRingAlloc`] that uses given allocator.
     #[inline(always)]
     pub fn new_in(allocator: A) -> Self {
-        RingAlloc {
-            inner: Rings::new_in(allocator),
+        InlineRingAlloc {
+            inner: Rings::new_inline(allocator, Mode::Ring, false),
         }
     }
+}
 
-    /// Attempts to create new [`RingAlloc`] that uses given allocator.
+impl<A, C> InlineRingAlloc<A, C>
+where
+    A: Allocator,
+    C: SizeClasses,
+{
+    /// Returns new [`InlineRingAlloc`] that uses given allocator
+    /// and serves only size classes enabled by `C`.
+    ///
+    /// See [`RingAlloc::with_classes_in`].
     #[inline(always)]
-    pub fn try_new_in(allocator: A) -> Result<Self, AllocError> {
-        Ok(RingAlloc {
-            inner: Rings::try_new_in(allocator)?,
-        })
+    pub fn with_classes_in(allocator: A) -> Self {
+        InlineRingAlloc {
+            inner: Rings::new_inline(allocator, Mode::Ring, false),
+        }
     }
 
     /// Attempts to allocate a block of memory with this ring-allocator.
     /// Returns a pointer to the beginning of the block if successful.
+    #[must_use = "the allocated block must be used and eventually deallocated"]
     #[inline(always)]
     pub fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        // Safety: `self.inner` is valid pointer to `Rings`
-        let inner = unsafe { self.inner.as_ref() };
-        if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE {
-            Self::_allocate(&inner.tiny_ring, layout, &inner.allocator)
-        } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE {
-            Self::_allocate(&inner.small_ring, layout, &inner.allocator)
-        } else if layout_max(layout) <= LARGE_ALLOCATION_MAX_SIZE {
-            Self::_allocate(&inner.large_ring, layout, &inner.allocator)
-        } else {
-            inner.allocator.allocate(layout)
-        }
+        self.inner.allocate(layout)
+    }
+
+    /// Attempts to allocate a block of zeroed memory with this ring-allocator.
+    ///
+    /// See [`RingAlloc::allocate_zeroed`].
+    #[must_use = "the allocated block must be used and eventually deallocated"]
+    #[inline(always)]
+    pub fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.inner.allocate_zeroed(layout)
     }
 
     /// Deallocates the memory referenced by `ptr`.
     ///
     /// # Safety
     ///
-    /// * `ptr` must denote a block of memory [*currently allocated*] via [`RingAlloc::allocate`], and
+    /// * `ptr` must denote a block of memory [*currently allocated*] via [`InlineRingAlloc::allocate`], and
     /// * `layout` must [*fit*] that block of memory.
     ///
     /// [*currently allocated*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#currently-allocated-memory
     /// [*fit*]: https://doc.rust-lang.org/std/alloc/trait.Allocator.html#memory-fitting
     #[inline(always)]
     pub unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
-        if layout_max(layout) <= TINY_ALLOCATION_MAX_SIZE {
-            unsafe {
-                Self::_deallocate::<{ TINY_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else if layout_max(layout) <= SMALL_ALLOCATION_MAX_SIZE {
-            unsafe {
-                Self::_deallocate::<{ SMALL_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else if layout_max(layout) <= LARGE_ALLOCATION_MAX_SIZE {
-            unsafe {
-                Self::_deallocate::<{ LARGE_ALLOCATION_CHUNK_SIZE }>(ptr, layout);
-            }
-        } else {
-            // Safety: `self.inner` is valid pointer to `Rings`
-            let inner = unsafe { self.inner.as_ref() };
-            // Safety: `ptr` is valid pointer allocated by `self.allocator`.
-            unsafe {
-                inner.allocator.deallocate(ptr, layout);
-            }
-        }
-    }
-
-    #[inline(always)]
-    fn _allocate<const N: usize>(
-        ring: &Ring<Chunk<N>>,
-        layout: Layout,
-        allocator: &A,
-    ) -> Result<NonNull<[u8]>, AllocError> {
-        // Try head chunk.
-        if let Some(chunk_ptr) = ring.head.get() {
-            // Safety: `chunk` is valid pointer to `Chunk` allocated by `self.allocator`.
-            let chunk = unsafe { chunk_ptr.as_ref() };
-
-            match chunk.allocate(chunk_ptr, layout) {
-                Some(ptr) => {
-                    // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
-                    // ptr is allocated to fit `layout.size()` bytes.
-                    return Ok(unsafe {
-                        NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
-                            ptr.as_ptr(),
-                            layout.size(),
-                        ))
-                    });
-                }
-                // Chunk is full. Try next one.
-                None => match chunk.next.take() {
-                    None => {
-                        debug_assert_eq!(ring.tail.get(), ring.head.get());
-                    }
-                    Some(next_ptr) => {
-                        // Move head to tail and bring next one as head.
-
-                        // Safety: tail is valid pointer to `Chunk` allocated by `self.allocator`.
-                        let tail_chunk = unsafe { ring.tail.get().unwrap().as_ref() };
-                        debug_assert_eq!(tail_chunk.next(), None);
-                        tail_chunk.next.set(Some(chunk_ptr));
-                        ring.tail.set(Some(chunk_ptr));
-                        ring.head.set(Some(next_ptr));
-
-                        let next = unsafe { next_ptr.as_ref() };
-
-                        if next.reset() {
-                            if let Some(ptr) = next.allocate(next_ptr, layout) {
-                                // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
-                                // ptr is allocated to fit `layout.size()` bytes.
-                                return Ok(unsafe {
-                                    NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
-                                        ptr.as_ptr(),
-                                        layout.size(),
-                                    ))
-                                });
-                            }
-                        }
-
-                        // Not ready yet. Allocate new chunk.
-                    }
-                },
-            }
-        } else {
-            debug_assert_eq!(ring.tail.get(), None);
-        }
-
-        let chunk_ptr = Chunk::<N>::new(allocator)?;
-
-        // Safety: `chunk` is valid pointer to `Chunk` allocated by `self.allocator`.
-        let chunk = unsafe { chunk_ptr.as_ref() };
-
-        let ptr = chunk
-            .allocate(chunk_ptr, layout)
-            .expect("Failed to allocate from fresh chunk");
-
-        // Put to head.
-        chunk.next.set(ring.head.get());
-
-        // If first chunk, put to tail.
-        if ring.tail.get().is_none() {
-            debug_assert_eq!(ring.head.get(), None);
-
-            // Modify after asserts.
-            ring.tail.set(Some(chunk_ptr));
-        } else {
-            debug_assert!(ring.head.get().is_some());
-        }
-
-        // Modify after asserts.
-        ring.head.set(Some(chunk_ptr));
-
-        // Safety: `ptr` is valid pointer to `Chunk` allocated by `self.allocator`.
-        // ptr is allocated to fit `layout.size()` bytes.
-        Ok(unsafe {
-            NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(
-                ptr.as_ptr(),
-                layout.size(),
-            ))
-        })
-    }
-
-    #[inline(always)]
-    unsafe fn _deallocate<const N: usize>(ptr: NonNull<u8>, layout: Layout) {
-        // Safety: `ptr` is valid pointer allocated from alive `Chunk`.
-        unsafe {
-            Chunk::<N>::deallocate(ptr.as_ptr(), layout);
-        }
+        // Safety: `ptr` is allocated by `self.inner.allocate(layout)`.
+        unsafe { self.inner.deallocate(ptr, layout) }
     }
 
-    /// Free all unused chunks back to underlying allocator.
+    /// Frees all unused chunks back to underlying allocator.
     pub fn flush(&self) {
-        // Safety: `self.inner` is valid pointer to `Rings`
-        let inner = unsafe { self.inner.as_ref() };
-        inner.clean_all();
+        self.inner.clean_all();
     }
 }
 
-unsafe impl<A> Allocator for RingAlloc<A>
+unsafe impl<A, C> Allocator for InlineRingAlloc<A, C>
 where
     A: Allocator,
+    C: SizeClasses,
 {
     #[inline(always)]
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         self.allocate(layout)
     }
 
+    #[inline(always)]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_zeroed(layout)
+    }
+
     #[inline(always)]
     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
         // Safety: covered by `Allocator::deallocate` contract.
         unsafe { self.deallocate(ptr, layout) }
     }
-
-    // TODO: Implement grow and shrink.
 }
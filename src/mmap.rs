@@ -0,0 +1,275 @@
+use core::{alloc::Layout, cell::RefCell, convert::TryFrom, ptr::NonNull};
+use std::{collections::BTreeMap, fs::File, io, os::unix::io::AsRawFd};
+
+use allocator_api2::alloc::{AllocError, Allocator};
+
+/// Mapping of a file region.
+struct Mapping {
+    /// Offset of the region in the file.
+    offset: u64,
+
+    /// Size of the mapping, multiple of page size.
+    len: usize,
+}
+
+struct State {
+    /// Length of the file used by the allocator so far.
+    end: u64,
+
+    /// Free regions of the file by their offsets.
+    /// Adjacent regions are merged.
+    free: BTreeMap<u64, u64>,
+
+    /// Live mappings by their addresses.
+    mappings: BTreeMap<usize, Mapping>,
+}
+
+/// Allocator that places blocks into memory-mapped regions of a file.
+///
+/// Intended as underlying allocator of [`RingAlloc`] for persistent arenas,
+/// e.g. `RingAlloc::new_in(MmapFileAlloc::new(file)?)`.
+/// Each chunk then lives in a region of the file mapped as shared,
+/// so its contents reach the file and can be persisted with [`MmapFileAlloc::flush`].
+///
+/// File grows when no free region fits a new block.
+/// Deallocation unmaps the block and returns its region to the free list
+/// for later allocations.
+/// Regions are aligned to page size within the file, existing contents
+/// of the file are left intact.
+///
+/// Allocator is not [`Sync`], each thread should use its own file.
+///
+/// [`RingAlloc`]: crate::RingAlloc
+pub struct MmapFileAlloc {
+    file: File,
+    page_size: usize,
+    state: RefCell<State>,
+}
+
+impl MmapFileAlloc {
+    /// Returns new allocator that maps regions of the file after its current end.
+    ///
+    /// File must be opened for reading and writing.
+    pub fn new(file: File) -> io::Result<Self> {
+        // Safety: `sysconf` has no preconditions.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page_size <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let page_size = page_size as usize;
+
+        let len = file.metadata()?.len();
+        let end = (len + page_size as u64 - 1) & !(page_size as u64 - 1);
+
+        Ok(MmapFileAlloc {
+            file,
+            page_size,
+            state: RefCell::new(State {
+                end,
+                free: BTreeMap::new(),
+                mappings: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// Returns the file that backs allocations.
+    #[inline(always)]
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns offset in the file of the byte at `ptr`,
+    /// or `None` if `ptr` doesn't point into memory allocated by this allocator.
+    pub fn offset_of(&self, ptr: NonNull<u8>) -> Option<u64> {
+        let addr = crate::addr(ptr.as_ptr());
+        let state = self.state.borrow();
+        let (&start, mapping) = state.mappings.range(..=addr).next_back()?;
+
+        if addr - start < mapping.len {
+            Some(mapping.offset + (addr - start) as u64)
+        } else {
+            None
+        }
+    }
+
+    /// Writes modified memory of all live blocks to the file
+    /// and waits until it is done.
+    pub fn flush(&self) -> io::Result<()> {
+        let state = self.state.borrow();
+        for (&start, mapping) in &state.mappings {
+            // Safety: mapping is alive and spans `mapping.len` bytes.
+            if unsafe { libc::msync(start as *mut libc::c_void, mapping.len, libc::MS_SYNC) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes region of `len` bytes from free list or from the end of the file.
+    fn take_region(&self, state: &mut State, len: u64) -> Result<u64, AllocError> {
+        let fit = state
+            .free
+            .iter()
+            .find(|&(_, &free_len)| free_len >= len)
+            .map(|(&offset, &free_len)| (offset, free_len));
+
+        if let Some((offset, free_len)) = fit {
+            state.free.remove(&offset);
+            if free_len > len {
+                state.free.insert(offset + len, free_len - len);
+            }
+            return Ok(offset);
+        }
+
+        let offset = state.end;
+        let end = offset.checked_add(len).ok_or(AllocError)?;
+        if end > self.file.metadata().map_err(|_| AllocError)?.len() {
+            self.file.set_len(end).map_err(|_| AllocError)?;
+        }
+        state.end = end;
+        Ok(offset)
+    }
+
+    /// Returns region to free list, merging it with adjacent free regions.
+    fn release_region(state: &mut State, mut offset: u64, mut len: u64) {
+        if let Some((&prev, &prev_len)) = state.free.range(..offset).next_back() {
+            if prev + prev_len == offset {
+                state.free.remove(&prev);
+                offset = prev;
+                len += prev_len;
+            }
+        }
+        if let Some(next_len) = state.free.remove(&(offset + len)) {
+            len += next_len;
+        }
+        state.free.insert(offset, len);
+    }
+
+    /// Maps `len` bytes of the file at `offset` to address aligned to `align`.
+    fn map(&self, offset: u64, len: usize, align: usize) -> Result<NonNull<u8>, AllocError> {
+        let fd = self.file.as_raw_fd();
+        let file_offset = libc::off_t::try_from(offset).map_err(|_| AllocError)?;
+
+        if align <= self.page_size {
+            // Safety: mapping new region of the file at address chosen by the system.
+            let ptr = unsafe {
+                libc::mmap(
+                    core::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    fd,
+                    file_offset,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(AllocError);
+            }
+            return NonNull::new(ptr.cast()).ok_or(AllocError);
+        }
+
+        // Reserve address range large enough to contain aligned mapping.
+        let reserve = len.checked_add(align).ok_or(AllocError)?;
+
+        // Safety: reserving new inaccessible region of address space.
+        let base = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                reserve,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return Err(AllocError);
+        }
+
+        let base_addr = crate::addr(base);
+        let aligned = (base_addr + align - 1) & !(align - 1);
+        let head = aligned - base_addr;
+        let tail = reserve - head - len;
+
+        // Safety: `aligned..aligned + len` is within reserved range,
+        // which is replaced with the file mapping.
+        let ptr = unsafe {
+            libc::mmap(
+                base.cast::<u8>().add(head).cast(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_FIXED,
+                fd,
+                file_offset,
+            )
+        };
+
+        // Safety: unmapping parts of reserved range that are not used.
+        unsafe {
+            if ptr == libc::MAP_FAILED {
+                libc::munmap(base, reserve);
+                return Err(AllocError);
+            }
+            if head > 0 {
+                libc::munmap(base, head);
+            }
+            if tail > 0 {
+                libc::munmap(base.cast::<u8>().add(head + len).cast(), tail);
+            }
+        }
+
+        NonNull::new(ptr.cast()).ok_or(AllocError)
+    }
+}
+
+unsafe impl Allocator for MmapFileAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let len = layout
+            .size()
+            .max(1)
+            .checked_add(self.page_size - 1)
+            .ok_or(AllocError)?
+            & !(self.page_size - 1);
+
+        let mut state = self.state.borrow_mut();
+        let offset = self.take_region(&mut state, len as u64)?;
+
+        let ptr = match self.map(offset, len, layout.align()) {
+            Ok(ptr) => ptr,
+            Err(AllocError) => {
+                Self::release_region(&mut state, offset, len as u64);
+                return Err(AllocError);
+            }
+        };
+
+        state
+            .mappings
+            .insert(crate::addr(ptr.as_ptr()), Mapping { offset, len });
+
+        Ok(NonNull::slice_from_raw_parts(ptr, len))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        let mut state = self.state.borrow_mut();
+
+        let mapping = state
+            .mappings
+            .remove(&crate::addr(ptr.as_ptr()))
+            .expect("Block is not allocated by this allocator");
+
+        // Safety: `ptr` is start of live mapping of `mapping.len` bytes.
+        unsafe { libc::munmap(ptr.as_ptr().cast(), mapping.len) };
+
+        Self::release_region(&mut state, mapping.offset, mapping.len as u64);
+    }
+}
+
+impl Drop for MmapFileAlloc {
+    fn drop(&mut self) {
+        for (&start, mapping) in &self.state.get_mut().mappings {
+            // Safety: mapping is alive and nothing can use it
+            // after the allocator is dropped.
+            unsafe { libc::munmap(start as *mut libc::c_void, mapping.len) };
+        }
+    }
+}
@@ -1,36 +1,4126 @@
 #![cfg(not(no_global_oom_handling))]
 
+/// Size of the block allocated for `u64`.
+/// Block metadata holds allocation site with `debug-introspect` feature
+/// and chunk's generation with `debug-validate` feature.
+#[allow(dead_code)]
+const U64_BLOCK_SIZE: usize = REDZONES_SIZE
+    + if cfg!(feature = "aligned-chunks") {
+        8
+    } else if cfg!(all(feature = "debug-introspect", debug_assertions)) {
+        40
+    } else {
+        16
+    }
+    + if cfg!(all(
+        feature = "debug-validate",
+        not(feature = "aligned-chunks")
+    )) {
+        8
+    } else {
+        0
+    };
+
+/// Size of guard bytes around each block.
+#[cfg(feature = "debug-redzone")]
+const REDZONES_SIZE: usize = 2 * crate::chunk::REDZONE_SIZE;
+
+#[cfg(not(feature = "debug-redzone"))]
+const REDZONES_SIZE: usize = 0;
+
 #[cfg(feature = "alloc")]
 mod local {
-    use crate::RingAlloc;
+    use alloc::{format, rc::Rc, string::String};
+    use core::{
+        cell::{Cell, RefCell},
+        ptr::NonNull,
+    };
+
+    use super::U64_BLOCK_SIZE;
+    use crate::{ArenaVec, InlineRingAlloc, MemoryReport, RingAlloc};
+    use allocator_api2::{
+        alloc::{AllocError, Allocator, Global, Layout},
+        boxed::Box,
+        vec::Vec,
+    };
     use allocator_api2_tests::make_test;
     make_test![
         test_sizes(RingAlloc::new()),
         test_vec(RingAlloc::new()),
         test_many_boxes(&RingAlloc::new())
     ];
-}
 
-#[cfg(feature = "std")]
-mod global {
-    use crate::OneRingAlloc;
+    mod inline {
+        use crate::InlineRingAlloc;
+        use allocator_api2_tests::make_test;
 
-    use allocator_api2::boxed::Box;
-    use allocator_api2_tests::make_test;
+        make_test![
+            test_sizes(&InlineRingAlloc::new()),
+            test_vec(&InlineRingAlloc::new()),
+            test_many_boxes(&InlineRingAlloc::new())
+        ];
+    }
 
-    make_test![
-        test_sizes(OneRingAlloc),
-        test_vec(OneRingAlloc),
-        test_many_boxes(OneRingAlloc)
-    ];
+    /// Number of `u64` blocks that fill three tiny chunks.
+    const U64_BLOCKS_IN_3_CHUNKS: usize = 3000 * 16 / U64_BLOCK_SIZE;
+
+    /// Underlying allocator that counts allocations.
+    #[derive(Default)]
+    struct CountingAllocator {
+        allocations: Cell<usize>,
+        deallocations: Cell<usize>,
+    }
+
+    unsafe impl Allocator for CountingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocations.set(self.allocations.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.deallocations.set(self.deallocations.get() + 1);
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    /// Underlying allocator that fails when its budget of blocks is spent.
+    struct LimitedAllocator {
+        left: Cell<usize>,
+
+        /// Deallocated blocks are returned to the budget.
+        refund: bool,
+    }
+
+    impl LimitedAllocator {
+        /// Fails after `n` allocations.
+        fn allocations(n: usize) -> Self {
+            LimitedAllocator {
+                left: Cell::new(n),
+                refund: false,
+            }
+        }
+
+        /// Holds at most `n` blocks at once.
+        fn live(n: usize) -> Self {
+            LimitedAllocator {
+                left: Cell::new(n),
+                refund: true,
+            }
+        }
+    }
+
+    unsafe impl Allocator for LimitedAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            if self.left.get() == 0 {
+                return Err(AllocError);
+            }
+            self.left.set(self.left.get() - 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if self.refund {
+                self.left.set(self.left.get() + 1);
+            }
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    /// Underlying allocator that hands out adjacent tiny chunks from an arena
+    /// and records sizes of blocks returned to the arena.
+    struct ArenaAllocator {
+        arena: NonNull<u8>,
+        used: Cell<usize>,
+        freed: RefCell<Vec<usize>>,
+    }
+
+    impl ArenaAllocator {
+        const CHUNK_SIZE: usize = 16384;
+        const ARENA_LAYOUT: Layout = match Layout::from_size_align(16384 * 4, 16384) {
+            Ok(layout) => layout,
+            Err(_) => panic!(),
+        };
+
+        fn new() -> Self {
+            ArenaAllocator {
+                arena: Global.allocate(Self::ARENA_LAYOUT).unwrap().cast(),
+                used: Cell::new(0),
+                freed: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn contains(&self, ptr: NonNull<u8>) -> bool {
+            let start = self.arena.as_ptr() as usize;
+            (start..start + Self::ARENA_LAYOUT.size()).contains(&(ptr.as_ptr() as usize))
+        }
+    }
+
+    impl Drop for ArenaAllocator {
+        fn drop(&mut self) {
+            unsafe { Global.deallocate(self.arena, Self::ARENA_LAYOUT) }
+        }
+    }
+
+    unsafe impl Allocator for ArenaAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            if layout.size() != Self::CHUNK_SIZE {
+                return Global.allocate(layout);
+            }
+            if self.used.get() == Self::ARENA_LAYOUT.size() {
+                return Err(AllocError);
+            }
+            let ptr = unsafe { self.arena.as_ptr().add(self.used.get()) };
+            self.used.set(self.used.get() + Self::CHUNK_SIZE);
+            let slice = core::ptr::slice_from_raw_parts_mut(ptr, Self::CHUNK_SIZE);
+            Ok(unsafe { NonNull::new_unchecked(slice) })
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if self.contains(ptr) {
+                self.freed.borrow_mut().push(layout.size());
+            } else {
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+    }
+
+    /// Allocator with large state that checks the state on each allocation.
+    struct LargeAllocator {
+        state: [u8; 65536],
+    }
+
+    impl LargeAllocator {
+        fn new() -> Self {
+            LargeAllocator {
+                state: [0xAB; 65536],
+            }
+        }
+    }
+
+    unsafe impl Allocator for LargeAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            assert!(self.state.iter().all(|b| *b == 0xAB));
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    /// Underlying allocator that shrinks blocks in place
+    /// and checks that blocks are deallocated with their shrunk size.
+    #[derive(Default)]
+    struct TrimmingAllocator {
+        /// Address, original size and current size of each live block.
+        blocks: RefCell<Vec<(usize, usize, usize)>>,
+        trimmed: Cell<usize>,
+    }
+
+    unsafe impl Allocator for TrimmingAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = Global.allocate(layout)?;
+            self.blocks.borrow_mut().push((
+                ptr.cast::<u8>().as_ptr() as usize,
+                layout.size(),
+                layout.size(),
+            ));
+            Ok(ptr)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let mut blocks = self.blocks.borrow_mut();
+            let idx = blocks
+                .iter()
+                .position(|&(addr, _, _)| addr == ptr.as_ptr() as usize)
+                .unwrap();
+            let (_, original, current) = blocks.swap_remove(idx);
+            assert_eq!(layout.size(), current);
+
+            let layout = Layout::from_size_align(original, layout.align()).unwrap();
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let mut blocks = self.blocks.borrow_mut();
+            let block = blocks
+                .iter_mut()
+                .find(|(addr, _, _)| *addr == ptr.as_ptr() as usize)
+                .unwrap();
+            assert_eq!(old_layout.size(), block.2);
+            block.2 = new_layout.size();
+
+            self.trimmed
+                .set(self.trimmed.get() + old_layout.size() - new_layout.size());
+            Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+        }
+    }
+
+    /// Allocates and deallocates blocks of random layouts from single chunk,
+    /// resetting it only when it is full,
+    /// and checks that `freed` never leaves `[base, cursor]`.
+    fn stress_chunk_counters<T: crate::ImUsize>(lifo: bool) {
+        type Chunk<T> = crate::chunk::Chunk<T, 16384>;
+
+        let chunk_ptr = Chunk::<T>::new(Global).unwrap();
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        let dealloc = |ptr: NonNull<u8>, layout| unsafe {
+            if lifo {
+                Chunk::<T>::deallocate_lifo(ptr.as_ptr(), layout);
+            } else {
+                Chunk::<T>::deallocate(ptr.as_ptr(), layout);
+            }
+        };
+
+        let mut live = Vec::new();
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let rounds = if cfg!(miri) { 1000 } else { 1_000_000 };
+
+        for _ in 0..rounds {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            let size = (state % 200) as usize;
+            let align = 1 << ((state >> 8) % 7);
+            let layout = Layout::from_size_align(size, align).unwrap();
+
+            let ptr = match chunk.allocate(chunk_ptr, layout) {
+                Some(ptr) => ptr,
+                None => {
+                    for (ptr, layout) in live.drain(..) {
+                        dealloc(ptr, layout);
+                    }
+                    assert!(chunk.unused());
+                    assert!(chunk.reset());
+                    chunk.allocate(chunk_ptr, layout).unwrap()
+                }
+            };
+            live.push((ptr, layout));
+            crate::chunk::check_counters(chunk);
+            assert!(!chunk.unused());
+
+            // Keep few blocks live and free them in random order.
+            if live.len() > 4 || state & (1 << 20) != 0 {
+                let idx = (state >> 24) as usize % live.len();
+                let (ptr, layout) = live.swap_remove(idx);
+                dealloc(ptr, layout);
+                crate::chunk::check_counters(chunk);
+                assert_eq!(chunk.unused(), live.is_empty());
+            }
+        }
+
+        for (ptr, layout) in live.drain(..) {
+            dealloc(ptr, layout);
+        }
+        assert!(chunk.unused());
+        unsafe { Chunk::<T>::free(chunk_ptr, Global) };
+    }
 
     #[test]
-    fn test_global_share() {
-        let b = std::thread::spawn(|| Box::new_in(0u32, OneRingAlloc))
-            .join()
-            .unwrap();
-        drop(b);
+    fn test_chunk_counters_stay_in_bounds() {
+        stress_chunk_counters::<Cell<usize>>(false);
+        stress_chunk_counters::<Cell<usize>>(true);
 
-        drop(Box::new_in(0u32, OneRingAlloc));
+        // Shared chunks add to `freed` with `fetch_add`.
+        #[cfg(all(feature = "std", not(loom)))]
+        stress_chunk_counters::<crate::sync::AtomicUsize>(false);
+    }
+
+    #[test]
+    #[cfg(not(feature = "aligned-chunks"))]
+    fn test_prefixed_layout() {
+        type Chunk = crate::chunk::Chunk<Cell<usize>, 16384>;
+
+        for align in (0..13).map(|shift| 1 << shift) {
+            for size in (0..80).chain([255, 256, 4095, 65536]) {
+                let layout = Layout::from_size_align(size, align).unwrap();
+                assert_eq!(
+                    Chunk::prefixed_layout(layout),
+                    Chunk::extended_layout(layout),
+                    "{:?}",
+                    layout
+                );
+            }
+        }
+
+        let huge = Layout::from_size_align(isize::MAX as usize - 7, 8).unwrap();
+        assert_eq!(Chunk::prefixed_layout(huge), None);
+        assert_eq!(Chunk::extended_layout(huge), None);
+    }
+
+    #[test]
+    fn test_chunk_debug() {
+        type Chunk = crate::chunk::Chunk<Cell<usize>, 16384>;
+
+        let chunk_ptr = Chunk::new(Global).unwrap();
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        let layout = Layout::new::<u64>();
+        let blocks = (0..4)
+            .map(|_| chunk.allocate(chunk_ptr, layout).unwrap())
+            .collect::<Vec<_>>();
+        unsafe { Chunk::deallocate(blocks[0].as_ptr(), layout) };
+
+        let used = chunk.used();
+        assert_eq!(used, 4 * U64_BLOCK_SIZE);
+
+        let debug = format!("{:?}", chunk);
+        assert!(debug.contains(&format!("used: {},", used)), "{}", debug);
+        assert!(
+            debug.contains(&format!("live: {},", 3 * U64_BLOCK_SIZE)),
+            "{}",
+            debug
+        );
+        assert!(
+            debug.contains(&format!("reclaimable: {},", U64_BLOCK_SIZE)),
+            "{}",
+            debug
+        );
+        assert!(
+            debug.contains(&format!(
+                "free: {},",
+                chunk.capacity.get() - used - core::mem::size_of::<Chunk>()
+            )),
+            "{}",
+            debug
+        );
+        assert!(debug.contains("unused: false"), "{}", debug);
+
+        for block in &blocks[1..] {
+            unsafe { Chunk::deallocate(block.as_ptr(), layout) };
+        }
+        unsafe { Chunk::free(chunk_ptr, Global) };
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-redzone"))]
+    fn test_min_alignment() {
+        let alloc = RingAlloc::with_min_alignment_in(Global, 32);
+        let layout = Layout::from_size_align(32, 32).unwrap();
+
+        let blocks = (0..10)
+            .map(|_| alloc.allocate(layout).unwrap().cast::<u8>().as_ptr() as usize)
+            .collect::<Vec<_>>();
+
+        // No padding between consecutive blocks.
+        let stride = blocks[1] - blocks[0];
+        assert!(blocks.windows(2).all(|w| w[1] - w[0] == stride));
+
+        // And no padding before the first block.
+        let mut dump = String::new();
+        alloc.dump_state(&mut dump).unwrap();
+        assert!(dump.contains(&format!("used {},", 10 * stride)), "{}", dump);
+
+        for block in blocks {
+            assert_eq!(block % 32, 0);
+            unsafe { alloc.deallocate(NonNull::new(block as *mut u8).unwrap(), layout) };
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn test_min_alignment_invalid() {
+        let _ = RingAlloc::with_min_alignment_in(Global, 24);
+    }
+
+    #[test]
+    fn test_trim_head_chunks() {
+        let trimming = TrimmingAllocator::default();
+        let alloc = RingAlloc::new_in(&trimming);
+        let layout = Layout::new::<[u8; 100]>();
+
+        let mut blocks = Vec::new();
+        for _ in 0..10 {
+            blocks.push(alloc.allocate(layout).unwrap());
+        }
+
+        // Small chunk is shrunk to first eighth of it.
+        let trimmed = unsafe { alloc.trim_head_chunks() };
+        assert_eq!(trimmed, 65536 - 8192);
+        assert_eq!(trimming.trimmed.get(), trimmed);
+
+        // Nothing left to trim.
+        assert_eq!(unsafe { alloc.trim_head_chunks() }, 0);
+
+        // Allocations continue in new chunks when trimmed one is full.
+        for i in 0..1000 {
+            let ptr = alloc.allocate(layout).unwrap();
+            unsafe { ptr.cast::<u8>().as_ptr().write_bytes(i as u8, 100) };
+            blocks.push(ptr);
+        }
+        assert!(alloc.check_rings() > 1);
+
+        for ptr in blocks {
+            unsafe { alloc.deallocate(ptr.cast(), layout) };
+        }
+        drop(alloc);
+        assert!(trimming.blocks.borrow().is_empty());
+    }
+
+    #[test]
+    #[cfg(not(feature = "aligned-chunks"))]
+    fn test_chunk_with_capacity() {
+        type Chunk = crate::chunk::Chunk<Cell<usize>, 16384>;
+
+        let trimming = TrimmingAllocator::default();
+        let chunk_ptr = Chunk::with_capacity(&trimming, 40000, 1, false).unwrap();
+        let chunk = unsafe { chunk_ptr.as_ref() };
+
+        // Rounded up to multiple of eighth of default size.
+        assert_eq!(chunk.capacity.get(), 40960);
+        assert_eq!(trimming.blocks.borrow()[0].1, 40960);
+
+        // Blocks fill whole capacity, beyond default chunk size.
+        let layout = Layout::new::<u64>();
+        let mut blocks = Vec::new();
+        while let Some(ptr) = chunk.allocate(chunk_ptr, layout) {
+            unsafe { ptr.cast::<u64>().as_ptr().write(blocks.len() as u64) };
+            blocks.push(ptr);
+        }
+        assert!(blocks.len() * U64_BLOCK_SIZE > 16384 * 2);
+        assert!(chunk.live() <= 40960);
+
+        for (i, ptr) in blocks.drain(..).enumerate() {
+            assert_eq!(unsafe { *ptr.cast::<u64>().as_ptr() }, i as u64);
+            unsafe { Chunk::deallocate(ptr.as_ptr(), layout) };
+        }
+        assert!(chunk.unused());
+        assert!(chunk.reset());
+
+        // Large chunk can be trimmed as well.
+        blocks.push(chunk.allocate(chunk_ptr, layout).unwrap());
+        assert_eq!(unsafe { Chunk::trim(chunk_ptr, &trimming) }, 40960 - 2048);
+        unsafe { Chunk::deallocate(blocks[0].as_ptr(), layout) };
+
+        unsafe { Chunk::free(chunk_ptr, &trimming) };
+        assert!(trimming.blocks.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_borrowing_in() {
+        let pool = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::borrowing_in(&pool);
+            let boxes = (0..5000u32)
+                .map(|i| Box::new_in(i, alloc.clone()))
+                .collect::<Vec<_>>();
+            assert!(pool.allocations.get() > 2);
+            drop(boxes);
+        }
+
+        // Rings and chunks are returned to the borrowed pool.
+        assert_eq!(pool.allocations.get(), pool.deallocations.get());
+
+        let alloc = RingAlloc::try_borrowing_in(&pool).unwrap();
+        drop(Box::new_in(0u32, &alloc));
+    }
+
+    #[test]
+    fn test_embedded_rings() {
+        let counter = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_embedded_in(&counter);
+            let b = Box::new_in([1u8; 100], &alloc);
+
+            // Rings and first small chunk are allocated at once.
+            assert_eq!(counter.allocations.get(), 1);
+
+            // Chunk with the rings is neither freed nor rewound over the rings.
+            drop(b);
+            alloc.flush();
+            unsafe { alloc.reset() };
+            assert_eq!(counter.deallocations.get(), 0);
+
+            let weak = alloc.downgrade();
+            let boxes = (0..1000u64)
+                .map(|i| Box::new_in([i; 4], alloc.clone()))
+                .collect::<Vec<_>>();
+            for (i, b) in boxes.iter().enumerate() {
+                assert_eq!(**b, [i as u64; 4]);
+            }
+            drop(boxes);
+            drop(alloc);
+
+            // Weak handle keeps the rings and their chunk.
+            assert!(weak.upgrade().is_none());
+            assert_eq!(counter.allocations.get(), counter.deallocations.get() + 1);
+        }
+
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_first_allocation_calls() {
+        let layout = Layout::new::<[u8; 100]>();
+
+        // Rings share the first chunk, so construction and first small allocation
+        // take a single call to the underlying allocator.
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_embedded_in(&counter);
+        let ptr = alloc.allocate(layout).unwrap();
+        assert_eq!(counter.allocations.get(), 1);
+        unsafe { alloc.deallocate(ptr.cast(), layout) };
+
+        let alloc = RingAlloc::new_embedded();
+        let b = Box::new_in([7u8; 100], &alloc);
+        assert_eq!(alloc.memory_report().small_chunks, 1);
+        assert_eq!(b[99], 7);
+    }
+
+    #[test]
+    fn test_grow_across_size_class() {
+        let counter = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_in(&counter);
+
+            let mut v = Vec::<u8, _>::with_capacity_in(200, &alloc);
+            v.extend((0..200).map(|i| i as u8));
+            let ptr = v.as_ptr();
+
+            // 300 bytes are served by the large ring, but the block grows in its small chunk.
+            v.reserve_exact(100);
+            v.extend((200..300).map(|i| i as u8));
+            assert!(v.iter().enumerate().all(|(i, &b)| b == i as u8));
+
+            if cfg!(feature = "aligned-chunks") {
+                assert_ne!(v.as_ptr(), ptr);
+            } else {
+                assert_eq!(v.as_ptr(), ptr);
+                assert_eq!(alloc.memory_report().large_chunks, 0);
+            }
+
+            // Block is returned to the chunk it was allocated from.
+            drop(v);
+            assert_eq!(alloc.memory_report().live_blocks, 0);
+
+            // Block that is not the last one in the chunk is moved.
+            let mut v = Vec::<u8, _>::with_capacity_in(200, &alloc);
+            let b = Box::new_in([1u64; 4], &alloc);
+            let ptr = v.as_ptr();
+            v.reserve_exact(300);
+            assert_ne!(v.as_ptr(), ptr);
+            drop(b);
+        }
+
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_replace_backing() {
+        let old = CountingAllocator::default();
+        let new = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_in(&old);
+            let boxes = (0..100u64)
+                .map(|i| Box::new_in([i; 4], &alloc))
+                .collect::<Vec<_>>();
+            assert!(!alloc.is_empty());
+            drop(boxes);
+            assert!(alloc.is_empty());
+
+            // Chunks are freed through the old allocator.
+            unsafe { alloc.replace_backing(&new) };
+            assert_eq!(old.allocations.get(), old.deallocations.get() + 1);
+
+            let b = Box::new_in(1u64, &alloc);
+            assert_eq!(new.allocations.get(), 1);
+            assert_eq!(old.allocations.get(), 2);
+            drop(b);
+        }
+
+        // Rings allocated by the old allocator are deallocated through the new one.
+        assert_eq!(new.allocations.get() + 1, new.deallocations.get());
+        assert_eq!(old.allocations.get(), old.deallocations.get() + 1);
+    }
+
+    #[test]
+    fn test_replace_backing_embedded() {
+        /// Underlying allocator that records allocated and deallocated blocks.
+        #[derive(Default)]
+        struct RecordingAllocator {
+            allocated: RefCell<Vec<(usize, Layout)>>,
+            deallocated: RefCell<Vec<(usize, Layout)>>,
+        }
+
+        unsafe impl Allocator for RecordingAllocator {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                let ptr = Global.allocate(layout)?;
+                self.allocated
+                    .borrow_mut()
+                    .push((ptr.cast::<u8>().as_ptr() as usize, layout));
+                Ok(ptr)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.deallocated
+                    .borrow_mut()
+                    .push((ptr.as_ptr() as usize, layout));
+                unsafe { Global.deallocate(ptr, layout) }
+            }
+        }
+
+        let old = RecordingAllocator::default();
+        let new = RecordingAllocator::default();
+        {
+            let alloc = RingAlloc::new_embedded_in(&old);
+            let boxes = (0..100u64)
+                .map(|i| Box::new_in(i, &alloc))
+                .collect::<Vec<_>>();
+            drop(boxes);
+
+            // Chunk that holds the rings is kept, the tiny chunk is freed.
+            unsafe { alloc.replace_backing(&new) };
+            assert_eq!(old.allocated.borrow().len(), 2);
+            assert_eq!(old.deallocated.borrow().len(), 1);
+        }
+
+        // Home chunk is deallocated through the new allocator with its original layout.
+        assert_eq!(*new.deallocated.borrow(), [old.allocated.borrow()[0]]);
+    }
+
+    #[test]
+    fn test_hashbrown() {
+        let counter = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_in(&counter);
+
+            // Table grows from small blocks to oversized ones.
+            let mut map = hashbrown::HashMap::new_in(&alloc);
+            for i in 0..10000u64 {
+                map.insert(i, [i; 4]);
+            }
+            for i in (0..10000u64).step_by(3) {
+                assert_eq!(map.remove(&i), Some([i; 4]));
+            }
+            map.shrink_to_fit();
+
+            for i in 0..10000u64 {
+                let expected = if i % 3 == 0 { None } else { Some(&[i; 4]) };
+                assert_eq!(map.get(&i), expected);
+            }
+
+            drop(map);
+            assert!(alloc.is_empty());
+
+            // All chunks are reclaimed.
+            alloc.flush();
+            assert_eq!(alloc.memory_report().chunks(), 0);
+            assert_eq!(counter.allocations.get(), counter.deallocations.get() + 1);
+        }
+
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_oversized_outlives_rings() {
+        let counter = CountingAllocator::default();
+        let layout = Layout::new::<[u8; 100000]>();
+
+        let alloc = RingAlloc::new_in(&counter);
+        let small = Box::new_in(1u64, &alloc);
+        let oversized = alloc.allocate(layout).unwrap().cast::<u8>();
+        unsafe { oversized.as_ptr().write_bytes(7, layout.size()) };
+        drop(small);
+
+        // Rings and the chunk are freed, oversized block is not.
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), 3);
+        assert_eq!(counter.deallocations.get(), 2);
+
+        // Block is still valid and is deallocated once, by the user.
+        unsafe {
+            assert_eq!(*oversized.as_ptr().add(layout.size() - 1), 7);
+            counter.deallocate(oversized, layout);
+        }
+        assert_eq!(counter.deallocations.get(), 3);
+    }
+
+    #[test]
+    fn test_handle() {
+        let alloc = RingAlloc::new();
+        let handle = alloc.handle();
+
+        let mut vecs = (0..100u32)
+            .map(|i| {
+                let mut v = Vec::new_in(handle);
+                v.extend(0..i);
+                v
+            })
+            .collect::<Vec<_>>();
+
+        // Handles don't touch reference counter.
+        assert_eq!(alloc.handle_count(), 1);
+
+        for (i, v) in vecs.iter_mut().enumerate() {
+            assert!(v.iter().copied().eq(0..i as u32));
+            v.extend(0..300);
+        }
+        drop(vecs);
+
+        // Blocks allocated via handle can be deallocated via the allocator.
+        let b = Box::new_in(7u64, handle);
+        let (ptr, _) = Box::into_raw_with_allocator(b);
+        unsafe { alloc.deallocate(NonNull::new(ptr).unwrap().cast(), Layout::new::<u64>()) };
+
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    fn test_allocate_slice() {
+        let counter = CountingAllocator::default();
+        let failed;
+        {
+            let alloc = RingAlloc::new_in(&counter);
+
+            let slice = alloc.allocate_slice::<u32>(10).unwrap();
+            assert_eq!(slice.len(), 10);
+
+            unsafe {
+                let ptr = slice.cast::<u32>();
+                for i in 0..10 {
+                    ptr.as_ptr().add(i).write(i as u32);
+                }
+                assert_eq!(ptr.as_ptr().add(9).read(), 9);
+                alloc.deallocate_slice(ptr, 10);
+            }
+
+            let empty = alloc.allocate_slice::<u64>(0).unwrap();
+            unsafe { alloc.deallocate_slice(empty.cast::<u64>(), 0) };
+
+            // Array layout overflows, underlying allocator is not reached.
+            let allocations = counter.allocations.get();
+            for len in [usize::MAX / 8 + 1, usize::MAX / 8, usize::MAX] {
+                assert!(alloc.allocate_slice::<u64>(len).is_err());
+            }
+            assert_eq!(counter.allocations.get(), allocations);
+
+            // Valid layouts that underlying allocator fails to satisfy.
+            // Results escape, so that optimizer can't elide the allocations
+            // and assume they succeed.
+            use core::hint::black_box;
+            assert!(black_box(alloc.allocate_slice::<u64>(isize::MAX as usize / 8)).is_err());
+            assert!(black_box(alloc.allocate_slice::<[u8; 3]>(isize::MAX as usize / 3)).is_err());
+            for align in [1, 8, 4096] {
+                let layout =
+                    Layout::from_size_align(isize::MAX as usize - (align - 1), align).unwrap();
+                assert!(black_box(alloc.allocate(layout)).is_err());
+            }
+            failed = counter.allocations.get() - allocations;
+        }
+        assert_eq!(
+            counter.allocations.get() - failed,
+            counter.deallocations.get()
+        );
+    }
+
+    #[test]
+    fn test_allocate_detailed() {
+        use crate::RingAllocError;
+
+        let alloc = RingAlloc::new();
+
+        let layout = Layout::new::<[u64; 4]>();
+        let ptr = alloc.try_allocate_detailed(layout).unwrap();
+        unsafe { alloc.deallocate(ptr.cast(), layout) };
+
+        let huge = Layout::from_size_align(isize::MAX as usize - 7, 8).unwrap();
+        assert_eq!(
+            alloc.try_allocate_detailed(huge),
+            Err(RingAllocError::BackingOom)
+        );
+
+        assert_eq!(
+            alloc.try_allocate_slice_detailed::<u64>(usize::MAX),
+            Err(RingAllocError::Overflow)
+        );
+        assert_eq!(
+            alloc.try_allocate_slice_detailed::<u64>(isize::MAX as usize / 8),
+            Err(RingAllocError::BackingOom)
+        );
+
+        alloc.set_strict(true);
+        let oversized = Layout::new::<[u8; 70000]>();
+        assert_eq!(
+            alloc.try_allocate_detailed(oversized),
+            Err(RingAllocError::LayoutTooLarge)
+        );
+        assert_eq!(
+            alloc.try_allocate_detailed(huge),
+            Err(RingAllocError::LayoutTooLarge)
+        );
+        assert!(alloc.allocate(oversized).is_err());
+
+        // Chunk allocations are not affected by strict mode.
+        let slice = alloc.try_allocate_slice_detailed::<u64>(100).unwrap();
+        unsafe { alloc.deallocate_slice(slice.cast::<u64>(), 100) };
+
+        let err: AllocError = RingAllocError::LayoutTooLarge.into();
+        assert_eq!(err, AllocError);
+        assert_eq!(
+            format!("{}", RingAllocError::BackingOom),
+            "underlying allocator is out of memory"
+        );
+    }
+
+    #[test]
+    fn test_boxed_iter() {
+        let counter = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_in(&counter);
+
+            let boxes = alloc.boxed_iter((0..10000u64).map(|i| [i, i * 2]));
+            assert_eq!(boxes.len(), 10000);
+            assert_eq!(boxes.capacity(), 10000);
+            for (i, b) in boxes.iter().enumerate() {
+                assert_eq!(**b, [i as u64, i as u64 * 2]);
+            }
+
+            // Consecutive boxes are placed back to back.
+            // Block of `[u64; 2]` is one `u64` larger than block of `u64`.
+            let first = &*boxes[0] as *const [u64; 2] as usize;
+            let second = &*boxes[1] as *const [u64; 2] as usize;
+            assert_eq!(second - first, U64_BLOCK_SIZE + 8);
+
+            // Size of the iterator is unknown.
+            let odd = alloc
+                .try_boxed_iter(
+                    (0..10000u32)
+                        .filter(|i| i % 2 == 1)
+                        .map(|i| format!("{}", i)),
+                )
+                .unwrap();
+            assert_eq!(odd.len(), 5000);
+            assert!(odd
+                .iter()
+                .enumerate()
+                .all(|(i, s)| **s == format!("{}", i * 2 + 1)));
+
+            let zsts = alloc.boxed_iter((0..10).map(|_| ()));
+            assert_eq!(zsts.len(), 10);
+        }
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_scratch() {
+        let counter = CountingAllocator::default();
+        {
+            // Previous scratch is on top of the chunk and is rewound.
+            let alloc = RingAlloc::new_lifo_in(&counter);
+            let layout = Layout::new::<[u64; 16]>();
+            let first = alloc.scratch(layout).unwrap();
+            let second = alloc.scratch(layout).unwrap();
+            assert_eq!(first.cast::<u8>(), second.cast::<u8>());
+
+            // Previous scratches are reclaimed with chunks.
+            let alloc = RingAlloc::new_in(&counter);
+            let allocations = counter.allocations.get();
+            for size in (0..10000).map(|i| 1000 + i % 100) {
+                let buffer = alloc.scratch(Layout::array::<u8>(size).unwrap()).unwrap();
+                unsafe { buffer.cast::<u8>().as_ptr().write_bytes(0xAA, size) };
+            }
+            assert!(counter.allocations.get() - allocations <= 2);
+            assert!(!alloc.is_empty());
+
+            // Oversized scratch is deallocated by the next call and on drop.
+            let allocations = counter.allocations.get();
+            alloc.scratch(Layout::new::<[u8; 100000]>()).unwrap();
+            alloc.scratch(Layout::new::<[u8; 100000]>()).unwrap();
+            assert_eq!(counter.allocations.get() - allocations, 2);
+            assert_eq!(counter.deallocations.get(), 1);
+        }
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_allocate_for() {
+        let counter = CountingAllocator::default();
+        {
+            let alloc = RingAlloc::new_in(&counter);
+
+            let tiny = alloc.allocate_for::<u32>().unwrap();
+            let small = alloc.allocate_for::<[u64; 4]>().unwrap();
+            let large = alloc.allocate_for::<[u8; 1000]>().unwrap();
+            let oversized = alloc.allocate_for::<[u8; 100000]>().unwrap();
+            let zst = alloc.allocate_for::<()>().unwrap();
+
+            unsafe {
+                tiny.as_ptr().write(1);
+                small.as_ptr().write([2; 4]);
+                large.as_ptr().write([3; 1000]);
+                oversized.as_ptr().cast::<u8>().write_bytes(4, 100000);
+
+                // Rings, three chunks and oversized block.
+                assert_eq!(counter.allocations.get(), 5);
+
+                assert_eq!(tiny.as_ptr().read(), 1);
+                alloc.deallocate_for(tiny);
+                assert_eq!(small.as_ptr().read(), [2; 4]);
+                alloc.deallocate(small.cast(), Layout::new::<[u64; 4]>());
+                assert_eq!(large.as_ptr().read(), [3; 1000]);
+                alloc.deallocate_for(large);
+                assert!((*oversized.as_ptr()).iter().all(|&b| b == 4));
+                alloc.deallocate_for(oversized);
+                alloc.deallocate_for(zst);
+            }
+
+            // Block allocated with layout is deallocated for type.
+            let ptr = alloc.allocate(Layout::new::<u64>()).unwrap();
+            unsafe { alloc.deallocate_for(ptr.cast::<u64>()) };
+
+            assert!(alloc.is_empty());
+        }
+
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_prefault_head_chunks() {
+        let trimming = TrimmingAllocator::default();
+        let alloc = RingAlloc::new_in(&trimming);
+
+        let layout = Layout::new::<[u8; 1000]>();
+        let block = alloc.allocate(layout).unwrap().cast::<u8>();
+        unsafe { block.as_ptr().write_bytes(0xAA, 1000) };
+
+        let block_addr = block.as_ptr() as usize;
+        let chunk_addr = trimming
+            .blocks
+            .borrow()
+            .iter()
+            .find(|&&(start, size, _)| start <= block_addr && block_addr < start + size)
+            .unwrap()
+            .0;
+
+        // Chunk header, block metadata and the block itself.
+        let used = unsafe {
+            core::slice::from_raw_parts(chunk_addr as *const u8, block_addr + 1000 - chunk_addr)
+        };
+        let before = used.to_vec();
+        let report = alloc.memory_report();
+
+        alloc.prefault_head_chunks();
+
+        assert_eq!(used, &before[..]);
+        assert_eq!(alloc.memory_report(), report);
+
+        // Memory after the cursor is still zeroed for zeroed allocations.
+        let zeroed = alloc.allocate_zeroed(layout).unwrap();
+        assert!(unsafe { zeroed.as_ref() }.iter().all(|&b| b == 0));
+
+        unsafe {
+            alloc.deallocate(zeroed.cast(), layout);
+            alloc.deallocate(block, layout);
+        }
+    }
+
+    #[test]
+    fn test_allocate_near() {
+        let trimming = TrimmingAllocator::default();
+        let alloc = RingAlloc::new_in(&trimming);
+
+        let node = Layout::new::<[u8; 300]>();
+        let big = Layout::new::<[u8; 65536]>();
+        let root = alloc.allocate(node).unwrap().cast::<u8>();
+
+        // Fill the chunk until the ring rotates to the next one.
+        let chunks = trimming.blocks.borrow().len();
+        let mut bigs = Vec::new();
+        while trimming.blocks.borrow().len() == chunks {
+            bigs.push(alloc.allocate(big).unwrap().cast::<u8>());
+        }
+
+        let chunk_of = |ptr: NonNull<u8>| {
+            let addr = ptr.as_ptr() as usize;
+            trimming
+                .blocks
+                .borrow()
+                .iter()
+                .position(|&(start, size, _)| start <= addr && addr < start + size)
+                .unwrap()
+        };
+
+        // Regular allocation goes to the new head chunk.
+        let other = alloc.allocate(node).unwrap().cast::<u8>();
+        assert_ne!(chunk_of(other), chunk_of(root));
+
+        // Children fill the rest of the chunk of the root.
+        let children = (0..8)
+            .map(|_| alloc.allocate_near(root, node).unwrap().cast::<u8>())
+            .collect::<Vec<_>>();
+        for &child in &children {
+            assert_eq!(chunk_of(child), chunk_of(root));
+        }
+
+        // Hint that is not in chunks of the size class is ignored.
+        let tiny = alloc.allocate_near(root, Layout::new::<u8>()).unwrap();
+        unsafe { alloc.deallocate(tiny.cast(), Layout::new::<u8>()) };
+
+        for ptr in children.into_iter().chain([root, other]) {
+            unsafe { alloc.deallocate(ptr, node) };
+        }
+        for ptr in bigs {
+            unsafe { alloc.deallocate(ptr, big) };
+        }
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    fn test_memory_report() {
+        let alloc = RingAlloc::new();
+        assert_eq!(alloc.memory_report(), MemoryReport::default());
+
+        let tiny = (0..10u8)
+            .map(|i| Box::new_in(i, &alloc))
+            .collect::<Vec<_>>();
+        let small = (0..10u64)
+            .map(|i| Box::new_in([i; 4], &alloc))
+            .collect::<Vec<_>>();
+
+        let report = alloc.memory_report();
+        assert_eq!(report.live_blocks, 20);
+        assert_eq!(report.tiny_chunks, 1);
+        assert_eq!(report.small_chunks, 1);
+        assert_eq!(report.large_chunks, 0);
+        assert!(report.live <= report.reserved);
+        assert!(report.overhead > 0);
+        assert!(report.overhead < report.reserved);
+
+        let utilization = report.utilization();
+        assert!((0.0..=1.0).contains(&utilization));
+
+        let text = format!("{}", report);
+        assert!(text.contains("in 20 blocks"));
+
+        drop(tiny);
+        drop(small);
+        let report = alloc.memory_report();
+        assert_eq!(report.live_blocks, 0);
+        assert_eq!(report.live, 0);
+        assert_eq!(report.utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_fragmentation() {
+        let alloc = RingAlloc::new();
+        assert_eq!(alloc.fragmentation(), 0.0);
+
+        let layout = Layout::new::<u64>();
+        let fill = || {
+            (0..U64_BLOCKS_IN_3_CHUNKS)
+                .map(|_| alloc.allocate(layout).unwrap().cast::<u8>())
+                .collect::<Vec<_>>()
+        };
+
+        let blocks = fill();
+        assert!(alloc.fragmentation() < 0.1);
+
+        // Stragglers keep chunks from being reused.
+        for (i, &ptr) in blocks.iter().enumerate() {
+            if i % 1000 != 0 {
+                unsafe { alloc.deallocate(ptr, layout) };
+            }
+        }
+        assert!(alloc.fragmentation() > 0.9);
+
+        unsafe { alloc.reset() };
+        let _blocks = fill();
+        assert!(alloc.fragmentation() < 0.1);
+    }
+
+    #[test]
+    fn test_large_allocator() {
+        let alloc = RingAlloc::new_in(LargeAllocator::new());
+        let boxes = (0..3000u32)
+            .map(|i| Box::new_in(i, &alloc))
+            .collect::<Vec<_>>();
+        for (i, b) in boxes.iter().enumerate() {
+            assert_eq!(**b, i as u32);
+        }
+        drop(boxes);
+
+        let inline = InlineRingAlloc::new_in(LargeAllocator::new());
+        drop(Box::new_in(0u32, &inline));
+    }
+
+    #[test]
+    fn test_merge_frees() {
+        let arena = ArenaAllocator::new();
+        let alloc = RingAlloc::new_in(&arena);
+        unsafe { alloc.set_merge_frees(true) };
+
+        let layout = Layout::new::<u64>();
+        let mut blocks = Vec::new();
+        while arena.used.get() < ArenaAllocator::CHUNK_SIZE * 3 {
+            blocks.push(alloc.allocate(layout).unwrap());
+        }
+        for block in blocks {
+            unsafe { alloc.deallocate(block.cast(), layout) };
+        }
+
+        alloc.flush();
+
+        // All chunks are freed and adjacent ones are freed together.
+        let freed = arena.freed.borrow().clone();
+        assert_eq!(freed.iter().sum::<usize>(), ArenaAllocator::CHUNK_SIZE * 3);
+        assert!(freed.len() < 3, "{:?}", freed);
+
+        // Ring is still usable after clean.
+        drop(Box::new_in(1u64, alloc));
+    }
+
+    #[test]
+    fn test_available_contiguous() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::from_size_align(65536, 8).unwrap();
+
+        // No chunks yet.
+        assert!(!alloc.available_contiguous(layout));
+
+        let mut blocks = Vec::new();
+        blocks.push(alloc.allocate(layout).unwrap());
+
+        // Nearly fill the head chunk.
+        while alloc.available_contiguous(layout) {
+            blocks.push(alloc.allocate(layout).unwrap());
+            assert!(blocks.len() < 32);
+        }
+
+        // Large request doesn't fit anymore, but small one still does.
+        assert!(!alloc.available_contiguous(layout));
+        assert!(alloc.available_contiguous(Layout::from_size_align(1024, 8).unwrap()));
+
+        // Oversized layouts are never served from chunks.
+        assert!(!alloc.available_contiguous(Layout::from_size_align(65537, 8).unwrap()));
+
+        for block in blocks {
+            unsafe { alloc.deallocate(block.cast(), layout) };
+        }
+    }
+
+    #[test]
+    fn test_allocate_in_head_only() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let layout = Layout::from_size_align(65536, 8).unwrap();
+
+        // No head chunk yet.
+        assert!(alloc.try_allocate_in_head_only(layout).is_none());
+
+        let mut blocks = Vec::new();
+        blocks.push(alloc.allocate(layout).unwrap());
+        let allocations = counter.allocations.get();
+
+        // Fill the head chunk.
+        while let Some(block) = alloc.try_allocate_in_head_only(layout) {
+            blocks.push(block);
+            assert!(blocks.len() < 32);
+        }
+
+        // Underlying allocator is never called.
+        assert!(alloc.try_allocate_in_head_only(layout).is_none());
+        assert!(alloc
+            .try_allocate_in_head_only(Layout::from_size_align(65537, 8).unwrap())
+            .is_none());
+        assert_eq!(counter.allocations.get(), allocations);
+
+        for block in blocks {
+            unsafe { alloc.deallocate(block.cast(), layout) };
+        }
+    }
+
+    #[test]
+    fn test_reserve_chunks() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let layout = Layout::new::<u64>();
+
+        alloc.reserve_chunks(layout, 3).unwrap();
+        assert_eq!(counter.allocations.get(), 4);
+
+        // Burst that spans all reserved chunks.
+        let blocks = (0..U64_BLOCKS_IN_3_CHUNKS)
+            .map(|_| alloc.allocate(layout).unwrap())
+            .collect::<Vec<_>>();
+
+        // `Rings` and 3 reserved chunks.
+        assert_eq!(counter.allocations.get(), 4);
+
+        for block in blocks {
+            unsafe { alloc.deallocate(block.cast(), layout) };
+        }
+    }
+
+    #[test]
+    fn test_alignments() {
+        let alloc = RingAlloc::new();
+
+        for align in (0..16).map(|shift| 1usize << shift) {
+            for size in [0, 1, 7, 8, 24, align, align * 3] {
+                let layout = Layout::from_size_align(size, align).unwrap();
+                let blocks = (0..10)
+                    .map(|i| {
+                        let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+                        unsafe { ptr.as_ptr().write_bytes(i, size) };
+                        ptr
+                    })
+                    .collect::<Vec<_>>();
+
+                for (i, ptr) in blocks.into_iter().enumerate() {
+                    let bytes = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), size) };
+                    assert!(bytes.iter().all(|&b| b == i as u8));
+                    unsafe { alloc.deallocate(ptr, layout) };
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_align_dominated_routing() {
+        for size in [1, 8, 64] {
+            for align in [16, 256, 4096] {
+                let layout = Layout::from_size_align(size, align).unwrap();
+                let chunk_size = match size.max(align) {
+                    0..=16 => 16384,
+                    17..=256 => 65536,
+                    _ => 2097152,
+                };
+                let underlying = TrimmingAllocator::default();
+                let alloc = RingAlloc::new_in(&underlying);
+
+                let blocks = (0..10)
+                    .map(|i| {
+                        let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+                        unsafe { ptr.as_ptr().write_bytes(i, size) };
+                        ptr
+                    })
+                    .collect::<Vec<_>>();
+
+                // Layout is routed by the larger of its size and alignment.
+                let chunk_sizes = underlying
+                    .blocks
+                    .borrow()
+                    .iter()
+                    .skip(1)
+                    .map(|&(_, original, _)| original)
+                    .collect::<Vec<_>>();
+                assert!(!chunk_sizes.is_empty());
+                assert!(
+                    chunk_sizes.iter().all(|&s| s == chunk_size),
+                    "size {}, align {}: chunks {:?}",
+                    size,
+                    align,
+                    chunk_sizes
+                );
+
+                for (i, ptr) in blocks.into_iter().enumerate() {
+                    let bytes = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), size) };
+                    assert!(bytes.iter().all(|&b| b == i as u8));
+                    unsafe { alloc.deallocate(ptr, layout) };
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "aligned-chunks")]
+    #[cfg(not(feature = "debug-redzone"))]
+    fn test_aligned_chunks_no_overhead() {
+        let alloc = RingAlloc::new();
+
+        // Overaligned blocks are packed without padding.
+        let layout = Layout::from_size_align(32, 32).unwrap();
+        let a = alloc.allocate(layout).unwrap().cast::<u8>();
+        let b = alloc.allocate(layout).unwrap().cast::<u8>();
+        assert_eq!(b.as_ptr() as usize - a.as_ptr() as usize, 32);
+
+        unsafe {
+            alloc.deallocate(a, layout);
+            alloc.deallocate(b, layout);
+        }
+    }
+
+    /// Layouts at and just past size class boundaries.
+    fn boundary_layouts() -> impl Iterator<Item = Layout> {
+        [16, 256, 65536].iter().flat_map(|&max| {
+            [
+                Layout::from_size_align(max, 1).unwrap(),
+                Layout::from_size_align(max, max).unwrap(),
+                Layout::from_size_align(1, max).unwrap(),
+                Layout::from_size_align(max + 1, 1).unwrap(),
+            ]
+        })
+    }
+
+    #[test]
+    fn test_size_class_boundaries() {
+        let alloc = RingAlloc::new();
+        let inline = InlineRingAlloc::new();
+
+        // Fresh chunks fit largest layouts of their size class.
+        for layout in boundary_layouts() {
+            let a = alloc.allocate(layout).unwrap().cast::<u8>();
+            let b = inline.allocate(layout).unwrap().cast::<u8>();
+            assert_eq!(a.as_ptr() as usize % layout.align(), 0);
+            assert_eq!(b.as_ptr() as usize % layout.align(), 0);
+            unsafe {
+                alloc.deallocate(a, layout);
+                inline.deallocate(b, layout);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eager_reclaim() {
+        fn burst<A: Allocator>(alloc: &RingAlloc<A>) {
+            let layout = Layout::new::<u64>();
+            let blocks = (0..U64_BLOCKS_IN_3_CHUNKS)
+                .map(|_| alloc.allocate(layout).unwrap())
+                .collect::<Vec<_>>();
+
+            // Free in allocation order, so chunks become unused one by one.
+            for block in blocks {
+                unsafe { alloc.deallocate(block.cast(), layout) };
+            }
+        }
+
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        burst(&alloc);
+        assert_eq!(counter.deallocations.get(), 0);
+
+        let eager_counter = CountingAllocator::default();
+        let eager = RingAlloc::new_eager_in(&eager_counter);
+        burst(&eager);
+
+        // All chunks except the head one are freed.
+        assert_eq!(eager_counter.allocations.get(), 4);
+        assert_eq!(eager_counter.deallocations.get(), 2);
+
+        // Ring stays consistent after reclaim.
+        burst(&eager);
+        drop(eager);
+        assert_eq!(
+            eager_counter.allocations.get(),
+            eager_counter.deallocations.get()
+        );
+    }
+
+    #[test]
+    fn test_unlink_middle_chunk() {
+        let alloc = RingAlloc::new_eager_in(Global);
+        let layout = Layout::new::<u64>();
+
+        let mut blocks = (0..U64_BLOCKS_IN_3_CHUNKS)
+            .map(|_| alloc.allocate(layout).unwrap().cast::<u8>())
+            .collect::<Vec<_>>();
+        let chunks = alloc.check_rings();
+        assert!(chunks >= 3);
+
+        // Blocks are contiguous within a chunk.
+        let mut starts = (1..blocks.len()).filter(|&i| {
+            blocks[i].as_ptr() as usize != blocks[i - 1].as_ptr() as usize + U64_BLOCK_SIZE
+        });
+        let (first, second) = (starts.next().unwrap(), starts.next().unwrap());
+
+        // Free all blocks of the middle chunk, so it is unlinked.
+        for block in blocks.drain(first..second) {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        assert_eq!(alloc.check_rings(), chunks - 1);
+
+        blocks.extend((0..1000).map(|_| alloc.allocate(layout).unwrap().cast()));
+        alloc.check_rings();
+
+        for block in blocks {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        assert_eq!(alloc.check_rings(), 1);
+
+        alloc.reserve_chunks(layout, 2).unwrap();
+        assert_eq!(alloc.check_rings(), 3);
+
+        alloc.flush();
+        assert_eq!(alloc.check_rings(), 0);
+    }
+
+    #[test]
+    fn test_reclaim_chunk() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let layout = Layout::new::<u64>();
+
+        let mut blocks = (0..U64_BLOCKS_IN_3_CHUNKS)
+            .map(|_| alloc.allocate(layout).unwrap().cast::<u8>())
+            .collect::<Vec<_>>();
+        let chunks = alloc.check_rings();
+        assert!(chunks >= 3);
+
+        let mut starts = (1..blocks.len()).filter(|&i| {
+            blocks[i].as_ptr() as usize != blocks[i - 1].as_ptr() as usize + U64_BLOCK_SIZE
+        });
+        let (first, second) = (starts.next().unwrap(), starts.next().unwrap());
+
+        // Chunk with allocated blocks is kept.
+        let last = blocks.remove(second - 1);
+        for block in blocks.drain(first..second - 1) {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        assert!(!unsafe { alloc.reclaim_chunk(last, layout) });
+        assert_eq!(alloc.check_rings(), chunks);
+
+        unsafe { alloc.deallocate(last, layout) };
+        assert!(unsafe { alloc.reclaim_chunk(last, layout) });
+        assert_eq!(counter.deallocations.get(), 1);
+        assert_eq!(alloc.check_rings(), chunks - 1);
+
+        // Ring stays usable after reclaim.
+        blocks.extend((0..1000).map(|_| alloc.allocate(layout).unwrap().cast()));
+        alloc.check_rings();
+
+        for block in blocks {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    #[cfg(not(feature = "aligned-chunks"))]
+    fn test_growth_factor() {
+        let trimming = TrimmingAllocator::default();
+        let alloc = RingAlloc::new_in(&trimming);
+        alloc.set_growth_factor(2);
+        let layout = Layout::new::<u64>();
+
+        // First block holds the rings.
+        let mut blocks = Vec::new();
+        while trimming.blocks.borrow().len() < 5 {
+            blocks.push(alloc.allocate(layout).unwrap().cast::<u8>());
+        }
+        let sizes = trimming
+            .blocks
+            .borrow()
+            .iter()
+            .skip(1)
+            .map(|&(_, size, _)| size)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [16384, 32768, 65536, 131072]);
+
+        // Each size class grows on its own.
+        let small = Layout::new::<[u8; 100]>();
+        let small_block = alloc.allocate(small).unwrap().cast::<u8>();
+        assert_eq!(trimming.blocks.borrow()[5].1, 65536);
+
+        // Blocks of heterogeneous chunks are routed back to their rings.
+        for block in blocks.drain(..) {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        unsafe { alloc.deallocate(small_block, small) };
+        assert!(alloc.is_empty());
+        assert_eq!(alloc.check_rings(), 5);
+
+        blocks.extend((0..U64_BLOCKS_IN_3_CHUNKS).map(|_| alloc.allocate(layout).unwrap().cast()));
+        for block in blocks {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+
+        // Growth is capped.
+        alloc.set_growth_factor(usize::MAX);
+        let block = alloc.allocate(small).unwrap();
+        unsafe { alloc.deallocate(block.cast(), small) };
+        alloc.reserve_chunks(small, 1).unwrap();
+        assert_eq!(trimming.blocks.borrow().last().unwrap().1, 65536 * 16);
+
+        drop(alloc);
+        assert!(trimming.blocks.borrow().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "front-reuse")]
+    fn test_front_reuse() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let layout = Layout::new::<u64>();
+
+        // Fill the first chunk.
+        let mut blocks = Vec::new();
+        blocks.push(alloc.allocate(layout).unwrap().cast::<u8>());
+        let allocations = counter.allocations.get();
+        loop {
+            let block = alloc.allocate(layout).unwrap();
+            if counter.allocations.get() > allocations {
+                unsafe { alloc.deallocate(block.cast(), layout) };
+                break;
+            }
+            blocks.push(block.cast::<u8>());
+        }
+        assert_eq!(alloc.check_rings(), 2);
+
+        // Keep only the last block of the first chunk alive.
+        let last = blocks.pop().unwrap();
+        for block in blocks.drain(..) {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+
+        // Front of the first chunk is reused without new chunks.
+        let allocations = counter.allocations.get();
+        for _ in 0..U64_BLOCKS_IN_3_CHUNKS / 2 {
+            blocks.push(alloc.allocate(layout).unwrap().cast());
+        }
+        assert_eq!(counter.allocations.get(), allocations);
+
+        for block in blocks.drain(..) {
+            unsafe { alloc.deallocate(block, layout) };
+        }
+        unsafe { alloc.deallocate(last, layout) };
+    }
+
+    #[test]
+    #[cfg(feature = "front-reuse")]
+    fn test_front_reuse_interleaved() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+
+        // Short-lived boxes freed in FIFO order with rare long-lived ones.
+        let mut window = alloc::collections::VecDeque::new();
+        let mut long_lived = Vec::new();
+        for i in 0..100000usize {
+            let b = Box::new_in(i, &alloc);
+            if i % 10000 == 0 {
+                long_lived.push(b);
+            } else {
+                window.push_back(b);
+            }
+            if window.len() > 100 {
+                let b = window.pop_front().unwrap();
+                assert!(*b % 10000 != 0);
+                drop(b);
+            }
+        }
+
+        // Each long-lived box pins at most one chunk.
+        assert!(counter.allocations.get() <= long_lived.len() + 1);
+
+        for (i, b) in long_lived.iter().enumerate() {
+            assert_eq!(**b, i * 10000);
+        }
+        for b in window.iter() {
+            assert!(**b % 10000 != 0);
+        }
+        alloc.check_rings();
+    }
+
+    #[test]
+    fn test_unaligned_cursor() {
+        let alloc = RingAlloc::new();
+
+        // Odd sizes leave cursor unaligned for the following allocations.
+        let layouts = [
+            Layout::new::<u8>(),
+            Layout::new::<u64>(),
+            Layout::new::<[u8; 3]>(),
+            Layout::new::<u32>(),
+            Layout::new::<u16>(),
+            Layout::new::<usize>(),
+        ];
+
+        let mut blocks = Vec::new();
+        for (idx, layout) in layouts.iter().cycle().take(600).enumerate() {
+            let ptr = alloc.allocate(*layout).unwrap().cast::<u8>();
+            assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+            unsafe { ptr.as_ptr().write_bytes(idx as u8, layout.size()) };
+            blocks.push((ptr, *layout, idx as u8));
+        }
+
+        // Blocks don't overlap.
+        for (ptr, layout, byte) in blocks {
+            unsafe {
+                let bytes = core::slice::from_raw_parts(ptr.as_ptr(), layout.size());
+                assert!(bytes.iter().all(|b| *b == byte));
+                alloc.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[cfg(not(feature = "aligned-chunks"))]
+    #[should_panic(expected = "different size class")]
+    fn test_deallocate_size_class_mismatch() {
+        let alloc = RingAlloc::new();
+        let ptr = alloc.allocate(Layout::new::<[u64; 4]>()).unwrap();
+
+        // Allocated in small chunk, deallocated as tiny block.
+        // Opposite is valid for blocks grown in place.
+        unsafe { alloc.deallocate(ptr.cast(), Layout::new::<u64>()) };
+    }
+
+    #[test]
+    fn test_handle_count() {
+        let alloc = RingAlloc::new();
+        assert_eq!(alloc.handle_count(), 1);
+
+        let a = alloc.clone();
+        let b = Box::new_in(1u8, alloc.clone());
+        assert_eq!(alloc.handle_count(), 3);
+
+        drop(a);
+        assert_eq!(alloc.handle_count(), 2);
+
+        drop(b);
+        assert_eq!(alloc.handle_count(), 1);
+    }
+
+    #[test]
+    fn test_size_rounding() {
+        let alloc = RingAlloc::with_size_rounding_in(Global, 16);
+        let ten = Layout::new::<[u8; 10]>();
+        let fifteen = Layout::new::<[u8; 15]>();
+
+        let a = alloc.allocate(ten).unwrap();
+        let b = alloc.allocate(fifteen).unwrap();
+        assert_eq!((a.len(), b.len()), (16, 16));
+
+        // Blocks of both sizes occupy the same slots after reset.
+        unsafe { alloc.reset() };
+        let c = alloc.allocate(fifteen).unwrap();
+        let d = alloc.allocate(ten).unwrap();
+        assert_eq!(c.cast::<u8>(), a.cast::<u8>());
+        assert_eq!(d.cast::<u8>(), b.cast::<u8>());
+
+        unsafe {
+            alloc.deallocate(d.cast(), ten);
+            alloc.deallocate(c.cast(), fifteen);
+        }
+
+        // Rounding stops at the boundary of the size class.
+        let alloc = RingAlloc::with_size_rounding_in(Global, 64);
+        let tiny = alloc.allocate(ten).unwrap();
+        let small = alloc.allocate(Layout::new::<[u8; 250]>()).unwrap();
+        let large = alloc.allocate(Layout::new::<[u8; 65500]>()).unwrap();
+        assert_eq!((tiny.len(), small.len(), large.len()), (16, 256, 65536));
+
+        unsafe {
+            alloc.deallocate(tiny.cast(), ten);
+            alloc.deallocate(small.cast(), Layout::new::<[u8; 250]>());
+            alloc.deallocate(large.cast(), Layout::new::<[u8; 65500]>());
+        }
+        assert_eq!(alloc.memory_report().live, 0);
+    }
+
+    #[test]
+    fn test_strict() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let oversized = Layout::from_size_align(65537, 1).unwrap();
+
+        let ptr = alloc.allocate(oversized).unwrap();
+        alloc.set_strict(true);
+
+        let allocations = counter.allocations.get();
+        assert_eq!(alloc.allocate(oversized), Err(AllocError));
+        assert_eq!(alloc.allocate_zeroed(oversized), Err(AllocError));
+        assert_eq!(counter.allocations.get(), allocations);
+
+        // Blocks that fit into rings are not affected.
+        let layout = Layout::from_size_align(65536, 1).unwrap();
+        let block = alloc.allocate(layout).unwrap();
+
+        unsafe {
+            alloc.deallocate(block.cast(), layout);
+            alloc.deallocate(ptr.cast(), oversized);
+        }
+    }
+
+    #[test]
+    fn test_overflow_allocator() {
+        // Overflow allocator must outlive the rings.
+        let overflow: &'static CountingAllocator = alloc::boxed::Box::leak(Default::default());
+
+        // Rings and one tiny chunk.
+        let limited = LimitedAllocator::allocations(2);
+        let alloc = RingAlloc::new_in(&limited);
+
+        let mut boxes = Vec::new();
+        let exhausted =
+            (0..U64_BLOCKS_IN_3_CHUNKS).any(|i| match Box::try_new_in(i as u64, &alloc) {
+                Ok(b) => {
+                    boxes.push(b);
+                    false
+                }
+                Err(AllocError) => true,
+            });
+        assert!(exhausted);
+        assert_eq!(overflow.allocations.get(), 0);
+
+        alloc.set_overflow_allocator(crate::SizeClass::Tiny, Some(overflow));
+
+        // Allocations continue in chunks from overflow allocator.
+        for i in 0..U64_BLOCKS_IN_3_CHUNKS {
+            boxes.push(Box::new_in(i as u64, &alloc));
+        }
+        assert!(overflow.allocations.get() >= 2);
+        let first = boxes.len() - U64_BLOCKS_IN_3_CHUNKS;
+        assert!(boxes[first..]
+            .iter()
+            .enumerate()
+            .all(|(i, b)| **b == i as u64));
+
+        // Other size classes have no overflow allocator.
+        assert!(alloc.allocate(Layout::new::<[u64; 8]>()).is_err());
+
+        drop(boxes);
+        drop(alloc);
+
+        // Chunks are returned to the allocator they came from.
+        assert_eq!(overflow.deallocations.get(), overflow.allocations.get());
+    }
+
+    #[test]
+    fn test_clean_on_exhaustion() {
+        // Separately allocated rings and three tiny chunks.
+        let budget = LimitedAllocator::live(4);
+        let alloc = RingAlloc::new_in(&budget);
+
+        let mut boxes = Vec::new();
+        while let Ok(b) = Box::try_new_in(0u64, &alloc) {
+            boxes.push(b);
+        }
+        assert_eq!(budget.left.get(), 0);
+        assert!(alloc.memory_report().tiny_chunks > 1);
+
+        // Tiny chunks are unused but still held by the ring.
+        drop(boxes);
+        assert_eq!(budget.left.get(), 0);
+
+        // Large chunk is allocated after tiny chunks are freed.
+        let layout = Layout::new::<[u8; 60000]>();
+        let ptr = alloc.allocate(layout).unwrap();
+        assert_eq!(alloc.memory_report().tiny_chunks, 0);
+        assert_eq!(alloc.memory_report().large_chunks, 1);
+
+        unsafe { alloc.deallocate(ptr.cast(), layout) };
+    }
+
+    #[test]
+    #[cfg(feature = "debug-validate")]
+    #[should_panic(expected = "deallocated twice")]
+    fn test_validate_double_free() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u64>();
+
+        let a = alloc.allocate(layout).unwrap().cast::<u8>();
+        let b = alloc.allocate(layout).unwrap().cast::<u8>();
+        unsafe {
+            alloc.deallocate(a, layout);
+            alloc.deallocate(b, layout);
+            alloc.deallocate(a, layout);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "debug-validate")]
+    #[should_panic(expected = "overlaps live block")]
+    fn test_validate_overlap() {
+        /// Broken underlying allocator that hands out the same memory
+        /// to every oversized allocation.
+        struct AliasingAllocator {
+            block: Cell<Option<NonNull<[u8]>>>,
+        }
+
+        unsafe impl Allocator for AliasingAllocator {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                if layout.size() <= 65536 {
+                    return Global.allocate(layout);
+                }
+                if self.block.get().is_none() {
+                    self.block.set(Some(Global.allocate(layout)?));
+                }
+                Ok(self.block.get().unwrap())
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                if layout.size() <= 65536 {
+                    unsafe { Global.deallocate(ptr, layout) }
+                }
+            }
+        }
+
+        let aliasing = AliasingAllocator {
+            block: Cell::new(None),
+        };
+        let alloc = RingAlloc::new_in(&aliasing);
+        let layout = Layout::from_size_align(100000, 8).unwrap();
+
+        let _a = alloc.allocate(layout).unwrap();
+        let _b = alloc.allocate(layout).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "debug-validate")]
+    fn test_validate_reset() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u64>();
+
+        let committed = alloc.allocate(layout).unwrap().cast::<u8>();
+        alloc.commit();
+        let _scratch = alloc.allocate(layout).unwrap();
+
+        // Blocks reset together with the chunk are forgotten,
+        // so reused memory doesn't overlap them.
+        unsafe { alloc.reset() };
+        let reused = alloc.allocate(layout).unwrap().cast::<u8>();
+
+        // Committed block is still live.
+        unsafe {
+            alloc.deallocate(reused, layout);
+            alloc.deallocate(committed, layout);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "debug-redzone")]
+    #[should_panic(expected = "corrupted")]
+    fn test_redzone_overrun() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<[u8; 8]>();
+
+        let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+        unsafe {
+            // Write one byte past the end of the block.
+            ptr.as_ptr().write_bytes(0, 9);
+            alloc.deallocate(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn test_weak() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let weak = alloc.downgrade();
+        let weak2 = weak.clone();
+        assert_eq!(alloc.handle_count(), 1);
+
+        let upgraded = weak.upgrade().unwrap();
+        assert!(upgraded == alloc);
+        assert_eq!(alloc.handle_count(), 2);
+        drop(Box::new_in(1u32, upgraded));
+
+        let b = Box::new_in(2u32, alloc);
+        drop(b);
+
+        // Chunks are freed with last strong handle, the rings are kept for weak handles.
+        assert!(weak.upgrade().is_none());
+        assert_eq!(counter.allocations.get(), counter.deallocations.get() + 1);
+
+        drop(weak);
+        assert!(weak2.upgrade().is_none());
+        drop(weak2);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_collection_owns_last_handle() {
+        let counter = CountingAllocator::default();
+
+        let (v, b, nested, big, weak) = {
+            let alloc = RingAlloc::new_in(&counter);
+
+            let mut v = Vec::new_in(alloc.clone());
+            v.extend(0..1000u64);
+
+            let b = Box::new_in([1u64; 4], alloc.clone());
+
+            let mut nested = Vec::new_in(alloc.clone());
+            nested.extend((0..10).map(|i| Box::new_in(i, alloc.clone())));
+
+            let mut big = Vec::with_capacity_in(100000, alloc.clone());
+            big.resize(100000, 0u8);
+
+            (v, b, nested, big, alloc.downgrade())
+        };
+
+        // Each collection deallocates its memory before dropping the last handle.
+        drop(v);
+        drop(b);
+        drop(big);
+        assert!(weak.upgrade().is_some());
+        drop(nested);
+        assert!(weak.upgrade().is_none());
+
+        drop(weak);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_leak_raw() {
+        let counter = CountingAllocator::default();
+        let value = Rc::new([1u64, 2, 3]);
+
+        let (ptr, handle) = {
+            let alloc = RingAlloc::new_in(&counter);
+            alloc.leak_raw(value.clone())
+        };
+        assert_eq!(Rc::strong_count(&value), 2);
+
+        // Handle keeps the memory alive after the original one is dropped.
+        assert_eq!(*unsafe { ptr.as_ref() }, value);
+        let zst = handle.leak_raw(()).0;
+
+        unsafe {
+            handle.reclaim_raw(ptr);
+            handle.reclaim_raw(zst);
+        }
+        assert_eq!(Rc::strong_count(&value), 1);
+        assert!(handle.is_empty());
+
+        drop(handle);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_boxed_slice() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+
+        // Empty slices and slices of zero-sized values take zero-sized blocks.
+        let empty = alloc.boxed_slice(String::from("a"), 0);
+        let units = alloc.boxed_slice((), 1000);
+        assert!(empty.is_empty());
+        assert_eq!(units.len(), 1000);
+        assert_eq!(alloc.memory_report().tiny_chunks, 1);
+
+        let small = alloc.boxed_slice(3u32, 50);
+        assert!(small.iter().all(|v| *v == 3));
+        assert_eq!(alloc.memory_report().small_chunks, 1);
+
+        // Slices that don't fit into the rest of the chunk are placed into the next one.
+        let slices = (0..40u8)
+            .map(|i| alloc.boxed_slice(i, 60000))
+            .collect::<Vec<_>>();
+        assert!(slices
+            .iter()
+            .enumerate()
+            .all(|(i, s)| s.len() == 60000 && s.iter().all(|v| *v == i as u8)));
+        assert_eq!(alloc.memory_report().large_chunks, 2);
+
+        // Oversized slice is allocated from underlying allocator.
+        let allocations = counter.allocations.get();
+        let large = alloc.boxed_slice(5u64, 10000);
+        assert!(large.iter().all(|v| *v == 5));
+        assert_eq!(counter.allocations.get(), allocations + 1);
+        assert_eq!(alloc.memory_report().chunks(), 4);
+
+        drop((empty, units, small, slices, large));
+        assert!(alloc.is_empty());
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_allocate_with_trailing() {
+        struct Header {
+            len: usize,
+            tag: u8,
+        }
+
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+
+        let (header, values) = alloc.allocate_with_trailing::<Header, u32>(10).unwrap();
+        unsafe {
+            header.as_ptr().write(Header { len: 10, tag: 7 });
+            let values = &mut *values.as_ptr();
+            for (i, v) in values.iter_mut().enumerate() {
+                *v = i as u32 * 3;
+            }
+        }
+
+        // Values follow the header in the same block.
+        let start = header.as_ptr() as usize;
+        let values_start = values.cast::<u32>().as_ptr() as usize;
+        assert!(values_start >= start + core::mem::size_of::<Header>());
+        assert_eq!(values_start % core::mem::align_of::<u32>(), 0);
+
+        unsafe {
+            let header = &*header.as_ptr();
+            let values = &*values.as_ptr();
+            assert_eq!((header.len, header.tag), (10, 7));
+            assert_eq!(values.len(), header.len);
+            assert_eq!(values[9], 27);
+        }
+
+        // Block with many trailing values is oversized.
+        let allocations = counter.allocations.get();
+        let (big, _) = alloc.allocate_with_trailing::<Header, u64>(10000).unwrap();
+        assert_eq!(counter.allocations.get(), allocations + 1);
+
+        assert!(alloc
+            .allocate_with_trailing::<Header, u64>(usize::MAX / 8)
+            .is_err());
+
+        unsafe {
+            alloc.deallocate_with_trailing::<Header, u64>(big, 10000);
+            alloc.deallocate_with_trailing::<Header, u32>(header, 10);
+        }
+        assert!(alloc.is_empty());
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_lazy() {
+        let counter = CountingAllocator::default();
+
+        // Unused lazy ring-allocator allocates nothing.
+        let lazy = RingAlloc::lazy_in(&counter);
+        assert!(lazy.get().is_none());
+        drop(lazy);
+        assert_eq!(counter.allocations.get(), 0);
+
+        // While eager one allocates its rings right away.
+        drop(RingAlloc::new_in(&counter));
+        assert_eq!(counter.allocations.get(), 1);
+
+        let lazy = RingAlloc::lazy_in(&counter);
+        let mut v = Vec::new_in(&lazy);
+        v.extend(0..100u32);
+        assert!(lazy.get().is_some());
+        assert_eq!(v.iter().sum::<u32>(), 4950);
+
+        drop(v);
+        assert!(lazy.get().unwrap().is_empty());
+        drop(lazy);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    fn test_size_classes() {
+        type SmallOnly = crate::Classes<false, true, false>;
+
+        let alloc = RingAlloc::<Global, SmallOnly>::with_classes_in(Global);
+
+        // Tiny block is served by small ring.
+        let tiny = Box::new_in(7u8, alloc.clone());
+        let small = Box::new_in([1u64; 8], alloc.clone());
+        assert_eq!((*tiny, *small), (7, [1; 8]));
+
+        let report = alloc.memory_report();
+        assert_eq!(report.tiny_chunks, 0);
+        assert_eq!(report.small_chunks, 1);
+        assert_eq!(report.live_blocks, 2);
+        assert_eq!(alloc.check_rings(), 1);
+
+        // Large block is served by underlying allocator.
+        let counter = CountingAllocator::default();
+        let only = RingAlloc::<_, SmallOnly>::with_classes_in(&counter);
+        let large = Box::new_in([0u8; 1024], only.clone());
+        assert_eq!(counter.allocations.get(), 2);
+        assert_eq!(only.memory_report().chunks(), 0);
+
+        drop((tiny, small, large));
+        assert!(alloc.is_empty());
+
+        // Rings of disabled classes take no space.
+        assert!(
+            core::mem::size_of::<InlineRingAlloc<Global, SmallOnly>>()
+                < core::mem::size_of::<InlineRingAlloc<Global>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn test_rotation_counts() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u64>();
+
+        // Each round keeps all its blocks live, so they span several chunks.
+        // After the first round chunks are reused and full head is rotated to the tail.
+        for _ in 0..10 {
+            let blocks = (0..5000)
+                .map(|_| alloc.allocate(layout).unwrap())
+                .collect::<Vec<_>>();
+            assert!(alloc.memory_report().tiny_chunks > 1);
+
+            for ptr in blocks {
+                unsafe { alloc.deallocate(ptr.cast(), layout) };
+            }
+        }
+
+        let [tiny, small, large] = alloc.rotation_counts();
+        assert!(tiny >= 9);
+        assert_eq!((small, large), (0, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn test_rotation_counts_large_chunk() {
+        let alloc = RingAlloc::new();
+
+        // Few allocations fit into the first large chunk.
+        let blocks = (0..10)
+            .map(|i| Box::new_in([i as u8; 1000], alloc.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(alloc.memory_report().large_chunks, 1);
+        assert_eq!(alloc.rotation_counts(), [0; 3]);
+
+        drop(blocks);
+    }
+
+    #[test]
+    fn test_inline() {
+        fn workload<A: Allocator>(alloc: A) {
+            let mut v = Vec::new_in(alloc);
+            v.extend(0..1000u32);
+            assert_eq!(v.iter().sum::<u32>(), 499500);
+        }
+
+        let ring_counter = CountingAllocator::default();
+        workload(RingAlloc::new_in(&ring_counter));
+
+        let inline_counter = CountingAllocator::default();
+        workload(InlineRingAlloc::new_in(&inline_counter));
+
+        // No allocation for rings.
+        assert_eq!(
+            inline_counter.allocations.get() + 1,
+            ring_counter.allocations.get()
+        );
+        assert_eq!(
+            inline_counter.allocations.get(),
+            inline_counter.deallocations.get()
+        );
+    }
+
+    #[test]
+    fn test_arena_vec_grows_in_place() {
+        let mut vec = ArenaVec::with_capacity_in(100, RingAlloc::new());
+        let ptr = vec.as_ptr();
+
+        // Stays within the large size class.
+        for i in 0..8000u64 {
+            vec.push(i);
+        }
+
+        // Elements were never moved.
+        assert_eq!(vec.as_ptr(), ptr);
+        assert!(vec.iter().copied().eq(0..8000));
+    }
+
+    #[test]
+    fn test_arena_vec_drops() {
+        let counter = Rc::new(());
+
+        let mut vec = ArenaVec::new();
+        for _ in 0..1000 {
+            vec.push(counter.clone());
+        }
+
+        // Allocation from the same chunk prevents in-place growth.
+        let b = Box::new_in(1u32, vec.allocator().clone());
+        vec.extend((0..1000).map(|_| counter.clone()));
+        assert_eq!(Rc::strong_count(&counter), 2001);
+
+        vec.truncate(10);
+        assert_eq!(Rc::strong_count(&counter), 11);
+        assert_eq!(vec.pop().map(|rc| Rc::ptr_eq(&rc, &counter)), Some(true));
+
+        drop(vec);
+        drop(b);
+        assert_eq!(Rc::strong_count(&counter), 1);
+
+        let mut zst = ArenaVec::new();
+        for _ in 0..100 {
+            zst.push(());
+        }
+        assert_eq!(zst.len(), 100);
+    }
+
+    #[test]
+    fn test_dropping_ring() {
+        use crate::DroppingRing;
+
+        struct Tracked(Rc<Cell<usize>>);
+
+        impl Drop for Tracked {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let mut arena = DroppingRing::new();
+
+        for _ in 0..1000 {
+            arena.alloc(Tracked(drops.clone()));
+        }
+        let plain = arena.alloc(42u64);
+        *plain += 1;
+        assert_eq!(*plain, 43);
+
+        // Values without destructors are not recorded.
+        assert_eq!(arena.pending_drops(), 1000);
+        assert_eq!(drops.get(), 0);
+
+        arena.reset();
+        assert_eq!(drops.get(), 1000);
+        assert_eq!(arena.pending_drops(), 0);
+        assert_eq!(Rc::strong_count(&drops), 1);
+
+        // Reset twice doesn't run destructors again.
+        arena.reset();
+        assert_eq!(drops.get(), 1000);
+
+        let value = arena.alloc(Tracked(drops.clone()));
+        assert_eq!(value.0.get(), 1000);
+        drop(arena);
+        assert_eq!(drops.get(), 1001);
+    }
+
+    #[test]
+    fn test_dropping_ring_oversized() {
+        use crate::DroppingRing;
+
+        struct Large(Rc<Cell<usize>>, [u8; 100_000]);
+
+        impl Drop for Large {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let counting = CountingAllocator::default();
+        let live = || counting.allocations.get() - counting.deallocations.get();
+
+        let drops = Rc::new(Cell::new(0));
+        let mut arena = DroppingRing::new_in(&counting);
+
+        let mut retained = None;
+        for _ in 0..2 {
+            for _ in 0..3 {
+                arena.alloc([0u8; 100_000]);
+            }
+            let large = arena.alloc(Large(drops.clone(), [0; 100_000]));
+            large.1[99_999] = 1;
+            assert_eq!(large.1[99_999], 1);
+            assert_eq!(arena.pending_drops(), 1);
+
+            // Oversized blocks are deallocated, only chunks are retained.
+            arena.reset();
+            assert_eq!(*retained.get_or_insert(live()), live());
+        }
+        assert_eq!(drops.get(), 2);
+
+        arena.alloc([0u8; 100_000]);
+        arena.alloc(Large(drops.clone(), [0; 100_000]));
+        drop(arena);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(live(), 0);
+    }
+
+    #[test]
+    fn test_equality() {
+        let alloc = RingAlloc::new();
+        let clone = alloc.clone();
+        let other = RingAlloc::new();
+
+        assert!(alloc == clone);
+        assert!(alloc != other);
+
+        // Comparison with references, e.g. allocators borrowed by collections.
+        let (clone_ref, other_ref) = (&clone, &other);
+        assert!(alloc == clone_ref);
+        assert!(clone_ref == alloc);
+        assert!(alloc != other_ref);
+        assert!(other_ref != alloc);
+    }
+
+    #[cfg(not(feature = "debug-redzone"))]
+    fn stack_workload<A: Allocator>(alloc: &RingAlloc<A>) {
+        let layout = Layout::new::<[u8; 16]>();
+
+        // Blocks of a round fit into single tiny chunk: 500 blocks of 24 bytes,
+        // fewer of them with larger block metadata.
+        let count = 500 * 24 / (U64_BLOCK_SIZE + 8);
+
+        for _ in 0..10 {
+            let blocks = (0..count)
+                .map(|_| alloc.allocate(layout).unwrap())
+                .collect::<Vec<_>>();
+
+            for block in blocks.into_iter().rev() {
+                unsafe { alloc.deallocate(block.cast(), layout) };
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-redzone"))]
+    fn test_lifo() {
+        let ring_counter = CountingAllocator::default();
+        stack_workload(&RingAlloc::new_in(&ring_counter));
+
+        let lifo_counter = CountingAllocator::default();
+        stack_workload(&RingAlloc::new_lifo_in(&lifo_counter));
+
+        // `Rings` and single chunk.
+        assert_eq!(lifo_counter.allocations.get(), 2);
+        #[cfg(not(feature = "front-reuse"))]
+        assert!(ring_counter.allocations.get() > lifo_counter.allocations.get());
+
+        // Ring mode reuses front of the chunk as well.
+        #[cfg(feature = "front-reuse")]
+        assert_eq!(
+            ring_counter.allocations.get(),
+            lifo_counter.allocations.get()
+        );
+
+        assert_eq!(
+            lifo_counter.allocations.get(),
+            lifo_counter.deallocations.get()
+        );
+        assert_eq!(
+            ring_counter.allocations.get(),
+            ring_counter.deallocations.get()
+        );
+    }
+
+    #[test]
+    fn test_try_grow_in_place() {
+        let alloc = RingAlloc::new();
+        let old = Layout::from_size_align(100, 8).unwrap();
+        let a = alloc.allocate(old).unwrap().cast::<u8>();
+
+        // Tail block grows within small chunk.
+        let new = Layout::from_size_align(200, 8).unwrap();
+        assert!(unsafe { alloc.try_grow_in_place(a, old, new) });
+
+        // Too large for small size class.
+        let too_large = Layout::from_size_align(1000, 8).unwrap();
+        assert!(!unsafe { alloc.try_grow_in_place(a, new, too_large) });
+
+        // Block is not the tail anymore.
+        let b = alloc.allocate(old).unwrap().cast::<u8>();
+        let grown = Layout::from_size_align(250, 8).unwrap();
+        assert!(!unsafe { alloc.try_grow_in_place(a, new, grown) });
+
+        unsafe {
+            alloc.deallocate(b, old);
+            alloc.deallocate(a, new);
+        }
+    }
+
+    #[test]
+    fn test_lifo_rewind() {
+        let alloc = RingAlloc::new_lifo_in(Global);
+        let layout = Layout::new::<u64>();
+
+        let a = alloc.allocate(layout).unwrap();
+        let b = alloc.allocate(layout).unwrap();
+        unsafe { alloc.deallocate(b.cast(), layout) };
+
+        // Top block is reused immediately.
+        let c = alloc.allocate(layout).unwrap();
+        assert_eq!(b, c);
+
+        // Out of order deallocation.
+        unsafe { alloc.deallocate(a.cast(), layout) };
+        let d = alloc.allocate(layout).unwrap();
+        assert_ne!(a.cast::<u8>(), d.cast::<u8>());
+
+        unsafe { alloc.deallocate(c.cast(), layout) };
+        unsafe { alloc.deallocate(d.cast(), layout) };
+
+        // Chunk is reset when it becomes unused.
+        let e = alloc.allocate(layout).unwrap();
+        assert_eq!(a, e);
+        unsafe { alloc.deallocate(e.cast(), layout) };
+    }
+
+    #[test]
+    fn test_clean_keeps_tail() {
+        let alloc = RingAlloc::new();
+
+        // Oldest chunk becomes unused while newer one holds a block.
+        let mut boxes = (0..1500u64)
+            .map(|i| Box::new_in(i, &alloc))
+            .collect::<Vec<_>>();
+        let kept = boxes.pop().unwrap();
+        drop(boxes);
+        alloc.flush();
+
+        // Filling the remaining chunk rotates the ring through its tail.
+        let boxes = (0..3000u64)
+            .map(|i| Box::new_in(i, &alloc))
+            .collect::<Vec<_>>();
+        assert!(boxes.iter().enumerate().all(|(i, b)| **b == i as u64));
+        assert_eq!(*kept, 1499);
+    }
+
+    #[test]
+    fn test_allocate_in_class() {
+        use crate::{Classes, SizeClass};
+
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u32>();
+
+        // Tiny block is placed into large chunk.
+        let ptr = alloc.allocate_in_class(layout, SizeClass::Large).unwrap();
+        unsafe { ptr.cast::<u32>().as_ptr().write(7) };
+        let report = alloc.memory_report();
+        assert_eq!((report.tiny_chunks, report.large_chunks), (0, 1));
+
+        // Layout must fit the class.
+        let small = Layout::new::<[u8; 32]>();
+        assert!(alloc.allocate_in_class(small, SizeClass::Tiny).is_err());
+
+        unsafe { alloc.deallocate_in_class(ptr.cast(), layout, SizeClass::Large) };
+        assert!(alloc.is_empty());
+
+        // Disabled class can't be chosen.
+        let alloc = RingAlloc::<Global, Classes<true, false, true>>::with_classes_in(Global);
+        assert!(alloc.allocate_in_class(layout, SizeClass::Small).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "const-generic-config")]
+    fn test_thresholds() {
+        use crate::Thresholds;
+
+        let alloc = RingAlloc::<Global, Thresholds<64, 1024, 4096>>::with_classes_in(Global);
+
+        let tiny = Box::new_in([1u8; 64], &alloc);
+        let small = Box::new_in([2u8; 1024], &alloc);
+        let large = Box::new_in([3u8; 4096], &alloc);
+        let oversized = Box::new_in([4u8; 4097], &alloc);
+
+        // Oversized block is not in the rings.
+        let report = alloc.memory_report();
+        assert_eq!(
+            (report.tiny_chunks, report.small_chunks, report.large_chunks),
+            (1, 1, 1)
+        );
+        assert_eq!(report.live_blocks, 3);
+
+        assert_eq!((tiny[63], small[1023], large[4095]), (1, 2, 3));
+        assert_eq!(oversized[4096], 4);
+        drop((tiny, small, large, oversized));
+        assert!(alloc.is_empty());
+
+        // Blocks grown in place beyond the threshold return to their chunk.
+        let mut vec = Vec::with_capacity_in(64, &alloc);
+        vec.extend(0..1000u32);
+        assert!(vec.iter().enumerate().all(|(i, x)| *x == i as u32));
+        drop(vec);
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_freeze() {
+        use std::{sync::Arc, thread};
+
+        let alloc = RingAlloc::new();
+
+        let mut values = Vec::with_capacity_in(1000, alloc.handle());
+        values.extend(0..1000u64);
+        let (ptr, len, cap) = (values.as_mut_ptr(), values.len(), values.capacity());
+        core::mem::forget(values);
+
+        // Other handles could allocate concurrently with readers.
+        let weak = alloc.downgrade();
+        let alloc = match alloc.freeze() {
+            Ok(_) => panic!("Ring-allocator with weak handle is frozen"),
+            Err(alloc) => alloc,
+        };
+        drop(weak);
+
+        let frozen = Arc::new(alloc.freeze().ok().unwrap());
+        let values = unsafe { core::slice::from_raw_parts(ptr, len) };
+
+        let threads = (0..4)
+            .map(|_| {
+                let frozen = frozen.clone();
+                thread::spawn(move || {
+                    let sum = values.iter().sum::<u64>();
+                    drop(frozen);
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+        for t in threads {
+            assert_eq!(t.join().unwrap(), 999 * 1000 / 2);
+        }
+
+        let alloc = Arc::try_unwrap(frozen).ok().unwrap().unfreeze();
+        drop(unsafe { Vec::from_raw_parts_in(ptr, len, cap, alloc.handle()) });
+        assert!(alloc.is_empty());
+    }
+
+    #[test]
+    fn test_bump_only() {
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_bump_only_in(&counter);
+        let layout = Layout::new::<u64>();
+
+        // Deallocated blocks are not reused before reset.
+        let a = alloc.allocate(layout).unwrap();
+        unsafe { alloc.deallocate(a.cast(), layout) };
+        let b = alloc.allocate(layout).unwrap();
+        assert_ne!(a, b);
+        unsafe { alloc.deallocate(b.cast(), layout) };
+        assert!(!alloc.is_empty());
+
+        // Oversized blocks are still returned to the underlying allocator.
+        let deallocations = counter.deallocations.get();
+        let huge = Layout::from_size_align(1 << 20, 8).unwrap();
+        let ptr = alloc.allocate(huge).unwrap();
+        unsafe { alloc.deallocate(ptr.cast(), huge) };
+        assert_eq!(counter.deallocations.get(), deallocations + 1);
+
+        // Chunk is rewound by reset, so memory is reused from the start.
+        unsafe { alloc.reset() };
+        assert!(alloc.is_empty());
+        let c = alloc.allocate(layout).unwrap();
+        assert_eq!(a, c);
+        unsafe { alloc.deallocate(c.cast(), layout) };
+
+        // Chunks are not added when every round is followed by reset.
+        for _ in 0..3 {
+            unsafe { alloc.reset() };
+            let blocks = (0..U64_BLOCKS_IN_3_CHUNKS)
+                .map(|_| alloc.allocate(layout).unwrap())
+                .collect::<Vec<_>>();
+            for ptr in blocks {
+                unsafe { alloc.deallocate(ptr.cast(), layout) };
+            }
+            assert_eq!(alloc.memory_report().tiny_chunks, 3);
+        }
+
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+    }
+
+    #[test]
+    #[cfg(not(feature = "debug-redzone"))]
+    fn test_allocate_zeroed_reused() {
+        let alloc = RingAlloc::new_lifo_in(Global);
+        let layout = Layout::new::<[u8; 16]>();
+
+        let a = alloc.allocate_zeroed(layout).unwrap();
+        unsafe {
+            assert!(a.as_ref().iter().all(|&byte| byte == 0));
+            a.cast::<u8>().as_ptr().write_bytes(0xFF, 16);
+            alloc.deallocate(a.cast(), layout);
+        }
+
+        // Plant a byte where next block would start.
+        // Memory above dirty mark is never handed out, so it must be left intact.
+        let next = unsafe { a.cast::<u8>().as_ptr().add(16 + 8) };
+        unsafe { next.write(0x5A) };
+
+        // Top block is reused and zeroed again.
+        let b = alloc.allocate_zeroed(layout).unwrap();
+        assert_eq!(a, b);
+        unsafe {
+            assert!(b.as_ref().iter().all(|&byte| byte == 0));
+            assert_eq!(next.read(), 0x5A);
+            next.write(0);
+            alloc.deallocate(b.cast(), layout);
+        }
+    }
+
+    #[test]
+    fn test_allocate_zeroed_after_reset() {
+        let alloc = RingAlloc::new_in(Global);
+        let layout = Layout::new::<[u8; 200]>();
+
+        let mut blocks = Vec::new();
+        for _ in 0..100 {
+            let ptr = alloc.allocate(layout).unwrap();
+            unsafe { ptr.cast::<u8>().as_ptr().write_bytes(0xFF, 200) };
+            blocks.push(ptr);
+        }
+        for ptr in blocks.drain(..) {
+            unsafe { alloc.deallocate(ptr.cast(), layout) };
+        }
+
+        for _ in 0..100 {
+            let ptr = alloc.allocate_zeroed(layout).unwrap();
+            unsafe { assert!(ptr.as_ref().iter().all(|&byte| byte == 0)) };
+            blocks.push(ptr);
+        }
+        for ptr in blocks.drain(..) {
+            unsafe { alloc.deallocate(ptr.cast(), layout) };
+        }
+    }
+
+    #[cfg(feature = "debug-introspect")]
+    #[test]
+    fn test_live_summary() {
+        use crate::SizeClass;
+
+        let alloc = RingAlloc::new();
+        let boxes = (0..5000u32)
+            .map(|i| Box::new_in(i, &alloc))
+            .collect::<Vec<_>>();
+        let leaked = Box::new_in(42u64, &alloc);
+        let leaked_addr = &*leaked as *const u64 as usize;
+        core::mem::forget(leaked);
+        drop(boxes);
+
+        let summary = alloc.live_summary();
+        assert_eq!(summary.len(), 1, "{:?}", summary);
+
+        let info = &summary[0];
+        assert_eq!(info.size_class, SizeClass::Tiny);
+        assert_eq!(info.live_bytes, U64_BLOCK_SIZE);
+        assert!(info.chunk_addr < leaked_addr);
+        assert!(leaked_addr < info.chunk_addr + info.chunk_size);
+    }
+
+    #[cfg(all(
+        feature = "debug-introspect",
+        debug_assertions,
+        not(feature = "aligned-chunks")
+    ))]
+    #[test]
+    fn test_live_summary_locations() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u64>();
+
+        let freed = alloc.allocate(layout).unwrap();
+        let line = line!() + 1;
+        let leaked = alloc.allocate(layout).unwrap();
+        let boxed = Box::new_in(1u64, &alloc);
+        unsafe { alloc.deallocate(freed.cast(), layout) };
+        drop(boxed);
+
+        let summary = alloc.live_summary();
+        assert_eq!(summary.len(), 1, "{:?}", summary);
+
+        let locations = &summary[0].locations;
+        assert_eq!(locations.len(), 1, "{:?}", locations);
+        assert_eq!(locations[0].file(), file!());
+        assert_eq!(locations[0].line(), line);
+
+        unsafe { alloc.deallocate(leaked.cast(), layout) };
+        assert!(alloc.live_summary().is_empty());
+    }
+
+    #[test]
+    fn test_reset() {
+        let alloc = RingAlloc::new();
+        for i in 0..5000u32 {
+            core::mem::forget(Box::new_in(i, &alloc));
+        }
+        let chunks = alloc.check_rings();
+        assert!(chunks > 1);
+
+        unsafe { alloc.reset() };
+
+        // Chunks are rewound and reused.
+        for i in 0..5000u32 {
+            core::mem::forget(Box::new_in(i, &alloc));
+        }
+        assert_eq!(alloc.check_rings(), chunks);
+        unsafe { alloc.reset() };
+    }
+
+    #[test]
+    fn test_commit() {
+        fn scratch<A: Allocator>(alloc: &RingAlloc<A>) {
+            for _ in 0..U64_BLOCKS_IN_3_CHUNKS {
+                let ptr = alloc.allocate(Layout::new::<u64>()).unwrap();
+                unsafe { ptr.cast::<u64>().as_ptr().write(u64::MAX) };
+            }
+        }
+
+        let counter = CountingAllocator::default();
+        let alloc = RingAlloc::new_in(&counter);
+        let layout = Layout::new::<u64>();
+
+        // Persistent blocks span several chunks.
+        let persistent = (0..U64_BLOCKS_IN_3_CHUNKS as u64)
+            .map(|i| {
+                let ptr = alloc.allocate(layout).unwrap().cast::<u64>();
+                unsafe { ptr.as_ptr().write(i) };
+                ptr
+            })
+            .collect::<Vec<_>>();
+        alloc.commit();
+
+        scratch(&alloc);
+        unsafe { alloc.reset() };
+
+        // Scratch space is reused.
+        let allocations = counter.allocations.get();
+        scratch(&alloc);
+        assert_eq!(counter.allocations.get(), allocations);
+        unsafe { alloc.reset() };
+
+        for (i, ptr) in persistent.iter().enumerate() {
+            assert_eq!(unsafe { *ptr.as_ptr() }, i as u64);
+        }
+
+        // Committed blocks are deallocated as usual.
+        for ptr in persistent {
+            unsafe { alloc.deallocate(ptr.cast(), layout) };
+        }
+        assert!(alloc.is_empty());
+        alloc.flush();
+        assert_eq!(alloc.check_rings(), 0);
+        drop(alloc);
+        assert_eq!(counter.allocations.get(), counter.deallocations.get());
+
+        // Stack discipline doesn't rewind below commit point.
+        let lifo = RingAlloc::new_lifo_in(Global);
+        let first = lifo.allocate(layout).unwrap().cast::<u8>();
+        lifo.commit();
+        let second = lifo.allocate(layout).unwrap().cast::<u8>();
+        unsafe { lifo.deallocate(second, layout) };
+        assert_eq!(lifo.allocate(layout).unwrap().cast(), second);
+        unsafe { lifo.reset() };
+        assert_eq!(lifo.allocate(layout).unwrap().cast(), second);
+        unsafe { lifo.reset() };
+
+        // Chunk forgets commit point once its blocks are deallocated.
+        unsafe { lifo.deallocate(first, layout) };
+        assert!(lifo.is_empty());
+        assert_eq!(lifo.allocate(layout).unwrap().cast(), first);
+        unsafe { lifo.reset() };
+    }
+
+    #[test]
+    #[cfg(all(feature = "debug-validate", not(feature = "aligned-chunks")))]
+    #[should_panic(expected = "after its ring-allocator was reset")]
+    fn test_use_after_reset() {
+        let alloc = RingAlloc::new();
+        let layout = Layout::new::<u32>();
+        let block = alloc.allocate(layout).unwrap();
+
+        unsafe {
+            alloc.reset();
+            alloc.deallocate(block.cast(), layout);
+        }
+    }
+
+    #[test]
+    fn test_bump_compat() {
+        use crate::compat::BumpCompat;
+
+        let mut bump = BumpCompat::new();
+        let first = bump.alloc(1u64) as *mut u64;
+        let strings = bump.alloc_slice_clone(&[String::from("a"), String::from("b")]);
+        assert_eq!(strings.concat(), "ab");
+        for s in strings.iter_mut() {
+            // Values are never dropped by the arena.
+            drop(core::mem::take(s));
+        }
+
+        let mut v = Vec::new_in(&bump);
+        v.extend(0..1000u32);
+        assert_eq!(v.iter().sum::<u32>(), 499500);
+        drop(v);
+
+        bump.reset();
+        assert_eq!(bump.alloc(2u64) as *mut u64, first);
+    }
+
+    #[test]
+    fn test_bump_compat_oversized() {
+        use crate::compat::BumpCompat;
+
+        let counting = CountingAllocator::default();
+        let live = || counting.allocations.get() - counting.deallocations.get();
+
+        let mut bump = BumpCompat::new_in(&counting);
+
+        let mut retained = None;
+        for _ in 0..2 {
+            for _ in 0..3 {
+                let bytes = bump.alloc_slice_copy(&[0u8; 100_000]);
+                bytes[99_999] = 1;
+                assert_eq!(bytes[99_999], 1);
+            }
+
+            // Oversized blocks deallocated through the arena are forgotten by it.
+            let mut v = Vec::with_capacity_in(100_000, &bump);
+            v.extend(0..100_000u32);
+            drop(v);
+
+            // Leaked ones are deallocated on reset.
+            core::mem::forget(Vec::<u8, _>::with_capacity_in(100_000, &bump));
+
+            // Oversized blocks are deallocated, only chunks are retained.
+            bump.reset();
+            assert_eq!(*retained.get_or_insert(live()), live());
+        }
+
+        bump.alloc_slice_copy(&[0u8; 100_000]);
+        drop(bump);
+        assert_eq!(live(), 0);
+    }
+
+    #[test]
+    fn test_dump_state() {
+        let alloc = RingAlloc::new();
+        let b = Box::new_in(42u32, alloc.clone());
+        let b_addr = &*b as *const u32 as usize;
+
+        let mut dump = String::new();
+        alloc.dump_state(&mut dump).unwrap();
+
+        assert!(dump.contains("tiny ring"));
+        assert!(dump.contains("[head]"));
+
+        // One of the reported chunks contains the box.
+        let found = dump
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("chunk 0x"))
+            .filter_map(|line| line.split(':').next())
+            .filter_map(|hex| usize::from_str_radix(hex, 16).ok())
+            .any(|chunk_addr| chunk_addr < b_addr && b_addr < chunk_addr + 16384);
+        assert!(found, "{}", dump);
+    }
+}
+
+/// Tests that do not require `alloc` and run under `--no-default-features`.
+#[cfg(not(loom))]
+mod fixed {
+    use core::{cell::Cell, ptr::NonNull};
+
+    use crate::RingAlloc;
+    use allocator_api2::alloc::{AllocError, Allocator, Layout};
+
+    const BUFFER_SIZE: usize = 262144;
+
+    #[repr(align(65536))]
+    struct Buffer([u8; BUFFER_SIZE]);
+
+    /// Allocator that bumps over a static buffer and never reuses memory.
+    struct FixedAllocator {
+        buffer: NonNull<u8>,
+        used: Cell<usize>,
+        allocations: Cell<usize>,
+        deallocations: Cell<usize>,
+    }
+
+    impl FixedAllocator {
+        fn new(buffer: &'static mut Buffer) -> Self {
+            FixedAllocator {
+                buffer: NonNull::from(&mut buffer.0).cast(),
+                used: Cell::new(0),
+                allocations: Cell::new(0),
+                deallocations: Cell::new(0),
+            }
+        }
+    }
+
+    unsafe impl Allocator for FixedAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let base = self.buffer.as_ptr() as usize;
+            let start = (base + self.used.get() + layout.align() - 1) & !(layout.align() - 1);
+            let offset = start - base;
+            if offset + layout.size() > BUFFER_SIZE {
+                return Err(AllocError);
+            }
+            self.used.set(offset + layout.size());
+            self.allocations.set(self.allocations.get() + 1);
+
+            // Safety: `offset + layout.size()` is within the buffer.
+            let ptr = unsafe { NonNull::new_unchecked(self.buffer.as_ptr().add(offset)) };
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let base = self.buffer.as_ptr() as usize;
+            let addr = ptr.as_ptr() as usize;
+            assert!(addr >= base && addr + layout.size() <= base + BUFFER_SIZE);
+            self.deallocations.set(self.deallocations.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_fixed_allocator() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+
+        // Safety: `BUFFER` is used only by this test.
+        let fixed = FixedAllocator::new(unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) });
+        {
+            let alloc = RingAlloc::try_new_in(&fixed).unwrap();
+
+            let tiny = Layout::new::<u32>();
+            let small = Layout::new::<[u64; 8]>();
+
+            let mut blocks = [(NonNull::dangling(), tiny); 64];
+            for (i, block) in blocks.iter_mut().enumerate() {
+                let layout = if i % 2 == 0 { tiny } else { small };
+                let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+                unsafe { ptr.as_ptr().write_bytes(i as u8, layout.size()) };
+                *block = (ptr, layout);
+            }
+
+            for (i, &(ptr, layout)) in blocks.iter().enumerate() {
+                let bytes = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+                assert!(bytes.iter().all(|&b| b == i as u8));
+                unsafe { alloc.deallocate(ptr, layout) };
+            }
+
+            // Rings, tiny chunk and small chunk.
+            assert_eq!(fixed.allocations.get(), 3);
+
+            // Chunks are reused after blocks are deallocated,
+            // ring needs at most one more small chunk to rotate.
+            for _ in 0..10000 {
+                let ptr = alloc.allocate(small).unwrap().cast::<u8>();
+                unsafe { alloc.deallocate(ptr, small) };
+            }
+            assert!(fixed.allocations.get() <= 4);
+        }
+
+        assert_eq!(fixed.allocations.get(), fixed.deallocations.get());
+    }
+
+    #[test]
+    fn test_fixed_allocator_exhausted() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+
+        // Safety: `BUFFER` is used only by this test.
+        let fixed = FixedAllocator::new(unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) });
+        {
+            let alloc = RingAlloc::try_new_in(&fixed).unwrap();
+
+            // Large chunk does not fit into the buffer.
+            assert!(alloc.allocate(Layout::new::<[u8; 1024]>()).is_err());
+
+            // Small chunks are still available.
+            let layout = Layout::new::<[u8; 128]>();
+            let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+            unsafe { alloc.deallocate(ptr, layout) };
+        }
+
+        assert_eq!(fixed.allocations.get(), fixed.deallocations.get());
+    }
+}
+
+#[cfg(all(feature = "std", not(loom)))]
+mod global {
+    use crate::OneRingAlloc;
+
+    use allocator_api2::{
+        alloc::{Allocator, Layout},
+        boxed::Box,
+        vec::Vec,
+    };
+    use allocator_api2_tests::make_test;
+
+    make_test![
+        test_sizes(OneRingAlloc),
+        test_vec(OneRingAlloc),
+        test_many_boxes(OneRingAlloc)
+    ];
+
+    #[test]
+    fn test_size_class_boundaries() {
+        for max in [16, 256, 65536] {
+            for (size, align) in [(max, 1), (max, max), (1, max), (max + 1, 1)] {
+                let layout = Layout::from_size_align(size, align).unwrap();
+                let ptr = OneRingAlloc.allocate(layout).unwrap().cast::<u8>();
+                assert_eq!(ptr.as_ptr() as usize % align, 0);
+                unsafe { OneRingAlloc.deallocate(ptr, layout) };
+            }
+        }
+    }
+
+    #[test]
+    // Huge requests are counted in the last bucket that `test_size_histogram` resets.
+    #[cfg(not(feature = "metrics"))]
+    fn test_huge_layouts() {
+        for align in [1, 8, 4096] {
+            let layout = Layout::from_size_align(isize::MAX as usize - (align - 1), align).unwrap();
+            assert!(OneRingAlloc.allocate(layout).is_err());
+        }
+    }
+
+    #[test]
+    fn test_align_dominated_layouts() {
+        for size in [1, 8, 64] {
+            for align in [16, 256, 4096] {
+                let layout = Layout::from_size_align(size, align).unwrap();
+                let blocks = (0..10)
+                    .map(|i| {
+                        let ptr = OneRingAlloc.allocate(layout).unwrap().cast::<u8>();
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+                        unsafe { ptr.as_ptr().write_bytes(i, size) };
+                        ptr
+                    })
+                    .collect::<Vec<_>>();
+
+                for (i, ptr) in blocks.into_iter().enumerate() {
+                    let bytes = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), size) };
+                    assert!(bytes.iter().all(|&b| b == i as u8));
+                    unsafe { OneRingAlloc.deallocate(ptr, layout) };
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_oversized_cache() {
+        let layout = Layout::from_size_align(1 << 20, 8).unwrap();
+        let before = OneRingAlloc.oversized_allocations();
+
+        for i in 0..1000 {
+            let ptr = OneRingAlloc.allocate(layout).unwrap();
+            assert!(ptr.len() >= layout.size());
+            unsafe {
+                ptr.cast::<u8>()
+                    .as_ptr()
+                    .write_bytes(i as u8, layout.size());
+                OneRingAlloc.deallocate(ptr.cast(), layout);
+            }
+        }
+
+        // Other tests may allocate oversized blocks concurrently.
+        assert!(OneRingAlloc.oversized_allocations() - before < 100);
+
+        // Blocks of uncacheable alignment and size bypass the cache.
+        for layout in [
+            Layout::from_size_align(1 << 20, 1 << 13).unwrap(),
+            Layout::from_size_align(1 << 27, 8).unwrap(),
+        ] {
+            let ptr = OneRingAlloc.allocate(layout).unwrap();
+            assert_eq!(ptr.cast::<u8>().as_ptr() as usize % layout.align(), 0);
+            unsafe { OneRingAlloc.deallocate(ptr.cast(), layout) };
+        }
+
+        // Cached sizes are rounded up to a quarter of the power of two below them.
+        for (size, rounded) in [
+            ((1 << 16) + 1, 5 << 14),
+            ((1 << 20) + 1, 5 << 18),
+            (3 << 20, 3 << 20),
+            ((7 << 21) + 1, 1 << 24),
+        ] {
+            let layout = Layout::from_size_align(size, 8).unwrap();
+            let ptr = OneRingAlloc.allocate(layout).unwrap();
+            assert_eq!(ptr.len(), rounded);
+            unsafe { OneRingAlloc.deallocate(ptr.cast(), layout) };
+        }
+
+        OneRingAlloc.clean_global();
+    }
+
+    #[test]
+    fn test_by_ref() {
+        fn default_alloc<A: Allocator + Copy + Default>() -> A {
+            A::default()
+        }
+
+        let alloc: OneRingAlloc = default_alloc();
+        let mut v = Vec::new_in(&alloc);
+        v.extend(0..100u32);
+        assert_eq!(v.iter().sum::<u32>(), 4950);
+
+        // Copies of reference are allocators too.
+        let mut w = Vec::new_in(*v.allocator());
+        w.push(Box::new_in(1u32, &OneRingAlloc));
+        assert_eq!(*w[0], 1);
+    }
+
+    #[test]
+    fn test_global_share() {
+        let b = std::thread::spawn(|| Box::new_in(0u32, OneRingAlloc))
+            .join()
+            .unwrap();
+        drop(b);
+
+        drop(Box::new_in(0u32, OneRingAlloc));
+    }
+
+    #[test]
+    fn test_flush_local_to_global() {
+        // Small ring of this namespace is not used by other tests,
+        // so no other chunk can be stolen instead.
+        let alloc = OneRingAlloc::dedicated::<3>();
+        let layout = Layout::new::<[u8; 128]>();
+
+        let (addr, donated) = std::thread::spawn(move || {
+            let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+            let addr = ptr.as_ptr() as usize;
+            unsafe { alloc.deallocate(ptr, layout) };
+
+            (addr, alloc.flush_local_to_global())
+        })
+        .join()
+        .unwrap();
+
+        assert!(donated >= 1);
+
+        // This thread steals donated chunk from the global ring.
+        let ptr = alloc.allocate(layout).unwrap().cast::<u8>();
+        let stolen = ptr.as_ptr() as usize;
+        assert!(stolen <= addr && addr - stolen < 65536);
+        unsafe { alloc.deallocate(ptr, layout) };
+    }
+
+    #[test]
+    fn test_ring_links() {
+        std::thread::spawn(|| {
+            let layout = Layout::new::<u64>();
+            let mut blocks = (0..3000)
+                .map(|_| OneRingAlloc.allocate(layout).unwrap())
+                .collect::<Vec<_>>();
+            OneRingAlloc.check_rings();
+
+            // Free blocks in the middle, so chunk that holds them is cleaned.
+            for block in blocks.drain(1000..2000) {
+                unsafe { OneRingAlloc.deallocate(block.cast(), layout) };
+            }
+            OneRingAlloc.clean_local();
+            OneRingAlloc.check_rings();
+
+            for block in blocks {
+                unsafe { OneRingAlloc.deallocate(block.cast(), layout) };
+            }
+            OneRingAlloc.flush_local_to_global();
+            OneRingAlloc.check_rings();
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_global_stats() {
+        // Thread exits while block is still allocated,
+        // so its chunk is donated to the global ring.
+        let b = std::thread::spawn(|| {
+            let b = Box::new_in([0u8; 64], OneRingAlloc);
+            assert!(OneRingAlloc.thread_stats().live >= 64);
+            b
+        })
+        .join()
+        .unwrap();
+
+        let stats = OneRingAlloc.global_stats();
+        assert!(stats.chunks >= 1);
+        assert!(stats.reserved >= 65536);
+        assert!(stats.live >= 64);
+
+        drop(b);
+    }
+
+    #[test]
+    fn test_dedicated_rings() {
+        let first = OneRingAlloc::dedicated::<1>();
+        let second = OneRingAlloc::dedicated::<2>();
+
+        let b = std::thread::spawn(move || {
+            let a = Box::new_in([0u8; 64], first);
+            let b = Box::new_in([0u8; 64], second);
+
+            // Each namespace rotates over its own chunk.
+            let first_stats = first.thread_stats();
+            let second_stats = second.thread_stats();
+            assert_eq!(first_stats.chunks, 1);
+            assert_eq!(second_stats.chunks, 1);
+            assert!(first_stats.live >= 64);
+            assert!(second_stats.live >= 64);
+            assert_eq!(OneRingAlloc.thread_stats().chunks, 0);
+
+            // Freeing block of one namespace doesn't affect another.
+            drop(a);
+            assert_eq!(first.thread_stats().live, 0);
+            assert_eq!(second.thread_stats().live, second_stats.live);
+
+            b
+        })
+        .join()
+        .unwrap();
+
+        // Chunks are retired to global rings of their own namespace.
+        assert!(second.global_stats().live >= 64);
+        assert_eq!(first.global_stats().live, 0);
+
+        drop(b);
+        first.clean_global();
+        second.clean_global();
+        assert_eq!(first.global_stats(), second.global_stats());
+        assert_eq!(first.global_stats().chunks, 0);
+    }
+
+    #[test]
+    fn test_compact_global() {
+        let alloc = OneRingAlloc::dedicated::<0>();
+
+        // Thread exits with chunks holding different number of live blocks.
+        let kept = std::thread::spawn(move || {
+            let boxes = (0..1200u32)
+                .map(|i| Box::new_in([i; 50], alloc))
+                .collect::<Vec<_>>();
+
+            // Later chunks keep more blocks than earlier ones.
+            boxes
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| i % 4 < i * 4 / 1200)
+                .map(|(_, b)| b)
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+
+        alloc.compact_global();
+
+        let [tiny, small, large] = alloc.global_live();
+        assert!(tiny.is_empty() && large.is_empty());
+        assert!(small.len() >= 3);
+        assert!(small.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(small[0], *small.iter().min().unwrap());
+        assert!(small[0] < small[small.len() - 1]);
+        OneRingAlloc.check_rings();
+
+        assert!(kept.iter().all(|b| b.iter().all(|x| *x == b[0])));
+        drop(kept);
+        alloc.compact_global();
+        assert_eq!(alloc.global_stats(), crate::RingStats::default());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let alloc = OneRingAlloc::dedicated::<5>();
+
+        let workload = || {
+            let boxes = (0..500u32)
+                .map(|i| Box::new_in([i; 16], alloc))
+                .collect::<Vec<_>>();
+            assert!(boxes.iter().enumerate().all(|(i, b)| **b == [i as u32; 16]));
+        };
+
+        // Run starts in partially used head chunk, so ring needs
+        // one chunk more than the run has live bytes for.
+        // It is reached after few runs.
+        for _ in 0..4 {
+            workload();
+        }
+        let snapshot = alloc.snapshot();
+        assert!(snapshot.chunks() > 0);
+
+        for _ in 0..10 {
+            workload();
+        }
+        snapshot.assert_no_growth();
+
+        // Chunks moved to global rings are not growth.
+        alloc.flush_local_to_global();
+        snapshot.assert_no_growth();
+    }
+
+    #[test]
+    #[should_panic(expected = "Number of chunks grew")]
+    fn test_snapshot_growth() {
+        let alloc = OneRingAlloc::dedicated::<6>();
+        let snapshot = alloc.snapshot();
+
+        let leaked = (0..5000u32)
+            .map(|i| Box::new_in([i; 16], alloc))
+            .collect::<Vec<_>>();
+        core::mem::forget(leaked);
+
+        snapshot.assert_no_growth();
+    }
+
+    #[test]
+    #[cfg(not(feature = "aligned-chunks"))]
+    fn test_configure_classes() {
+        let alloc = OneRingAlloc::dedicated::<4>();
+
+        std::thread::spawn(move || {
+            let layout = Layout::new::<u64>();
+            let before = alloc.allocate(layout).unwrap().cast::<u64>();
+            unsafe { before.as_ptr().write(1) };
+            assert_eq!(alloc.thread_stats().chunks, 1);
+
+            // Tiny blocks are allocated from small chunk.
+            alloc.configure_classes(false, true);
+            let after = alloc.allocate(layout).unwrap().cast::<u64>();
+            unsafe { after.as_ptr().write(2) };
+            assert_eq!(alloc.thread_stats().chunks, 2);
+
+            // Block allocated before the change is returned to its tiny chunk.
+            unsafe {
+                assert_eq!(before.as_ptr().read(), 1);
+                alloc.deallocate(before.cast(), layout);
+            }
+
+            // Block allocated after the change is returned to its small chunk.
+            alloc.configure_classes(true, true);
+            unsafe {
+                assert_eq!(after.as_ptr().read(), 2);
+                alloc.deallocate(after.cast(), layout);
+            }
+
+            assert_eq!(alloc.thread_stats().live, 0);
+            alloc.clean_local();
+        })
+        .join()
+        .unwrap();
+
+        alloc.clean_global();
+        assert_eq!(alloc.global_stats().chunks, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "deferred-frees")]
+    fn test_deferred_frees() {
+        std::thread::spawn(|| {
+            OneRingAlloc.set_deferred_frees(true);
+
+            let boxes = (0..10u64)
+                .map(|i| Box::new_in(i, OneRingAlloc))
+                .collect::<Vec<_>>();
+            drop(boxes);
+
+            // Freed bytes are not accounted until flushed.
+            assert_eq!(OneRingAlloc.deferred_bytes(), 10 * super::U64_BLOCK_SIZE);
+            OneRingAlloc.flush_deferred_frees();
+            assert_eq!(OneRingAlloc.deferred_bytes(), 0);
+
+            // Cleaning flushes deferred frees first.
+            drop(Box::new_in(0u64, OneRingAlloc));
+            assert_eq!(OneRingAlloc.deferred_bytes(), super::U64_BLOCK_SIZE);
+            OneRingAlloc.clean_local();
+            assert_eq!(OneRingAlloc.deferred_bytes(), 0);
+
+            // Frees are accounted at once when threshold is reached.
+            let boxes = (0..1000u64)
+                .map(|i| Box::new_in(i, OneRingAlloc))
+                .collect::<Vec<_>>();
+            drop(boxes);
+            assert!(OneRingAlloc.deferred_bytes() < 4096);
+
+            OneRingAlloc.set_deferred_frees(false);
+            assert_eq!(OneRingAlloc.deferred_bytes(), 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    /// Stress test for cross-thread deallocations with deferred frees.
+    ///
+    /// Run under ThreadSanitizer with
+    /// `RUSTFLAGS="-Zsanitizer=thread" cargo +nightly test -Zbuild-std --target x86_64-unknown-linux-gnu deferred_frees`
+    #[test]
+    #[cfg(feature = "deferred-frees")]
+    fn test_deferred_frees_stress() {
+        const THREADS: usize = 4;
+        const BOXES: usize = 20000;
+
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..THREADS).map(|_| std::sync::mpsc::channel()).unzip();
+
+        let threads = receivers
+            .into_iter()
+            .enumerate()
+            .map(|(idx, receiver)| {
+                // Each thread sends its boxes to the next one.
+                let sender = senders[(idx + 1) % THREADS].clone();
+                std::thread::spawn(move || {
+                    OneRingAlloc.set_deferred_frees(idx % 2 == 0);
+
+                    for i in 0..BOXES {
+                        let b = Box::new_in([idx, i], OneRingAlloc);
+                        sender.send(b).unwrap();
+                    }
+                    drop(sender);
+
+                    let from = (idx + THREADS - 1) % THREADS;
+                    for (i, b) in receiver.iter().enumerate() {
+                        assert_eq!(*b, [from, i]);
+                    }
+                    OneRingAlloc.check_rings();
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(senders);
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[cfg(not(feature = "aligned-chunks"))]
+    #[should_panic(expected = "different size class")]
+    fn test_deallocate_size_class_mismatch() {
+        let ptr = OneRingAlloc.allocate(Layout::new::<u64>()).unwrap();
+
+        // Allocated in tiny chunk, deallocated as small block.
+        unsafe { OneRingAlloc.deallocate(ptr.cast(), Layout::new::<[u64; 4]>()) };
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn test_size_histogram() {
+        let sizes = [1, 2, 3, 4, 100, 256, 257, 100000];
+        let buckets = [0, 1, 2, 2, 7, 8, 9, 17];
+
+        let before = OneRingAlloc.size_histogram();
+        for size in sizes {
+            let layout = Layout::from_size_align(size, 1).unwrap();
+            let ptr = OneRingAlloc.allocate(layout).unwrap();
+            unsafe { OneRingAlloc.deallocate(ptr.cast(), layout) };
+        }
+        let after = OneRingAlloc.size_histogram();
+
+        // Other tests may allocate concurrently.
+        for bucket in 0..OneRingAlloc::SIZE_HISTOGRAM_BUCKETS {
+            let expected = buckets.iter().filter(|b| **b == bucket).count() as u64;
+            assert!(after[bucket] - before[bucket] >= expected);
+        }
+
+        // Sizes larger than 2^30 are counted in the last bucket.
+        let before = OneRingAlloc.size_histogram()[31];
+        let layout = Layout::from_size_align(1 << 40, 1).unwrap();
+        assert!(OneRingAlloc.allocate(layout).is_err());
+        let after = OneRingAlloc.size_histogram()[31];
+        assert!(after > before);
+
+        // Other tests don't make such requests, so the bucket can't grow back.
+        OneRingAlloc.reset_metrics();
+        assert!(OneRingAlloc.size_histogram()[31] < after);
+    }
+
+    /// Chunks flushed to global rings are inspected, cleaned and stolen concurrently.
+    /// Run under thread sanitizer to check for data races on shared chunks.
+    #[test]
+    fn test_shared_chunks() {
+        const THREADS: usize = 4;
+        const ROUNDS: usize = 50;
+
+        let done = std::sync::atomic::AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let stats = OneRingAlloc.global_stats();
+                    assert!(stats.live <= stats.reserved);
+                    OneRingAlloc.clean_global();
+                }
+            });
+
+            let workers = (0..THREADS)
+                .map(|idx| {
+                    scope.spawn(move || {
+                        for round in 0..ROUNDS {
+                            // Chunk with live boxes is flushed to global ring on thread exit
+                            // and stolen by other threads while boxes are dropped here.
+                            let boxes = std::thread::spawn(move || {
+                                (0..100)
+                                    .map(|i| Box::new_in([idx, round, i], OneRingAlloc))
+                                    .collect::<std::vec::Vec<_>>()
+                            })
+                            .join()
+                            .unwrap();
+
+                            for (i, b) in boxes.into_iter().enumerate() {
+                                assert_eq!(*b, [idx, round, i]);
+                            }
+                        }
+                    })
+                })
+                .collect::<std::vec::Vec<_>>();
+
+            for worker in workers {
+                worker.join().unwrap();
+            }
+            done.store(true, std::sync::atomic::Ordering::Relaxed);
+        });
+    }
+
+    #[test]
+    fn test_clean_local_keeps_tail() {
+        std::thread::spawn(|| {
+            // Oldest chunk becomes unused while newer one holds a block.
+            let mut boxes = (0..1500u64)
+                .map(|i| Box::new_in(i, OneRingAlloc))
+                .collect::<Vec<_>>();
+            let kept = boxes.pop().unwrap();
+            drop(boxes);
+            OneRingAlloc.clean_local();
+
+            // Filling the remaining chunk rotates the ring through its tail.
+            let boxes = (0..3000u64)
+                .map(|i| Box::new_in(i, OneRingAlloc))
+                .collect::<Vec<_>>();
+            assert!(boxes.iter().enumerate().all(|(i, b)| **b == i as u64));
+            assert_eq!(*kept, 1499);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_padding_races_with_deallocate() {
+        const ROUNDS: usize = 10000;
+
+        // Dedicated rings are not polluted with chunks of other tests.
+        let alloc = OneRingAlloc::dedicated::<3>();
+
+        let (tx, rx) = std::sync::mpsc::channel::<Box<u8, crate::DedicatedOneRing<3>>>();
+
+        let consumer = std::thread::spawn(move || {
+            for mut b in rx {
+                *b = b.wrapping_add(1);
+                drop(b);
+            }
+        });
+
+        std::thread::spawn(move || {
+            for round in 0..ROUNDS {
+                // Misaligns cursor, so next allocation accounts padding
+                // while another thread frees blocks of the same chunk.
+                tx.send(Box::new_in(round as u8, alloc)).unwrap();
+
+                let b = Box::new_in(round as u64, alloc);
+                assert_eq!(*b, round as u64);
+            }
+            drop(tx);
+            consumer.join().unwrap();
+
+            // Chunks are reclaimed only if no update of `freed` is lost.
+            assert_eq!(alloc.thread_stats().live, 0);
+            alloc.clean_local();
+            assert_eq!(alloc.thread_stats().chunks, 0);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_unregister_thread() {
+        let alloc = OneRingAlloc::dedicated::<7>();
+
+        let (b, stats) = std::thread::spawn(move || {
+            OneRingAlloc.register_thread();
+
+            let b = Box::new_in([1u64; 4], alloc);
+            drop(Box::new_in([2u64; 4], alloc));
+            assert_eq!(alloc.thread_stats().chunks, 1);
+
+            OneRingAlloc.unregister_thread();
+            (b, alloc.thread_stats())
+        })
+        .join()
+        .unwrap();
+
+        // Chunk with live block is donated to global ring.
+        assert_eq!(stats, crate::RingStats::default());
+        assert_eq!(alloc.global_stats().chunks, 1);
+        assert_eq!(*b, [1; 4]);
+
+        drop(b);
+        alloc.clean_global();
+        assert_eq!(alloc.global_stats(), crate::RingStats::default());
+    }
+
+    #[test]
+    fn test_pin_thread_rings() {
+        const SLOT: usize = 1363;
+
+        let (first, chunks) = std::thread::spawn(|| {
+            let b = Box::new_in(0u32, OneRingAlloc);
+            let addr = &*b as *const u32 as usize;
+            drop(b);
+
+            let chunks = OneRingAlloc.thread_stats().chunks;
+            OneRingAlloc.pin_thread_rings(SLOT);
+            assert_eq!(OneRingAlloc.thread_stats().chunks, 0);
+            (addr, chunks)
+        })
+        .join()
+        .unwrap();
+
+        // Only thread-local rings are checked, as other tests
+        // use global rings concurrently.
+        let second = std::thread::spawn(move || {
+            assert!(OneRingAlloc.claim_thread_rings(SLOT));
+            assert!(!OneRingAlloc.claim_thread_rings(SLOT));
+            assert_eq!(OneRingAlloc.thread_stats().chunks, chunks);
+
+            let b = Box::new_in(0u32, OneRingAlloc);
+            &*b as *const u32 as usize
+        })
+        .join()
+        .unwrap();
+
+        // Allocated from the same warm chunk.
+        assert!(first.abs_diff(second) < 16384);
+    }
+
+    #[test]
+    fn test_exit_under_contention() {
+        use std::{
+            sync::{Arc, Barrier},
+            time::{Duration, Instant},
+        };
+
+        const THREADS: usize = 16;
+
+        let ready = Arc::new(Barrier::new(THREADS + 1));
+        let exit = Arc::new(Barrier::new(THREADS + 1));
+
+        let threads = (0..THREADS)
+            .map(|i| {
+                let ready = ready.clone();
+                let exit = exit.clone();
+                std::thread::spawn(move || {
+                    // Chunks stay in use after exit.
+                    let blocks = (
+                        Box::new_in(i as u8, OneRingAlloc),
+                        Box::new_in([i as u64; 8], OneRingAlloc),
+                        Box::new_in([i as u64; 128], OneRingAlloc),
+                    );
+                    ready.wait();
+                    exit.wait();
+                    blocks
+                })
+            })
+            .collect::<Vec<_>>();
+
+        ready.wait();
+
+        // Threads exit while global rings are locked.
+        // Joining them would never complete if they waited for the locks.
+        let guard = OneRingAlloc.lock_global_rings();
+        let start = Instant::now();
+        exit.wait();
+        let blocks = threads
+            .into_iter()
+            .map(|t| t.join().unwrap())
+            .collect::<Vec<_>>();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        drop(guard);
+
+        for (i, (tiny, small, large)) in blocks.into_iter().enumerate() {
+            assert_eq!((*tiny, small[7], large[127]), (i as u8, i as u64, i as u64));
+        }
+
+        // Orphaned chunks are adopted by global rings.
+        OneRingAlloc.check_rings();
+        OneRingAlloc.clean_global();
+    }
+
+    #[test]
+    fn test_thread_initialized() {
+        std::thread::spawn(|| {
+            assert!(!OneRingAlloc.thread_initialized());
+
+            // Querying doesn't initialize rings.
+            assert!(!OneRingAlloc.thread_initialized());
+            assert_eq!(OneRingAlloc.thread_stats().chunks, 0);
+
+            let b = Box::new_in(1u32, OneRingAlloc);
+            assert!(OneRingAlloc.thread_initialized());
+            assert!(!OneRingAlloc::dedicated::<1>().thread_initialized());
+
+            drop(b);
+            OneRingAlloc.unregister_thread();
+            assert!(!OneRingAlloc.thread_initialized());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_global_chunk_cap() {
+        use crate::SizeClass;
+        use std::sync::{Arc, Barrier};
+
+        const THREADS: usize = 4;
+        const CAP: usize = 2;
+
+        // Large ring of this namespace is not used by other tests.
+        let alloc = OneRingAlloc::dedicated::<3>();
+        alloc.set_global_chunk_cap(SizeClass::Large, CAP);
+
+        let donate = Arc::new(Barrier::new(THREADS));
+        let threads = (0..THREADS)
+            .map(|_| {
+                let donate = donate.clone();
+                std::thread::spawn(move || {
+                    // Large chunk fits 34 such blocks.
+                    let boxes = (0..50u8)
+                        .map(|i| Box::new_in([i; 60000], alloc))
+                        .collect::<Vec<_>>();
+                    assert!(boxes.iter().enumerate().all(|(i, b)| b[0] == i as u8));
+                    drop(boxes);
+
+                    // No thread steals donated chunks.
+                    donate.wait();
+                    alloc.flush_local_to_global()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let donated = threads
+            .into_iter()
+            .map(|t| t.join().unwrap())
+            .sum::<usize>();
+        assert!(donated >= THREADS * 2);
+
+        // Rest of donated chunks are freed.
+        let [_, _, large] = alloc.global_live();
+        assert_eq!(large.len(), CAP);
+
+        alloc.set_global_chunk_cap(SizeClass::Large, 1);
+        let [_, _, large] = alloc.global_live();
+        assert_eq!(large.len(), 1);
+
+        alloc.set_global_chunk_cap(SizeClass::Large, usize::MAX);
+        alloc.clean_global();
+    }
+
+    #[test]
+    #[cfg(feature = "donor-tracking")]
+    fn test_drain_thread_donations() {
+        use std::sync::{Arc, Barrier};
+
+        // Large ring of this namespace is not used by other tests.
+        let alloc = OneRingAlloc::dedicated::<6>();
+
+        // Both threads allocate before any chunk is donated,
+        // so neither steals chunks of the other.
+        let allocated = Arc::new(Barrier::new(2));
+        let donated = Arc::new(Barrier::new(2));
+
+        let first = {
+            let (allocated, donated) = (allocated.clone(), donated.clone());
+            std::thread::spawn(move || {
+                let boxes = (0..50u8)
+                    .map(|i| Box::new_in([i; 60000], alloc))
+                    .collect::<Vec<_>>();
+                drop(boxes);
+
+                allocated.wait();
+                let count = alloc.flush_local_to_global();
+                donated.wait();
+                (OneRingAlloc.thread_donor_id(), count)
+            })
+        };
+
+        let second = std::thread::spawn(move || {
+            let mut boxes = (0..50u8)
+                .map(|i| Box::new_in([i; 60000], alloc))
+                .collect::<Vec<_>>();
+            let kept = boxes.pop().unwrap();
+            drop(boxes);
+
+            allocated.wait();
+            donated.wait();
+
+            // Chunk with live block is donated on exit.
+            (OneRingAlloc.thread_donor_id(), kept)
+        });
+
+        let (first_id, first_count) = first.join().unwrap();
+        let (second_id, kept) = second.join().unwrap();
+        assert_ne!(first_id, second_id);
+        assert!(first_count >= 2);
+        // Only large ring is counted, as other tests use smaller rings of this namespace.
+        assert_eq!(alloc.global_live()[2].len(), first_count + 1);
+
+        // Chunk with live block is not freed.
+        assert_eq!(alloc.drain_thread_donations(second_id), 0);
+
+        assert_eq!(alloc.drain_thread_donations(first_id), first_count);
+        assert_eq!(alloc.drain_thread_donations(first_id), 0);
+        assert_eq!(alloc.global_live()[2].len(), 1);
+
+        assert_eq!(kept[0], 49);
+        drop(kept);
+        assert_eq!(alloc.drain_thread_donations(second_id), 1);
+        assert_eq!(alloc.global_live()[2].len(), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "per-cpu", target_os = "linux"))]
+    #[cfg_attr(miri, ignore = "miri doesn't support `sched_getcpu`")]
+    fn test_per_cpu() {
+        let per_cpu = OneRingAlloc::per_cpu();
+
+        let threads = (0..8)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    let mut boxes = Vec::new();
+                    for i in 0..1000u64 {
+                        boxes.push(Box::new_in([t, i, 0, 0], per_cpu));
+                        if i % 100 == 0 {
+                            std::thread::yield_now();
+                        }
+                    }
+                    boxes
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Blocks are freed on a thread other than one that allocated them.
+        for (t, thread) in threads.into_iter().enumerate() {
+            let boxes = thread.join().unwrap();
+            for (i, b) in boxes.into_iter().enumerate() {
+                assert_eq!(*b, [t as u64, i as u64, 0, 0]);
+            }
+        }
+
+        assert!(per_cpu.stats().chunks > 0);
+        per_cpu.clean();
+        assert_eq!(per_cpu.stats(), crate::RingStats::default());
+    }
+
+    /// `OneRingAlloc::single_thread` may be called from one thread only,
+    /// so this is the only test that uses it.
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "multiple threads"))]
+    fn test_single_thread() {
+        // Safety: no other test calls `single_thread`.
+        let alloc = unsafe { OneRingAlloc::single_thread() };
+
+        let mut v = Vec::new_in(alloc);
+        v.extend((0..10000u64).map(|i| Box::new_in(i, alloc)));
+        assert!(v.iter().enumerate().all(|(i, b)| **b == i as u64));
+
+        // Vector buffer and `big` are oversized and not in the report.
+        let big = Box::new_in([1u8; 100000], alloc);
+        assert_eq!(alloc.memory_report().live_blocks, 10000);
+        drop(v);
+        drop(big);
+
+        // Chunks are not shared with `OneRingAlloc`.
+        let stats = OneRingAlloc.thread_stats();
+        alloc.clean();
+        assert_eq!(alloc.memory_report().chunks(), 0);
+        assert_eq!(OneRingAlloc.thread_stats(), stats);
+
+        // Debug builds catch calls from another thread.
+        if cfg!(debug_assertions) {
+            let other = std::thread::spawn(|| {
+                // Safety: rings are not used, the call panics.
+                let _ = unsafe { OneRingAlloc::single_thread() };
+            });
+            if let Err(panic) = other.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "mmap", unix, not(loom)))]
+mod mmap {
+    use core::ptr::NonNull;
+    use std::fs::{self, OpenOptions};
+
+    use crate::{MmapFileAlloc, RingAlloc};
+    use allocator_api2::vec::Vec;
+
+    #[test]
+    fn test_mmap_file_alloc() {
+        let path = std::env::temp_dir().join(format!("ring-alloc-mmap-{}", std::process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        let mmap = MmapFileAlloc::new(file).unwrap();
+        let file_len = || mmap.file().metadata().unwrap().len();
+
+        {
+            let alloc = RingAlloc::new_in(&mmap);
+
+            let mut small = Vec::new_in(&alloc);
+            small.extend_from_slice(b"persistent ring");
+
+            // Oversized block goes to the file directly.
+            let mut large = Vec::new_in(&alloc);
+            large.extend((0..100000u32).map(|i| i as u8));
+
+            let small_offset = mmap.offset_of(NonNull::from(&small[0])).unwrap() as usize;
+            let large_offset = mmap.offset_of(NonNull::from(&large[0])).unwrap() as usize;
+            assert_eq!(mmap.offset_of(NonNull::from(&0u8)), None);
+
+            mmap.flush().unwrap();
+
+            let bytes = fs::read(&path).unwrap();
+            assert_eq!(&bytes[small_offset..][..small.len()], &small[..]);
+            assert_eq!(&bytes[large_offset..][..large.len()], &large[..]);
+        }
+
+        // Regions of freed blocks are reused, file doesn't grow.
+        let len = file_len();
+        {
+            let alloc = RingAlloc::new_in(&mmap);
+            let mut v = Vec::new_in(&alloc);
+            v.extend_from_slice(b"reused");
+            assert_eq!(&v[..], b"reused");
+        }
+        assert_eq!(file_len(), len);
+
+        drop(mmap);
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+/// With `nightly` feature `allocator_api2` re-exports `core::alloc::Allocator`,
+/// so allocators work with std collections through the same impls.
+#[cfg(all(feature = "nightly", feature = "std", not(loom)))]
+mod nightly {
+    use std::{boxed::Box, vec::Vec};
+
+    use crate::{OneRingAlloc, RingAlloc};
+
+    #[test]
+    fn test_std_collections() {
+        let alloc = RingAlloc::new();
+
+        let b = Box::new_in(42u32, &alloc);
+        let mut v = Vec::new_in(alloc.clone());
+        v.extend(0..100u32);
+
+        assert_eq!(*b, 42);
+        assert_eq!(v.iter().sum::<u32>(), 4950);
+
+        let b = Box::new_in(7u64, OneRingAlloc);
+        let mut v = Vec::new_in(OneRingAlloc);
+        v.extend_from_slice(b"ring");
+
+        assert_eq!(*b, 7);
+        assert_eq!(v, b"ring");
+    }
+}
+
+/// Model-checked tests for global rings.
+///
+/// Run with `RUSTFLAGS="--cfg loom" cargo test --release loom`.
+///
+/// `loom` runs thread-local destructors after the thread is joined
+/// and after lazy statics are dropped for the main thread.
+/// So allocator is used only from spawned threads
+/// that run thread-exit routine explicitly.
+#[cfg(all(feature = "std", loom))]
+mod loom {
+    use crate::OneRingAlloc;
+
+    use allocator_api2::boxed::Box;
+    use loom::thread;
+
+    #[test]
+    fn loom_cross_thread_deallocate() {
+        loom::model(|| {
+            thread::spawn(|| {
+                let b = Box::new_in(1u32, OneRingAlloc);
+
+                let t = thread::spawn(move || drop(b));
+
+                // Allocate from the same chunk while it is freed by another thread.
+                let c = Box::new_in(2u32, OneRingAlloc);
+                t.join().unwrap();
+
+                assert_eq!(*c, 2);
+                drop(c);
+                OneRingAlloc.unregister_thread();
+            })
+            .join()
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_flush_and_steal() {
+        loom::model(|| {
+            // Chunk with live block is flushed to global ring on thread exit.
+            let b = thread::spawn(|| {
+                let b = Box::new_in(1u32, OneRingAlloc);
+                OneRingAlloc.unregister_thread();
+                b
+            })
+            .join()
+            .unwrap();
+
+            let t1 = thread::spawn(move || {
+                assert_eq!(*b, 1);
+                drop(b);
+            });
+
+            // Steal global ring while block is freed by another thread.
+            let t2 = thread::spawn(|| {
+                let c = Box::new_in(2u32, OneRingAlloc);
+                assert_eq!(*c, 2);
+                drop(c);
+                OneRingAlloc.unregister_thread();
+            });
+
+            t1.join().unwrap();
+            t2.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_padding_races_with_deallocate() {
+        loom::model(|| {
+            thread::spawn(|| {
+                let a = Box::new_in(1u8, OneRingAlloc);
+                let t = thread::spawn(move || drop(a));
+
+                // Cursor is not aligned for `u64`,
+                // so padding is accounted while block is freed by another thread.
+                let b = Box::new_in(2u64, OneRingAlloc);
+                t.join().unwrap();
+                drop(b);
+
+                // No update of `freed` is lost, so chunk is unused.
+                assert_eq!(OneRingAlloc.thread_stats().live, 0);
+                OneRingAlloc.clean_local();
+                assert_eq!(OneRingAlloc.thread_stats().chunks, 0);
+                OneRingAlloc.unregister_thread();
+            })
+            .join()
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_concurrent_flush() {
+        loom::model(|| {
+            let t1 = thread::spawn(|| {
+                let b = Box::new_in(1u32, OneRingAlloc);
+                OneRingAlloc.unregister_thread();
+                b
+            });
+            let t2 = thread::spawn(|| {
+                let b = Box::new_in(2u32, OneRingAlloc);
+                OneRingAlloc.unregister_thread();
+                b
+            });
+
+            let b1 = t1.join().unwrap();
+            let b2 = t2.join().unwrap();
+
+            assert_eq!(*b1, 1);
+            assert_eq!(*b2, 2);
+        });
     }
 }
@@ -0,0 +1,369 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::{size_of, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use allocator_api2::alloc::{AllocError, Allocator, Layout};
+
+use crate::RingAlloc;
+
+#[cfg(not(feature = "alloc"))]
+macro_rules! arena_vec {
+    ($(#[$meta:meta])* pub struct $arena_vec:ident;) => {
+        $(#[$meta])*
+        pub struct $arena_vec<T, A: Allocator> {
+            ptr: NonNull<T>,
+            len: usize,
+            cap: usize,
+            alloc: RingAlloc<A>,
+            marker: PhantomData<T>,
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! arena_vec {
+    ($(#[$meta:meta])* pub struct $arena_vec:ident;) => {
+        $(#[$meta])*
+        pub struct $arena_vec<T, A: Allocator = allocator_api2::alloc::Global> {
+            ptr: NonNull<T>,
+            len: usize,
+            cap: usize,
+            alloc: RingAlloc<A>,
+            marker: PhantomData<T>,
+        }
+    };
+}
+
+arena_vec! {
+    /// Growable array allocated with [`RingAlloc`].
+    ///
+    /// Unlike `Vec` with generic allocator it is aware that ring-allocator
+    /// is a bump allocator and grows its buffer in place when buffer
+    /// is the last block allocated from the chunk.
+    /// When vector is built incrementally in a fresh arena
+    /// elements are not copied on growth, as long as buffer stays in the same size class
+    /// and nothing else is allocated from the chunk in between.
+    pub struct ArenaVec;
+}
+
+#[cfg(feature = "alloc")]
+impl<T> ArenaVec<T> {
+    /// Returns new empty [`ArenaVec`] that uses new [`RingAlloc`] with [`Global`] allocator.
+    ///
+    /// [`Global`]: allocator_api2::alloc::Global
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn new() -> Self {
+        ArenaVec::new_in(RingAlloc::new())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg(not(no_global_oom_handling))]
+impl<T> Default for ArenaVec<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        ArenaVec::new()
+    }
+}
+
+impl<T, A> ArenaVec<T, A>
+where
+    A: Allocator,
+{
+    const IS_ZST: bool = size_of::<T>() == 0;
+
+    /// Returns new empty [`ArenaVec`] that uses given ring-allocator.
+    /// Does not allocate.
+    #[inline(always)]
+    pub fn new_in(alloc: RingAlloc<A>) -> Self {
+        ArenaVec {
+            ptr: NonNull::dangling(),
+            len: 0,
+            cap: if Self::IS_ZST { usize::MAX } else { 0 },
+            alloc,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns new empty [`ArenaVec`] with at least specified capacity
+    /// that uses given ring-allocator.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn with_capacity_in(capacity: usize, alloc: RingAlloc<A>) -> Self {
+        let mut vec = ArenaVec::new_in(alloc);
+        vec.reserve(capacity);
+        vec
+    }
+
+    /// Returns number of elements in the vector.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if vector contains no elements.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns number of elements vector can hold without reallocation.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Returns ring-allocator used by this vector.
+    #[inline(always)]
+    pub fn allocator(&self) -> &RingAlloc<A> {
+        &self.alloc
+    }
+
+    /// Returns raw pointer to the vector's buffer.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns slice of all elements.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[T] {
+        // Safety: `len` elements are initialized.
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Returns mutable slice of all elements.
+    #[inline(always)]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // Safety: `len` elements are initialized.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// Buffer is grown in place if possible.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(AllocError) = self.try_reserve(additional) {
+            capacity_overflow_or_oom::<T>(self.len.saturating_add(additional));
+        }
+    }
+
+    /// Attempts to reserve capacity for at least `additional` more elements.
+    ///
+    /// Buffer is grown in place if possible.
+    #[inline(always)]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let required = self.len.checked_add(additional).ok_or(AllocError)?;
+        if required <= self.cap {
+            return Ok(());
+        }
+        self.grow(required)
+    }
+
+    #[cold]
+    fn grow(&mut self, required: usize) -> Result<(), AllocError> {
+        debug_assert!(!Self::IS_ZST);
+
+        let new_cap = required.max(self.cap.saturating_mul(2)).max(4);
+        let new_layout = Layout::array::<T>(new_cap).map_err(|_| AllocError)?;
+
+        if self.cap > 0 {
+            // Safety: current buffer is allocated with this layout.
+            let old_layout = unsafe { Layout::array::<T>(self.cap).unwrap_unchecked() };
+
+            // Safety: `self.ptr` is allocated from `self.alloc` with `old_layout`.
+            if unsafe {
+                self.alloc
+                    .try_grow_in_place(self.ptr.cast(), old_layout, new_layout)
+            } {
+                self.cap = new_cap;
+                return Ok(());
+            }
+
+            // Try to grow exactly to required capacity before moving.
+            if new_cap > required {
+                let required_layout = Layout::array::<T>(required).map_err(|_| AllocError)?;
+
+                // Safety: `self.ptr` is allocated from `self.alloc` with `old_layout`.
+                if unsafe {
+                    self.alloc
+                        .try_grow_in_place(self.ptr.cast(), old_layout, required_layout)
+                } {
+                    self.cap = required;
+                    return Ok(());
+                }
+            }
+        }
+
+        let new_ptr = self.alloc.allocate(new_layout)?.cast::<T>();
+
+        if self.cap > 0 {
+            // Safety: current buffer is allocated with this layout.
+            let old_layout = unsafe { Layout::array::<T>(self.cap).unwrap_unchecked() };
+
+            // Safety: buffers do not overlap and new one is larger.
+            unsafe {
+                core::ptr::copy_nonoverlapping(self.ptr.as_ptr(), new_ptr.as_ptr(), self.len);
+                self.alloc.deallocate(self.ptr.cast(), old_layout);
+            }
+        }
+
+        self.ptr = new_ptr;
+        self.cap = new_cap;
+        Ok(())
+    }
+
+    /// Appends an element to the back of the vector.
+    #[cfg(not(no_global_oom_handling))]
+    #[inline(always)]
+    pub fn push(&mut self, value: T) {
+        if self.len == self.cap {
+            self.reserve(1);
+        }
+
+        // Safety: capacity is greater than length.
+        unsafe {
+            self.ptr.as_ptr().add(self.len).write(value);
+        }
+        self.len += 1;
+    }
+
+    /// Removes the last element from the vector and returns it,
+    /// or `None` if vector is empty.
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+
+        // Safety: element at `len` is initialized and is not accessible anymore.
+        Some(unsafe { self.ptr.as_ptr().add(self.len).read() })
+    }
+
+    /// Shortens the vector, dropping excess elements.
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = core::ptr::slice_from_raw_parts_mut(
+            // Safety: `len` is less than `self.len`.
+            unsafe { self.ptr.as_ptr().add(len) },
+            self.len - len,
+        );
+        self.len = len;
+
+        // Safety: elements in `tail` are initialized and not accessible anymore.
+        unsafe {
+            core::ptr::drop_in_place(tail);
+        }
+    }
+
+    /// Removes all elements from the vector.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Drops vector's elements, deallocates the buffer
+    /// and returns ring-allocator.
+    #[inline(always)]
+    pub fn into_allocator(self) -> RingAlloc<A> {
+        let mut me = ManuallyDrop::new(self);
+        me.clear();
+        me.free_buffer();
+
+        // Safety: `me` is not used after this point.
+        unsafe { core::ptr::read(&me.alloc) }
+    }
+
+    #[inline(always)]
+    fn free_buffer(&mut self) {
+        if !Self::IS_ZST && self.cap > 0 {
+            // Safety: current buffer is allocated with this layout.
+            unsafe {
+                let layout = Layout::array::<T>(self.cap).unwrap_unchecked();
+                self.alloc.deallocate(self.ptr.cast(), layout);
+            }
+        }
+    }
+}
+
+#[cfg(not(no_global_oom_handling))]
+#[cold]
+fn capacity_overflow_or_oom<T>(capacity: usize) -> ! {
+    match Layout::array::<T>(capacity) {
+        #[cfg(feature = "alloc")]
+        Ok(layout) => alloc::alloc::handle_alloc_error(layout),
+        #[cfg(not(feature = "alloc"))]
+        Ok(_) => panic!("Failed to allocate ArenaVec buffer"),
+        Err(_) => panic!("ArenaVec capacity overflow"),
+    }
+}
+
+impl<T, A> Drop for ArenaVec<T, A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.clear();
+        self.free_buffer();
+    }
+}
+
+impl<T, A> Deref for ArenaVec<T, A>
+where
+    A: Allocator,
+{
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A> DerefMut for ArenaVec<T, A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A> fmt::Debug for ArenaVec<T, A>
+where
+    T: fmt::Debug,
+    A: Allocator,
+{
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(not(no_global_oom_handling))]
+impl<T, A> Extend<T> for ArenaVec<T, A>
+where
+    A: Allocator,
+{
+    #[inline(always)]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Runs the whole test binary with `OneRingAlloc` as the global allocator.
+//! Test harness and `std` allocate before and between tests,
+//! including while thread-local rings are initialized.
+
+#![cfg(all(feature = "std", not(loom)))]
+
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use ring_alloc::OneRingAlloc;
+
+#[global_allocator]
+static GLOBAL: OneRingAlloc = OneRingAlloc;
+
+fn churn(seed: usize) -> usize {
+    let mut map = BTreeMap::new();
+    let mut total = 0;
+
+    for i in 0..1000 {
+        let n = (i * 7 + seed) % 300;
+        let v: Vec<u8> = vec![i as u8; n];
+        let s = format!("{seed}-{i}-{}", v.len());
+        map.insert(s, v.into_boxed_slice());
+
+        if i % 3 == 0 {
+            map.pop_first();
+        }
+    }
+
+    // Large and oversized blocks.
+    for size in [1000, 60000, 300000, 3000000] {
+        let v = vec![seed as u8; size];
+        total += v.len();
+    }
+
+    total + map.values().map(|v| v.len()).sum::<usize>()
+}
+
+#[test]
+fn test_global_allocator() {
+    let main = churn(0);
+
+    let threads: Vec<_> = (1..9)
+        .map(|seed| thread::spawn(move || churn(seed)))
+        .collect();
+
+    // Blocks allocated by other threads are freed here.
+    let boxes: Vec<Box<[u8]>> = (0..8)
+        .map(|i| thread::spawn(move || vec![i; 100].into_boxed_slice()))
+        .map(|t| t.join().unwrap())
+        .collect();
+
+    for t in threads {
+        assert!(t.join().unwrap() > 0);
+    }
+
+    assert!(main > 0);
+    // Allocations of this thread are served by its rings.
+    assert!(OneRingAlloc.thread_stats().chunks > 0);
+    drop(boxes);
+
+    OneRingAlloc.clean_global();
+}
+
+/// Sum of the vector allocated by [`AllocOnDrop`].
+static DROP_SUM: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocates in destructor, which may run after thread-local rings are destroyed.
+struct AllocOnDrop;
+
+impl Drop for AllocOnDrop {
+    fn drop(&mut self) {
+        let v = vec![1; 100];
+        DROP_SUM.store(v.iter().sum(), Ordering::Relaxed);
+    }
+}
+
+thread_local! {
+    static ALLOC_ON_DROP: AllocOnDrop = const { AllocOnDrop };
+}
+
+#[test]
+fn test_alloc_in_thread_local_destructor() {
+    thread::spawn(|| {
+        // Registered before thread-local rings, so destroyed after them.
+        ALLOC_ON_DROP.with(|_| {});
+        drop(vec![0u8; 10]);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(DROP_SUM.load(Ordering::Relaxed), 100);
+}